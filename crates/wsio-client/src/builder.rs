@@ -20,7 +20,19 @@ use url::Url;
 use crate::{
     WsIoClient,
     config::WsIoClientConfig,
-    core::packet::codecs::WsIoPacketCodec,
+    core::{
+        error::{
+            WsIoError,
+            WsIoProtocolViolationPolicy,
+            connect::{
+                WsIoConnectError,
+                WsIoConnectErrorAction,
+            },
+        },
+        event::execution::WsIoHandlerExecutionMode,
+        packet::codecs::WsIoPacketCodec,
+        traits::packet::codec::PacketCodec,
+    },
     runtime::WsIoClientRuntime,
     session::WsIoClientSession,
 };
@@ -56,15 +68,22 @@ impl WsIoClientBuilder {
         url.set_path("ws.io");
         Ok(Self {
             config: WsIoClientConfig {
+                ack_timeout: Duration::from_secs(10),
+                compression_threshold: None,
                 disconnect_timeout: Duration::from_secs(5),
+                error_handler: None,
+                event_handler_timeout: None,
+                handler_execution_mode: WsIoHandlerExecutionMode::Concurrent,
                 init_handler: None,
                 init_handler_timeout: Duration::from_secs(3),
                 init_packet_timeout: Duration::from_secs(5),
+                on_connect_error_handler: None,
                 on_session_close_handler: None,
                 on_session_close_handler_timeout: Duration::from_secs(2),
                 on_session_ready_handler: None,
-                packet_codec: WsIoPacketCodec::SerdeJson,
+                packet_codec: Arc::new(WsIoPacketCodec::SerdeJson),
                 ping_interval: Duration::from_secs(25),
+                protocol_violation_policy: WsIoProtocolViolationPolicy::Disconnect,
                 ready_packet_timeout: Duration::from_secs(5),
                 reconnect_delay: Duration::from_secs(1),
                 request_modifier: None,
@@ -80,6 +99,26 @@ impl WsIoClientBuilder {
     }
 
     // Private methods
+
+    /// Returns the connect URL with a `codec` query parameter naming the
+    /// configured packet codec, so namespaces accepting several codecs select
+    /// the matching one.
+    fn connect_url_with_codec(&self) -> Url {
+        let mut url = self.connect_url.clone();
+        let query_pairs = url
+            .query_pairs()
+            .filter(|(k, _)| k != "codec")
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect::<Vec<_>>();
+
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(query_pairs)
+            .append_pair("codec", self.config.packet_codec.name());
+
+        url
+    }
+
     fn normalize_url_path(path: &str) -> String {
         format!(
             "/{}",
@@ -89,9 +128,30 @@ impl WsIoClientBuilder {
 
     // Public methods
 
+    /// Sets how long `emit_with_ack` waits for the server acknowledgement.
+    ///
+    /// The timeout starts when the event is queued, so it also covers waiting
+    /// for a ready session while the client is reconnecting.
+    pub fn ack_timeout(mut self, duration: Duration) -> Self {
+        self.config.ack_timeout = duration;
+        self
+    }
+
     /// Builds a [`WsIoClient`] with the accumulated configuration.
     pub fn build(self) -> WsIoClient {
-        WsIoClient(WsIoClientRuntime::new(self.config, self.connect_url))
+        let connect_url = self.connect_url_with_codec();
+        WsIoClient(WsIoClientRuntime::new(self.config, connect_url))
+    }
+
+    /// Deflates packets of at least `bytes` encoded bytes exchanged with the
+    /// server.
+    ///
+    /// The `COMPRESSION` capability is advertised during the handshake and
+    /// compression only applies when the namespace enables it as well. Unset by
+    /// default.
+    pub fn compression_threshold(mut self, bytes: usize) -> Self {
+        self.config.compression_threshold = Some(bytes);
+        self
     }
 
     /// Sets how long `disconnect().await` waits for graceful WebSocket
@@ -101,6 +161,23 @@ impl WsIoClientBuilder {
         self
     }
 
+    /// Sets the maximum duration a handler of an event received from the server
+    /// may run.
+    ///
+    /// A handler still running after `duration` is dropped, fails its ack with
+    /// a timeout error and is reported to the `on_error` hook as timed out.
+    /// Unset by default.
+    pub fn event_handler_timeout(mut self, duration: Duration) -> Self {
+        self.config.event_handler_timeout = Some(duration);
+        self
+    }
+
+    /// Sets how the handlers of events received from the server run.
+    pub fn handler_execution_mode(mut self, mode: WsIoHandlerExecutionMode) -> Self {
+        self.config.handler_execution_mode = mode;
+        self
+    }
+
     /// Sets the maximum duration allowed for the init handler to run.
     ///
     /// The init handler is registered with [`Self::with_init_handler`] and is
@@ -120,6 +197,32 @@ impl WsIoClientBuilder {
         self
     }
 
+    /// Registers a handler that runs when the server rejects the connection
+    /// during its handshake, such as when middleware refuses its credentials.
+    ///
+    /// Return [`WsIoConnectErrorAction::Stop`] to stop the client instead of
+    /// reconnecting after [`Self::reconnect_delay`].
+    pub fn on_connect_error<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(&WsIoConnectError) -> WsIoConnectErrorAction + Send + Sync + 'static,
+    {
+        self.config.on_connect_error_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Registers a hook receiving errors raised while handling packets from
+    /// the server.
+    ///
+    /// The hook is called synchronously from the task the error happened on, so
+    /// it should hand off any slow work.
+    pub fn on_error<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(WsIoError) + Send + Sync + 'static,
+    {
+        self.config.error_handler = Some(Box::new(handler));
+        self
+    }
+
     /// Registers a handler that runs when a session closes.
     ///
     /// The handler is awaited during session cleanup and is bounded by
@@ -154,9 +257,11 @@ impl WsIoClientBuilder {
 
     /// Sets the packet codec used to encode and decode ws.io protocol packets.
     ///
-    /// This must match the server namespace codec.
-    pub fn packet_codec(mut self, packet_codec: WsIoPacketCodec) -> Self {
-        self.config.packet_codec = packet_codec;
+    /// The codec name is sent in the `codec` query parameter, and the server
+    /// namespace must accept a codec with that name. Accepts the built-in
+    /// [`WsIoPacketCodec`] or any custom [`PacketCodec`] implementation.
+    pub fn packet_codec<C: PacketCodec>(mut self, packet_codec: C) -> Self {
+        self.config.packet_codec = Arc::new(packet_codec);
         self
     }
 
@@ -170,6 +275,16 @@ impl WsIoClientBuilder {
         self
     }
 
+    /// Sets the policy for a server that breaks the ws.io protocol.
+    ///
+    /// With [`WsIoProtocolViolationPolicy::Disconnect`] the session is closed
+    /// with a protocol error close frame; with
+    /// [`WsIoProtocolViolationPolicy::Report`] the offending packet is dropped.
+    pub fn protocol_violation_policy(mut self, policy: WsIoProtocolViolationPolicy) -> Self {
+        self.config.protocol_violation_policy = policy;
+        self
+    }
+
     /// Sets how long the client waits for the server ready packet.
     ///
     /// The ready timeout starts after the client handles the server init packet
@@ -248,10 +363,13 @@ impl WsIoClientBuilder {
         self.config.init_handler = Some(Box::new(move |session, bytes, packet_codec| {
             let handler = handler.clone();
             Box::pin(async move {
-                handler(session, bytes.map(|bytes| packet_codec.decode_data(bytes)).transpose()?)
-                    .await?
-                    .map(|data| packet_codec.encode_data(&data))
-                    .transpose()
+                handler(
+                    session,
+                    bytes.map(|bytes| packet_codec.decode_data_as(bytes)).transpose()?,
+                )
+                .await?
+                .map(|data| packet_codec.encode_data(&data))
+                .transpose()
             })
         }));
 
@@ -305,12 +423,19 @@ mod tests {
     #[test]
     fn test_builder_configuration_chaining_updates_runtime_config() {
         let builder = test_builder()
+            .ack_timeout(Duration::from_secs(7))
+            .compression_threshold(1024)
             .disconnect_timeout(Duration::from_secs(20))
+            .event_handler_timeout(Duration::from_secs(8))
+            .handler_execution_mode(WsIoHandlerExecutionMode::Sequential)
             .init_handler_timeout(Duration::from_secs(10))
             .init_packet_timeout(Duration::from_secs(15))
+            .on_connect_error(|_error| WsIoConnectErrorAction::Stop)
+            .on_error(|_error| {})
             .on_session_close_handler_timeout(Duration::from_secs(5))
             .packet_codec(WsIoPacketCodec::SerdeJson)
             .ping_interval(Duration::from_secs(30))
+            .protocol_violation_policy(WsIoProtocolViolationPolicy::Report)
             .ready_packet_timeout(Duration::from_secs(10))
             .reconnect_delay(Duration::from_secs(5))
             .request_path("/custom/path");
@@ -320,16 +445,45 @@ mod tests {
         let client = builder.build();
 
         let config = &client.0.config;
+        assert_eq!(config.ack_timeout, Duration::from_secs(7));
+        assert_eq!(config.compression_threshold, Some(1024));
         assert_eq!(config.disconnect_timeout, Duration::from_secs(20));
+        assert!(config.error_handler.is_some());
+        assert!(config.on_connect_error_handler.is_some());
+        assert_eq!(config.event_handler_timeout, Some(Duration::from_secs(8)));
+        assert_eq!(config.handler_execution_mode, WsIoHandlerExecutionMode::Sequential);
         assert_eq!(config.init_handler_timeout, Duration::from_secs(10));
         assert_eq!(config.init_packet_timeout, Duration::from_secs(15));
         assert_eq!(config.on_session_close_handler_timeout, Duration::from_secs(5));
-        assert!(matches!(config.packet_codec, WsIoPacketCodec::SerdeJson));
+        assert_eq!(format!("{:?}", config.packet_codec), "SerdeJson");
+        assert!(config.packet_codec.is_text());
         assert_eq!(config.ping_interval, Duration::from_secs(30));
+        assert_eq!(config.protocol_violation_policy, WsIoProtocolViolationPolicy::Report);
         assert_eq!(config.ready_packet_timeout, Duration::from_secs(10));
         assert_eq!(config.reconnect_delay, Duration::from_secs(5));
     }
 
+    #[test]
+    fn test_builder_connect_url_requests_configured_codec() {
+        let builder = WsIoClientBuilder::new(Url::parse("ws://localhost:8080/socket?codec=cbor&token=abc").unwrap())
+            .unwrap()
+            .packet_codec(WsIoPacketCodec::SerdeJson);
+
+        let connect_url = builder.connect_url_with_codec();
+        let codecs = connect_url
+            .query_pairs()
+            .filter(|(key, _)| key == "codec")
+            .map(|(_, value)| value.into_owned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(codecs, ["json"]);
+        assert!(
+            connect_url
+                .query_pairs()
+                .any(|(key, value)| key == "token" && value == "abc")
+        );
+    }
+
     #[test]
     fn test_builder_request_path_normalizes() {
         let builder = test_builder().request_path("/multiple//slashes///path/");
@@ -17,21 +17,32 @@ use tokio_tungstenite::tungstenite::{
 
 use crate::{
     core::{
-        packet::codecs::WsIoPacketCodec,
+        error::{
+            WsIoProtocolViolationPolicy,
+            connect::{
+                WsIoConnectError,
+                WsIoConnectErrorAction,
+            },
+        },
+        event::execution::WsIoHandlerExecutionMode,
+        traits::packet::codec::PacketCodec,
         types::{
             ArcAsyncUnaryResultHandler,
             BoxAsyncUnaryResultHandler,
+            BoxErrorHandler,
         },
     },
     session::WsIoClientSession,
 };
 
 // Types
+type ConnectErrorHandler = Box<dyn Fn(&WsIoConnectError) -> WsIoConnectErrorAction + Send + Sync + 'static>;
+
 type InitHandler = Box<
     dyn for<'a> Fn(
             Arc<WsIoClientSession>,
             Option<&'a [u8]>,
-            &'a WsIoPacketCodec,
+            &'a dyn PacketCodec,
         ) -> Pin<Box<dyn Future<Output = Result<Option<Vec<u8>>>> + Send + 'a>>
         + Send
         + Sync
@@ -43,6 +54,20 @@ type RequestModifier =
 
 // Structs
 pub(crate) struct WsIoClientConfig {
+    /// Maximum duration to wait for the server to acknowledge an event emitted
+    /// with `emit_with_ack`.
+    ///
+    /// The timeout starts when the event is queued, so it also covers the time
+    /// spent waiting for a ready session.
+    pub(crate) ack_timeout: Duration,
+
+    /// Minimum encoded size in bytes from which packets are deflated, or
+    /// `None` to leave compression off.
+    ///
+    /// When set, the `COMPRESSION` capability is advertised during the
+    /// handshake and only takes effect with namespaces that enable it too.
+    pub(crate) compression_threshold: Option<usize>,
+
     /// Maximum duration to wait for graceful WebSocket shutdown after
     /// `disconnect` is requested.
     ///
@@ -50,6 +75,29 @@ pub(crate) struct WsIoClientConfig {
     /// aborted so `disconnect().await` can complete.
     pub(crate) disconnect_timeout: Duration,
 
+    /// Optional hook receiving errors raised while handling packets from the
+    /// server.
+    ///
+    /// It is called with decode failures, handler errors and panics, lifecycle
+    /// handler timeouts and protocol violations, tagged with the event name
+    /// when known. Without it these errors are dropped.
+    pub(crate) error_handler: Option<BoxErrorHandler>,
+
+    /// Maximum duration a handler of an event received from the server may run
+    /// before it is cut off and reported as timed out.
+    ///
+    /// `None` lets handlers run until they finish or the session closes.
+    /// Individual events can get their own timeout with
+    /// `WsIoClient::set_handler_timeout`.
+    pub(crate) event_handler_timeout: Option<Duration>,
+
+    /// How the handlers of events received from the server run.
+    ///
+    /// Sequential and bounded modes keep their order and limit across
+    /// reconnects. Individual events can be switched with
+    /// `WsIoClient::set_execution_mode`.
+    pub(crate) handler_execution_mode: WsIoHandlerExecutionMode,
+
     /// Optional client-side init handler used during the server handshake.
     ///
     /// When the server sends an init packet, this handler receives the optional
@@ -66,6 +114,13 @@ pub(crate) struct WsIoClientConfig {
     /// init packet is not received in time, the session setup fails.
     pub(crate) init_packet_timeout: Duration,
 
+    /// Optional handler invoked when the server rejects the connection during
+    /// its handshake.
+    ///
+    /// Its return value decides whether the client keeps reconnecting. Without
+    /// it the client always reconnects.
+    pub(crate) on_connect_error_handler: Option<ConnectErrorHandler>,
+
     /// Optional handler invoked when a session closes.
     ///
     /// The handler is awaited with `on_session_close_handler_timeout`.
@@ -84,7 +139,7 @@ pub(crate) struct WsIoClientConfig {
     ///
     /// It must match the server namespace codec and controls whether encoded
     /// packets use WebSocket text or binary messages.
-    pub(crate) packet_codec: WsIoPacketCodec,
+    pub(crate) packet_codec: Arc<dyn PacketCodec>,
 
     /// Interval between client heartbeat frames sent after the WebSocket session
     /// is created.
@@ -93,6 +148,12 @@ pub(crate) struct WsIoClientConfig {
     /// single-byte binary frames before protocol packet decoding.
     pub(crate) ping_interval: Duration,
 
+    /// What a session does when the server breaks the ws.io protocol.
+    ///
+    /// Under the disconnect policy the session is closed and the client
+    /// reconnects as after any other disconnect.
+    pub(crate) protocol_violation_policy: WsIoProtocolViolationPolicy,
+
     /// Maximum duration to wait for the ready packet from the server.
     ///
     /// The client sends its init response first, then waits for the server to mark
@@ -118,10 +179,19 @@ pub(crate) struct WsIoClientConfig {
 impl FmtDebug for WsIoClientConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("WsIoClientConfig")
+            .field("ack_timeout", &self.ack_timeout)
+            .field("compression_threshold", &self.compression_threshold)
             .field("disconnect_timeout", &self.disconnect_timeout)
+            .field("error_handler", &self.error_handler.as_ref().map(|_| "<handler>"))
+            .field("event_handler_timeout", &self.event_handler_timeout)
+            .field("handler_execution_mode", &self.handler_execution_mode)
             .field("init_handler", &self.init_handler.as_ref().map(|_| "<handler>"))
             .field("init_handler_timeout", &self.init_handler_timeout)
             .field("init_packet_timeout", &self.init_packet_timeout)
+            .field(
+                "on_connect_error_handler",
+                &self.on_connect_error_handler.as_ref().map(|_| "<handler>"),
+            )
             .field(
                 "on_session_close_handler",
                 &self.on_session_close_handler.as_ref().map(|_| "<handler>"),
@@ -136,6 +206,7 @@ impl FmtDebug for WsIoClientConfig {
             )
             .field("packet_codec", &self.packet_codec)
             .field("ping_interval", &self.ping_interval)
+            .field("protocol_violation_policy", &self.protocol_violation_policy)
             .field("ready_packet_timeout", &self.ready_packet_timeout)
             .field("reconnect_delay", &self.reconnect_delay)
             .field(
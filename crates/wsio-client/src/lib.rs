@@ -3,6 +3,7 @@
 use std::{
     fmt::Display,
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{
@@ -24,7 +25,19 @@ pub mod session;
 
 use crate::{
     builder::WsIoClientBuilder,
-    core::traits::task::spawner::TaskSpawner,
+    core::{
+        event::{
+            execution::WsIoHandlerExecutionMode,
+            raw::WsIoRawEvent,
+        },
+        traits::{
+            event::{
+                definition::WsIoEvent,
+                extract::EventHandler,
+            },
+            task::spawner::TaskSpawner,
+        },
+    },
     runtime::WsIoClientRuntime,
     session::WsIoClientSession,
 };
@@ -74,11 +87,44 @@ impl WsIoClient {
         self.0.emit(event.as_ref(), data).await
     }
 
+    /// Emits `E` with a payload whose type is checked against its definition.
+    pub async fn emit_typed<E: WsIoEvent>(&self, data: &E::Data) -> Result<()> {
+        self.0.emit(E::NAME, Some(data)).await
+    }
+
+    /// Emits an event and waits for the server to acknowledge it.
+    ///
+    /// The server's ack handler reply is decoded as `R`. Fails when the ack
+    /// handler fails, the configured `ack_timeout` elapses or the client
+    /// disconnects first.
+    pub async fn emit_with_ack<D: Serialize, R: DeserializeOwned>(
+        &self,
+        event: impl AsRef<str>,
+        data: Option<&D>,
+    ) -> Result<R> {
+        self.0.emit_with_ack(event.as_ref(), data).await
+    }
+
     #[inline]
     pub fn is_session_ready(&self) -> bool {
         self.0.is_session_ready()
     }
 
+    /// Whether the current session recovered the server-side state of the
+    /// previous one after a reconnect; see
+    /// [`WsIoClientSession::is_recovered`].
+    #[inline]
+    pub fn is_session_recovered(&self) -> bool {
+        self.0.is_session_recovered()
+    }
+
+    /// Round-trip time measured by the current session, or `None` while
+    /// disconnected or before a ping has been answered.
+    #[inline]
+    pub fn latency(&self) -> Option<Duration> {
+        self.0.latency()
+    }
+
     #[inline]
     pub fn off(&self, event: impl AsRef<str>) {
         self.0.off(event.as_ref());
@@ -89,6 +135,13 @@ impl WsIoClient {
         self.0.off_by_handler_id(event.as_ref(), handler_id);
     }
 
+    /// Removes every pattern handler registered with exactly `pattern`,
+    /// including `on_any` handlers when `pattern` is `*`.
+    #[inline]
+    pub fn off_pattern(&self, pattern: impl AsRef<str>) {
+        self.0.off_pattern(pattern.as_ref());
+    }
+
     #[inline]
     pub fn on<H, Fut, D>(&self, event: impl AsRef<str>, handler: H) -> u32
     where
@@ -99,6 +152,109 @@ impl WsIoClient {
         self.0.on(event.as_ref(), handler)
     }
 
+    /// Registers a handler that receives every event with its raw payload.
+    ///
+    /// Same as `on_pattern("*", handler)`.
+    #[inline]
+    pub fn on_any<H, Fut>(&self, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.0.on_pattern("*", handler)
+    }
+
+    /// Registers a handler whose arguments are extracted from the event, in
+    /// any combination and order.
+    ///
+    /// Arguments implement
+    /// [`FromEventContext`](crate::core::traits::event::extract::FromEventContext):
+    /// the session as `Arc<WsIoClientSession>`, the payload as
+    /// [`WsIoData`](crate::core::event::extract::WsIoData), the event name as
+    /// [`WsIoEventName`](crate::core::event::extract::WsIoEventName) and an ack
+    /// responder as [`WsIoAck`](crate::core::event::extract::WsIoAck). A failed
+    /// extraction skips the handler and is reported to the `on_error` hook.
+    #[inline]
+    pub fn on_extract<H, T>(&self, event: impl AsRef<str>, handler: H) -> u32
+    where
+        H: EventHandler<WsIoClientSession, T>,
+    {
+        self.0.on_extract(event.as_ref(), handler)
+    }
+
+    /// Registers a handler for every event whose name matches `pattern`, such
+    /// as `chat:*`.
+    ///
+    /// `*` matches any run of characters. The handler receives the event name and
+    /// its raw payload, runs alongside handlers registered with `on` and never
+    /// replies to acks.
+    #[inline]
+    pub fn on_pattern<H, Fut>(&self, pattern: impl AsRef<str>, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.0.on_pattern(pattern.as_ref(), handler)
+    }
+
+    /// Registers a handler that receives `event` with its payload undecoded,
+    /// together with the packet codec it was encoded with.
+    ///
+    /// Unlike `on`, raw handlers can share an event name with handlers of any
+    /// payload type. Decode the payload into
+    /// [`WsIoValue`](crate::core::value::WsIoValue) to inspect it without a
+    /// concrete type. Raw handlers never reply to acks.
+    #[inline]
+    pub fn on_raw<H, Fut>(&self, event: impl AsRef<str>, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.0.on_raw(event.as_ref(), handler)
+    }
+
+    /// Registers a handler for `E`, decoding its payload into the type of its
+    /// definition.
+    #[inline]
+    pub fn on_typed<E, H, Fut>(&self, handler: H) -> u32
+    where
+        E: WsIoEvent,
+        H: Fn(Arc<WsIoClientSession>, Arc<E::Data>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.0.on(E::NAME, handler)
+    }
+
+    /// Registers an event handler whose return value is sent back to the server
+    /// when the event was emitted with an ack.
+    ///
+    /// A handler that fails answers the ack with an error instead. Each event
+    /// takes a single ack handler; registering a second one panics.
+    #[inline]
+    pub fn on_with_ack<H, Fut, D, R>(&self, event: impl AsRef<str>, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+        D: DeserializeOwned + Send + Sync + 'static,
+        R: Serialize + Send + 'static,
+    {
+        self.0.on_with_ack(event.as_ref(), handler)
+    }
+
+    /// Runs the handlers of `event` in `mode` instead of the client's handler
+    /// execution mode.
+    #[inline]
+    pub fn set_execution_mode(&self, event: impl AsRef<str>, mode: WsIoHandlerExecutionMode) {
+        self.0.set_execution_mode(event.as_ref(), mode);
+    }
+
+    /// Cuts off the handlers of `event` after `duration` instead of the
+    /// client's event handler timeout.
+    #[inline]
+    pub fn set_handler_timeout(&self, event: impl AsRef<str>, duration: Duration) {
+        self.0.set_handler_timeout(event.as_ref(), duration);
+    }
+
     #[inline]
     pub fn spawn_task<F: Future<Output = Result<()>> + Send + 'static>(&self, future: F) {
         self.0.spawn_task(future);
@@ -1,6 +1,12 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::Result;
+use anyhow::{
+    Result,
+    bail,
+};
 use arc_swap::{
     ArcSwap,
     ArcSwapOption,
@@ -41,6 +47,7 @@ use tokio_tungstenite::{
     tungstenite::{
         Message,
         client::IntoClientRequest,
+        http::HeaderValue,
     },
 };
 use tokio_util::sync::CancellationToken;
@@ -50,9 +57,21 @@ use crate::{
     config::WsIoClientConfig,
     core::{
         channel_capacity_from_websocket_config,
-        event::registry::WsIoEventRegistry,
+        event::{
+            ack::WsIoEventAckRegistry,
+            execution::WsIoHandlerExecutionMode,
+            raw::WsIoRawEvent,
+            registry::WsIoEventRegistry,
+        },
         packet::WsIoPacket,
-        traits::task::spawner::TaskSpawner,
+        protocol::{
+            WSIO_SESSION_HEADER,
+            WsIoProtocolCapabilities,
+        },
+        traits::{
+            event::extract::EventHandler,
+            task::spawner::TaskSpawner,
+        },
     },
     session::WsIoClientSession,
 };
@@ -69,12 +88,14 @@ enum RuntimeStatus {
 // Structs
 #[derive(Debug)]
 pub(crate) struct WsIoClientRuntime {
+    pub(crate) ack_registry: WsIoEventAckRegistry,
     cancel_token: ArcSwap<CancellationToken>,
     pub(crate) config: WsIoClientConfig,
     connect_url: Url,
     connection_loop_task: Mutex<Option<JoinHandle<()>>>,
     pub(crate) event_registry: WsIoEventRegistry<WsIoClientSession, WsIoClientRuntime>,
     operate_lock: Mutex<()>,
+    recovery_token: ArcSwapOption<String>,
     send_event_message_rx: Mutex<Receiver<Arc<Message>>>,
     send_event_message_task: Mutex<Option<JoinHandle<()>>>,
     send_event_message_tx: Sender<Arc<Message>>,
@@ -93,14 +114,18 @@ impl TaskSpawner for WsIoClientRuntime {
 impl WsIoClientRuntime {
     pub(crate) fn new(config: WsIoClientConfig, connect_url: Url) -> Arc<Self> {
         let channel_capacity = channel_capacity_from_websocket_config(&config.websocket_config);
+        let event_registry = WsIoEventRegistry::with_execution_mode(config.handler_execution_mode)
+            .with_default_handler_timeout(config.event_handler_timeout);
         let (send_event_message_tx, send_event_message_rx) = channel(channel_capacity);
         Arc::new(Self {
+            ack_registry: WsIoEventAckRegistry::new(),
             cancel_token: ArcSwap::new(Arc::new(CancellationToken::new())),
             config,
             connect_url,
             connection_loop_task: Mutex::new(None),
-            event_registry: WsIoEventRegistry::new(),
+            event_registry,
             operate_lock: Mutex::new(()),
+            recovery_token: ArcSwapOption::new(None),
             send_event_message_rx: Mutex::new(send_event_message_rx),
             send_event_message_task: Mutex::new(None),
             send_event_message_tx,
@@ -112,8 +137,14 @@ impl WsIoClientRuntime {
 
     // Private methods
     async fn run_connection(self: &Arc<Self>) -> Result<()> {
-        // Connect to server
+        // Connect to server, presenting the session token of the previous connection so the server can recover it
         let mut request = self.connect_url.as_str().into_client_request()?;
+        if let Some(recovery_token) = self.recovery_token.load().as_deref() {
+            request
+                .headers_mut()
+                .insert(WSIO_SESSION_HEADER, HeaderValue::from_str(recovery_token)?);
+        }
+
         if let Some(modifier) = &self.config.request_modifier {
             request = modifier(request).await?;
         }
@@ -130,9 +161,9 @@ impl WsIoClientRuntime {
         let mut read_ws_stream_task = spawn(async move {
             while let Some(message) = ws_stream_reader.next().await {
                 if match message {
-                    Ok(Message::Binary(bytes)) => session_clone.handle_incoming_packet(&bytes).await,
+                    Ok(Message::Binary(bytes)) => session_clone.handle_incoming_binary_packet(bytes).await,
                     Ok(Message::Close(_)) | Err(_) => break,
-                    Ok(Message::Text(text)) => session_clone.handle_incoming_packet(text.as_bytes()).await,
+                    Ok(Message::Text(text)) => session_clone.handle_incoming_packet(text.into()).await,
                     _ => Ok(()),
                 }
                 .is_err()
@@ -278,11 +309,17 @@ impl WsIoClientRuntime {
         let mut send_event_message_rx = self.send_event_message_rx.lock().await;
         while send_event_message_rx.try_recv().is_ok() {}
 
+        // Fail all pending acks
+        self.ack_registry.clear();
+
         // Await connection loop task termination
         if let Some(connection_loop_task) = self.connection_loop_task.lock().await.take() {
             let _ = connection_loop_task.await;
         }
 
+        // Start over with a new session on the next connect, since this one was closed on purpose
+        self.recovery_token.store(None);
+
         self.status.store(RuntimeStatus::Stopped);
     }
 
@@ -292,18 +329,52 @@ impl WsIoClientRuntime {
         })?;
 
         self.send_event_message_tx
-            .send(
-                self.encode_packet_to_message(&WsIoPacket::new_event(
-                    event,
-                    data.map(|data| self.config.packet_codec.encode_data(data))
-                        .transpose()?,
-                ))?,
-            )
+            .send(self.encode_packet_to_message(&WsIoPacket::new_event(event, self.encode_event_data(data)?))?)
             .await?;
 
         Ok(())
     }
 
+    pub(crate) async fn emit_with_ack<D: Serialize, R: DeserializeOwned>(
+        &self,
+        event: &str,
+        data: Option<&D>,
+    ) -> Result<R> {
+        self.status.ensure(RuntimeStatus::Running, |status| {
+            format!("Cannot emit in invalid status: {status:?}")
+        })?;
+
+        // Queued events are sent once a session is ready, so only reject when the current server is known to lack acks
+        if let Some(session) = self.session.load().as_ref()
+            && session.is_ready()
+            && !session.protocol_capabilities().contains(WsIoProtocolCapabilities::ACKS)
+        {
+            bail!("Server does not support acks");
+        }
+
+        let packet_data = self.encode_event_data(data)?;
+        let (ack_id, ack_rx) = self.ack_registry.register();
+        let send_result =
+            match self.encode_packet_to_message(&WsIoPacket::new_event_with_ack(event, packet_data, ack_id)) {
+                Ok(message) => self.send_event_message_tx.send(message).await.map_err(Into::into),
+                Err(err) => Err(err),
+            };
+
+        if let Err(err) = send_result {
+            self.ack_registry.remove(ack_id);
+            return Err(err);
+        }
+
+        self.ack_registry
+            .wait(ack_id, ack_rx, self.config.ack_timeout, &*self.config.packet_codec)
+            .await
+    }
+
+    #[inline]
+    pub(crate) fn encode_event_data<D: Serialize>(&self, data: Option<&D>) -> Result<Option<Vec<u8>>> {
+        data.map(|data| self.config.packet_codec.encode_data(data)).transpose()
+    }
+
     #[inline]
     pub(crate) fn encode_packet_to_message(&self, packet: &WsIoPacket) -> Result<Arc<Message>> {
         let bytes = self.config.packet_codec.encode(packet)?;
@@ -319,6 +390,19 @@ impl WsIoClientRuntime {
         self.session.load().as_ref().is_some_and(|session| session.is_ready())
     }
 
+    #[inline]
+    pub(crate) fn is_session_recovered(&self) -> bool {
+        self.session
+            .load()
+            .as_ref()
+            .is_some_and(|session| session.is_recovered())
+    }
+
+    #[inline]
+    pub(crate) fn latency(&self) -> Option<Duration> {
+        self.session.load().as_ref().and_then(|session| session.latency())
+    }
+
     #[inline]
     pub(crate) fn off(&self, event: &str) {
         self.event_registry.off(event);
@@ -329,6 +413,11 @@ impl WsIoClientRuntime {
         self.event_registry.off_by_handler_id(event, handler_id);
     }
 
+    #[inline]
+    pub(crate) fn off_pattern(&self, pattern: &str) {
+        self.event_registry.off_pattern(pattern);
+    }
+
     #[inline]
     pub(crate) fn on<H, Fut, D>(&self, event: &str, handler: H) -> u32
     where
@@ -338,4 +427,58 @@ impl WsIoClientRuntime {
     {
         self.event_registry.on(event, handler)
     }
+
+    #[inline]
+    pub(crate) fn on_extract<H, T>(&self, event: &str, handler: H) -> u32
+    where
+        H: EventHandler<WsIoClientSession, T>,
+    {
+        self.event_registry.on_extract(event, handler)
+    }
+
+    #[inline]
+    pub(crate) fn on_pattern<H, Fut>(&self, pattern: &str, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.event_registry.on_pattern(pattern, handler)
+    }
+
+    #[inline]
+    pub(crate) fn on_raw<H, Fut>(&self, event: &str, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.event_registry.on_raw(event, handler)
+    }
+
+    #[inline]
+    pub(crate) fn on_with_ack<H, Fut, D, R>(&self, event: &str, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoClientSession>, Arc<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+        D: DeserializeOwned + Send + Sync + 'static,
+        R: Serialize + Send + 'static,
+    {
+        self.event_registry.on_with_ack(event, handler)
+    }
+
+    /// Stores the token presented to recover the session on the next
+    /// reconnect, or forgets it when the server does not offer recovery.
+    #[inline]
+    pub(crate) fn set_recovery_token(&self, recovery_token: Option<String>) {
+        self.recovery_token.store(recovery_token.map(Arc::new));
+    }
+
+    #[inline]
+    pub(crate) fn set_execution_mode(&self, event: &str, mode: WsIoHandlerExecutionMode) {
+        self.event_registry.set_execution_mode(event, mode);
+    }
+
+    #[inline]
+    pub(crate) fn set_handler_timeout(&self, event: &str, duration: Duration) {
+        self.event_registry.set_handler_timeout(event, duration);
+    }
 }
@@ -1,9 +1,25 @@
-use std::sync::{
-    Arc,
-    LazyLock,
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        LazyLock,
+        OnceLock,
+        atomic::{
+            AtomicBool,
+            AtomicI64,
+            AtomicU64,
+            Ordering,
+        },
+    },
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
 };
 
 use anyhow::{
+    Error,
     Result,
     bail,
 };
@@ -13,6 +29,7 @@ use num_enum::{
     IntoPrimitive,
     TryFromPrimitive,
 };
+use parking_lot::Mutex as SyncMutex;
 use tokio::{
     spawn,
     sync::{
@@ -29,19 +46,57 @@ use tokio::{
         timeout,
     },
 };
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::{
+    Bytes,
+    Message,
+    protocol::{
+        CloseFrame,
+        frame::coding::CloseCode,
+    },
+};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
     WsIoClient,
     core::{
         channel_capacity_from_websocket_config,
+        error::{
+            WsIoError,
+            WsIoErrorKind,
+            WsIoProtocolViolationPolicy,
+            catch_handler,
+            connect::{
+                WsIoConnectError,
+                WsIoConnectErrorAction,
+            },
+            truncate_close_reason,
+        },
         packet::{
             WsIoPacket,
             WsIoPacketType,
+            compression::{
+                compress_packet_message,
+                decompress_packet_frame,
+            },
+        },
+        protocol::{
+            WsIoNegotiatedProtocol,
+            WsIoProtocolCapabilities,
+            WsIoProtocolHandshake,
+            WsIoSessionRecovery,
+        },
+        traits::{
+            ack::sender::AckSender,
+            error::reporter::ErrorReporter,
+            task::spawner::TaskSpawner,
+        },
+        utils::{
+            task::abort_locked_task,
+            time::{
+                shift_system_time,
+                unix_time_micros,
+            },
         },
-        traits::task::spawner::TaskSpawner,
-        utils::task::abort_locked_task,
     },
     runtime::WsIoClientRuntime,
 };
@@ -61,17 +116,68 @@ enum SessionState {
 }
 
 // Structs
+
+/// Round-trip times and clock offsets measured with the last answered pings.
+#[derive(Debug, Default)]
+struct ClockSamples(VecDeque<(u64, i64)>);
+
+impl ClockSamples {
+    /// Records a sample and returns the clock offset of the fastest round trip
+    /// in the window.
+    ///
+    /// The shorter the round trip, the less an uneven split between its two
+    /// directions can skew the offset, so one slow pong does not move it.
+    fn push(&mut self, latency_micros: u64, clock_offset_micros: i64) -> i64 {
+        if self.0.len() == CLOCK_SAMPLE_WINDOW {
+            self.0.pop_front();
+        }
+
+        self.0.push_back((latency_micros, clock_offset_micros));
+        self.0
+            .iter()
+            .min_by_key(|(latency_micros, _)| *latency_micros)
+            .map_or(clock_offset_micros, |(_, clock_offset_micros)| *clock_offset_micros)
+    }
+}
+
 #[derive(Debug)]
 pub struct WsIoClientSession {
     cancel_token: ArcSwap<CancellationToken>,
+    clock_offset_micros: AtomicI64,
+    clock_samples: SyncMutex<ClockSamples>,
+    created_at: Instant,
     init_timeout_task: Mutex<Option<JoinHandle<()>>>,
+    latency_micros: AtomicU64,
     message_tx: Sender<Arc<Message>>,
     ping_task: Mutex<Option<JoinHandle<()>>>,
+    protocol: OnceLock<WsIoNegotiatedProtocol>,
     ready_timeout_task: Mutex<Option<JoinHandle<()>>>,
+    recovered: AtomicBool,
     runtime: Arc<WsIoClientRuntime>,
     state: AtomicEnumCell<SessionState>,
 }
 
+impl AckSender for WsIoClientSession {
+    #[inline]
+    async fn send_ack(&self, ack_id: u64, data: Option<Vec<u8>>) -> Result<()> {
+        self.send_packet(&WsIoPacket::new_ack(ack_id, data)).await
+    }
+
+    #[inline]
+    async fn send_ack_error(&self, ack_id: u64, code: &str) -> Result<()> {
+        self.send_packet(&WsIoPacket::new_ack_error(ack_id, code)).await
+    }
+}
+
+impl ErrorReporter for WsIoClientSession {
+    #[inline]
+    fn report_error(&self, error: WsIoError) {
+        if let Some(error_handler) = &self.runtime.config.error_handler {
+            error_handler(error);
+        }
+    }
+}
+
 impl TaskSpawner for WsIoClientSession {
     #[inline]
     fn cancel_token(&self) -> Arc<CancellationToken> {
@@ -87,10 +193,16 @@ impl WsIoClientSession {
         (
             Arc::new(Self {
                 cancel_token: ArcSwap::new(Arc::new(CancellationToken::new())),
+                clock_offset_micros: AtomicI64::new(0),
+                clock_samples: SyncMutex::new(ClockSamples::default()),
+                created_at: Instant::now(),
                 init_timeout_task: Mutex::new(None),
+                latency_micros: AtomicU64::new(UNKNOWN_LATENCY),
                 message_tx,
                 ping_task: Mutex::new(None),
+                protocol: OnceLock::new(),
                 ready_timeout_task: Mutex::new(None),
+                recovered: AtomicBool::new(false),
                 runtime,
                 state: AtomicEnumCell::new(SessionState::Created),
             }),
@@ -99,6 +211,43 @@ impl WsIoClientSession {
     }
 
     // Private methods
+    /// Size threshold from which packets are deflated, or `None` unless both
+    /// the client and the server enabled compression.
+    #[inline]
+    fn compression_threshold(&self) -> Option<usize> {
+        self.runtime.config.compression_threshold.filter(|_| {
+            self.protocol_capabilities()
+                .contains(WsIoProtocolCapabilities::COMPRESSION)
+        })
+    }
+
+    /// Microseconds since the session was created, used as the id of the
+    /// pings it sends.
+    #[inline]
+    fn elapsed_micros(&self) -> u64 {
+        self.created_at.elapsed().as_micros() as u64
+    }
+
+    #[inline]
+    fn handle_ack_packet(
+        &self,
+        ack_id: Option<u64>,
+        error_code: Option<&str>,
+        packet_data: Option<&[u8]>,
+    ) -> Result<()> {
+        let Some(ack_id) = ack_id else {
+            bail!("Ack packet missing ack id");
+        };
+
+        let ack_registry = &self.runtime.ack_registry;
+        match error_code {
+            Some(error_code) => ack_registry.reject(ack_id, error_code.into()),
+            None => ack_registry.resolve(ack_id, packet_data.map(<[u8]>::to_vec)),
+        };
+
+        Ok(())
+    }
+
     #[inline]
     fn handle_disconnect_packet(&self) -> Result<()> {
         let runtime = self.runtime.clone();
@@ -106,37 +255,101 @@ impl WsIoClientSession {
         Ok(())
     }
 
-    #[inline]
-    fn handle_event_packet(self: &Arc<Self>, event: &str, packet_data: Option<Vec<u8>>) -> Result<()> {
-        self.runtime.event_registry.dispatch_event_packet(
-            self.clone(),
-            event,
-            &self.runtime.config.packet_codec,
-            packet_data,
-            &self.runtime,
-        );
+    fn handle_error_packet(&self, packet_data: Option<&[u8]>) -> Result<()> {
+        // Verify current state; the server only rejects a session while it awaits the ready packet
+        let state = self.state.get();
+        if state != SessionState::AwaitingReady {
+            bail!("Received error packet in invalid state: {state:?}");
+        }
+
+        let Some(packet_data) = packet_data else {
+            bail!("Error packet missing data");
+        };
+
+        let connect_error = self
+            .runtime
+            .config
+            .packet_codec
+            .decode_data_as::<WsIoConnectError>(packet_data)?;
+
+        // Stop reconnecting if the handler asks to, the server closes the session itself
+        let action = match &self.runtime.config.on_connect_error_handler {
+            Some(on_connect_error_handler) => on_connect_error_handler(&connect_error),
+            None => WsIoConnectErrorAction::Reconnect,
+        };
+
+        if action == WsIoConnectErrorAction::Stop {
+            let runtime = self.runtime.clone();
+            spawn(async move { runtime.disconnect().await });
+        }
+
+        Ok(())
+    }
+
+    async fn handle_event_packet(
+        self: &Arc<Self>,
+        event: &str,
+        packet_data: Option<Bytes>,
+        ack_id: Option<u64>,
+    ) -> Result<()> {
+        self.runtime
+            .event_registry
+            .dispatch_event_packet(
+                self.clone(),
+                event,
+                &self.runtime.config.packet_codec,
+                packet_data,
+                ack_id,
+                &self.runtime,
+            )
+            .await;
 
         Ok(())
     }
 
-    async fn handle_init_packet(self: &Arc<Self>, packet_data: Option<&[u8]>) -> Result<()> {
+    async fn handle_init_packet(self: &Arc<Self>, packet_key: Option<&str>, packet_data: Option<&[u8]>) -> Result<()> {
         // Verify current state; only valid from AwaitingInit → Initiating
         let state = self.state.get();
         match state {
             SessionState::AwaitingInit => self.state.try_transition(state, SessionState::Initiating)?,
-            _ => bail!("Received init packet in invalid state: {state:?}"),
+            _ => {
+                self.handle_protocol_violation(Error::msg(format!("Received init packet in invalid state: {state:?}")));
+
+                return Ok(());
+            },
         }
 
         // Abort init-timeout task
         abort_locked_task(&self.init_timeout_task).await;
 
+        // Negotiate protocol with the server handshake, closing the session if the server is incompatible
+        let negotiate_result = packet_key
+            .map(str::parse::<WsIoProtocolHandshake>)
+            .transpose()
+            .and_then(|handshake| self.local_handshake().negotiate(handshake.as_ref()));
+
+        match negotiate_result {
+            Ok(protocol) => {
+                let _ = self.protocol.set(protocol);
+            },
+            Err(err) => {
+                let reason = err.to_string();
+                self.report_error(WsIoError::new(WsIoErrorKind::ProtocolViolation(err)));
+                self.close_with_frame(Some(CloseFrame {
+                    code: CloseCode::Protocol,
+                    reason: truncate_close_reason(&reason).into(),
+                }));
+
+                return Ok(());
+            },
+        }
+
         // Invoke init_handler with timeout protection if configured
         let response_data = if let Some(init_handler) = &self.runtime.config.init_handler {
-            timeout(
-                self.runtime.config.init_handler_timeout,
-                init_handler(self.clone(), packet_data, &self.runtime.config.packet_codec),
-            )
-            .await??
+            self.run_lifecycle_handler("init", self.runtime.config.init_handler_timeout, || {
+                init_handler(self.clone(), packet_data, &*self.runtime.config.packet_codec)
+            })
+            .await?
         } else {
             None
         };
@@ -155,33 +368,156 @@ impl WsIoClientSession {
         }));
 
         // Send init packet
-        self.send_packet(&WsIoPacket::new_init(response_data)).await
+        self.send_packet(&WsIoPacket::new_init_with_handshake(
+            response_data,
+            &self.local_handshake(),
+        ))
+        .await
     }
 
-    async fn handle_ready_packet(self: &Arc<Self>) -> Result<()> {
+    async fn handle_ready_packet(self: &Arc<Self>, packet_key: Option<&str>) -> Result<()> {
         // Verify current state; only valid from AwaitingReady → Ready
         let state = self.state.get();
         match state {
             SessionState::AwaitingReady => self.state.try_transition(state, SessionState::Ready)?,
-            _ => bail!("Received ready packet in invalid state: {state:?}"),
+            _ => {
+                self.handle_protocol_violation(Error::msg(format!(
+                    "Received ready packet in invalid state: {state:?}"
+                )));
+
+                return Ok(());
+            },
         }
 
         // Abort ready-timeout task
         abort_locked_task(&self.ready_timeout_task).await;
 
+        // Keep the session token for the next reconnect and learn whether this session was recovered
+        let recovery = match packet_key {
+            Some(packet_key)
+                if self
+                    .protocol_capabilities()
+                    .contains(WsIoProtocolCapabilities::STATE_RECOVERY) =>
+            {
+                match packet_key.parse::<WsIoSessionRecovery>() {
+                    Ok(recovery) => Some(recovery),
+                    Err(err) => {
+                        self.handle_protocol_violation(err);
+                        None
+                    },
+                }
+            },
+            _ => None,
+        };
+
+        self.recovered.store(
+            recovery.as_ref().is_some_and(|recovery| recovery.recovered),
+            Ordering::Release,
+        );
+        self.runtime.set_recovery_token(recovery.map(|recovery| recovery.token));
+
         // Wake send event message task
         self.runtime.wake_send_event_message_task_notify.notify_waiters();
 
+        // Measure latency right away instead of waiting for the ping task
+        let _ = self.send_ping().await;
+
         // Invoke on_session_ready_handler if configured
         if let Some(on_session_ready_handler) = self.runtime.config.on_session_ready_handler.clone() {
             // Run handler asynchronously in a detached task
-            self.spawn_task(on_session_ready_handler(self.clone()));
+            let session = self.clone();
+            self.spawn_task(async move {
+                if let Err(kind) = catch_handler(|| on_session_ready_handler(session.clone())).await {
+                    session.report_error(WsIoError::new(kind).with_event("on_session_ready"));
+                }
+
+                Ok(())
+            });
         }
 
         Ok(())
     }
 
+    /// Records the round-trip time of an answered ping and estimates the clock
+    /// offset, assuming the pong left the server halfway through it.
+    ///
+    /// The offset kept is the one of the fastest of the last few round trips,
+    /// since a slow one is likely split unevenly between the two directions.
+    fn handle_pong_packet(&self, ping_id: Option<u64>, packet_data: Option<&[u8]>) -> Result<()> {
+        let Some(ping_id) = ping_id else {
+            bail!("Pong packet missing ping id");
+        };
+
+        let Some(packet_data) = packet_data else {
+            bail!("Pong packet missing server time");
+        };
+
+        let server_time_micros = self.runtime.config.packet_codec.decode_data_as::<u64>(packet_data)?;
+
+        let latency_micros = self.elapsed_micros().saturating_sub(ping_id);
+        let clock_offset_micros = server_time_micros as i64 + (latency_micros / 2) as i64 - unix_time_micros() as i64;
+
+        let clock_offset_micros = self.clock_samples.lock().push(latency_micros, clock_offset_micros);
+
+        // Offset first, so a known latency always comes with its offset
+        self.clock_offset_micros.store(clock_offset_micros, Ordering::Relaxed);
+
+        self.latency_micros.store(latency_micros, Ordering::Release);
+        Ok(())
+    }
+
+    /// Handshake advertised to the server, leaving out compression when the
+    /// client does not enable it.
+    #[inline]
+    fn local_handshake(&self) -> WsIoProtocolHandshake {
+        match self.runtime.config.compression_threshold {
+            Some(_) => WsIoProtocolHandshake::LOCAL,
+            None => WsIoProtocolHandshake::LOCAL.without_capabilities(WsIoProtocolCapabilities::COMPRESSION),
+        }
+    }
+
+    /// Reports a packet that breaks the ws.io protocol and, under the
+    /// disconnect policy, closes the session with a close frame naming it.
+    fn handle_protocol_violation(&self, err: Error) {
+        if matches!(self.state.get(), SessionState::Closed | SessionState::Closing) {
+            return;
+        }
+
+        let reason = err.to_string();
+        self.report_error(WsIoError::new(WsIoErrorKind::ProtocolViolation(err)));
+        if self.runtime.config.protocol_violation_policy == WsIoProtocolViolationPolicy::Disconnect {
+            self.close_with_frame(Some(CloseFrame {
+                code: CloseCode::Protocol,
+                reason: truncate_close_reason(&reason).into(),
+            }));
+        }
+    }
+
+    /// Runs a lifecycle handler within `duration`, reporting its error, panic
+    /// or timeout under the handler `name` before failing.
+    async fn run_lifecycle_handler<T, F, Fut>(&self, name: &'static str, duration: Duration, handler: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let kind = match timeout(duration, catch_handler(handler)).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(kind)) => kind,
+            Err(_) => WsIoErrorKind::Timeout(duration),
+        };
+
+        let message = kind.to_string();
+        self.report_error(WsIoError::new(kind).with_event(name));
+        bail!("{name} {message}")
+    }
+
     async fn send_message(&self, message: Arc<Message>) -> Result<()> {
+        // Only messages queued once ready are compressed, so the init packet the server negotiates from stays plain
+        let message = match self.compression_threshold() {
+            Some(threshold) if self.is_ready() => Arc::new(compress_packet_message((*message).clone(), threshold)),
+            _ => message,
+        };
+
         Ok(self.message_tx.send(message).await?)
     }
 
@@ -189,6 +525,28 @@ impl WsIoClientSession {
         self.send_message(self.runtime.encode_packet_to_message(packet)?).await
     }
 
+    /// Sends a ping packet carrying the last measured latency, or the 1-byte
+    /// heartbeat frame before the session is ready or when the server cannot
+    /// answer pings.
+    async fn send_ping(&self) -> Result<()> {
+        if !self.is_ready() || !self.protocol_capabilities().contains(WsIoProtocolCapabilities::LATENCY) {
+            return self.send_message(PING_MESSAGE.clone()).await;
+        }
+
+        let data = self
+            .latency()
+            .map(|latency| {
+                self.runtime
+                    .config
+                    .packet_codec
+                    .encode_data(&(latency.as_micros() as u64))
+            })
+            .transpose()?;
+
+        self.send_packet(&WsIoPacket::new_ping(self.elapsed_micros(), data))
+            .await
+    }
+
     // Protected methods
     pub(crate) async fn cleanup(self: &Arc<Self>) {
         // Set state to Closing
@@ -204,11 +562,13 @@ impl WsIoClientSession {
 
         // Invoke on_session_close_handler with timeout protection if configured
         if let Some(on_session_close_handler) = &self.runtime.config.on_session_close_handler {
-            let _ = timeout(
-                self.runtime.config.on_session_close_handler_timeout,
-                on_session_close_handler(self.clone()),
-            )
-            .await;
+            let _ = self
+                .run_lifecycle_handler(
+                    "on_session_close",
+                    self.runtime.config.on_session_close_handler_timeout,
+                    || on_session_close_handler(self.clone()),
+                )
+                .await;
         }
 
         // Set state to Closed
@@ -217,6 +577,10 @@ impl WsIoClientSession {
 
     #[inline]
     pub(crate) fn close(&self) {
+        self.close_with_frame(None);
+    }
+
+    pub(crate) fn close_with_frame(&self, close_frame: Option<CloseFrame>) {
         // Skip if session is already Closing or Closed, otherwise set state to Closing
         match self.state.get() {
             SessionState::Closed | SessionState::Closing => return,
@@ -224,7 +588,7 @@ impl WsIoClientSession {
         }
 
         // Send websocket close frame to initiate graceful shutdown
-        let _ = self.message_tx.try_send(Arc::new(Message::Close(None)));
+        let _ = self.message_tx.try_send(Arc::new(Message::Close(close_frame)));
     }
 
     pub(crate) async fn emit_event_message(&self, message: Arc<Message>) -> Result<()> {
@@ -235,24 +599,79 @@ impl WsIoClientSession {
         self.send_message(message).await
     }
 
-    pub(crate) async fn handle_incoming_packet(self: &Arc<Self>, encoded_packet: &[u8]) -> Result<()> {
-        // TODO: lazy load
-        let packet = self.runtime.config.packet_codec.decode(encoded_packet)?;
+    /// Handles a binary frame, unwrapping it first when compression has been
+    /// negotiated with the server.
+    pub(crate) async fn handle_incoming_binary_packet(self: &Arc<Self>, frame: Bytes) -> Result<()> {
+        if self.compression_threshold().is_none() {
+            return self.handle_incoming_packet(frame).await;
+        }
+
+        let max_size = self.runtime.config.websocket_config.max_message_size;
+        match decompress_packet_frame(frame, max_size) {
+            Ok(encoded_packet) => self.handle_incoming_packet(encoded_packet).await,
+            Err(err) => {
+                self.handle_protocol_violation(err.context("Failed to decompress packet"));
+                Ok(())
+            },
+        }
+    }
+
+    pub(crate) async fn handle_incoming_packet(self: &Arc<Self>, encoded_packet: Bytes) -> Result<()> {
+        // Key and data stay borrowed from the frame until an event handler needs them
+        let mut packet = match self.runtime.config.packet_codec.decode_view(&encoded_packet) {
+            Ok(packet) => packet,
+            Err(err) => {
+                self.handle_protocol_violation(err.context("Failed to decode packet"));
+                return Ok(());
+            },
+        };
+
         match packet.r#type {
+            WsIoPacketType::Ack => {
+                if self.is_ready()
+                    && let Err(err) =
+                        self.handle_ack_packet(packet.ack_id, packet.key.as_deref(), packet.data.as_deref())
+                {
+                    self.handle_protocol_violation(err);
+                }
+
+                Ok(())
+            },
             WsIoPacketType::Disconnect => self.handle_disconnect_packet(),
+            WsIoPacketType::Error => {
+                if let Err(err) = self.handle_error_packet(packet.data.as_deref()) {
+                    self.handle_protocol_violation(err);
+                }
+
+                Ok(())
+            },
             WsIoPacketType::Event => {
                 if self.is_ready() {
+                    let packet_data = packet.take_data_bytes(&encoded_packet);
                     if let Some(event) = packet.key.as_deref() {
-                        return self.handle_event_packet(event, packet.data);
+                        return self.handle_event_packet(event, packet_data, packet.ack_id).await;
                     } else {
-                        bail!("Event packet missing key");
+                        self.handle_protocol_violation(Error::msg("Event packet missing key"));
                     }
                 }
 
                 Ok(())
             },
-            WsIoPacketType::Init => self.handle_init_packet(packet.data.as_deref()).await,
-            WsIoPacketType::Ready => self.handle_ready_packet().await,
+            WsIoPacketType::Init => {
+                self.handle_init_packet(packet.key.as_deref(), packet.data.as_deref())
+                    .await
+            },
+            WsIoPacketType::Ping => Ok(()),
+            WsIoPacketType::Pong => {
+                if self.is_ready()
+                    && let Err(err) = self.handle_pong_packet(packet.ack_id, packet.data.as_deref())
+                {
+                    self.handle_protocol_violation(err);
+                }
+
+                Ok(())
+            },
+            WsIoPacketType::Ready => self.handle_ready_packet(packet.key.as_deref()).await,
         }
     }
 
@@ -268,12 +687,12 @@ impl WsIoClientSession {
             }
         }));
 
-        // Create ping task to send 1-byte heartbeat frame to keep the connection alive
+        // Create ping task to keep the connection alive and measure latency
         let session = self.clone();
         *self.ping_task.lock().await = Some(spawn(async move {
             loop {
                 sleep(session.runtime.config.ping_interval).await;
-                if session.send_message(PING_MESSAGE.clone()).await.is_err() {
+                if session.send_ping().await.is_err() {
                     session.close();
                 }
             }
@@ -286,11 +705,91 @@ impl WsIoClientSession {
         WsIoClient(self.runtime.clone())
     }
 
+    /// Estimated number of microseconds the server clock is ahead of the local
+    /// one, or `None` until a ping has been answered.
+    ///
+    /// Taken from the fastest of the last few answered pings, so a single
+    /// delayed pong does not skew it.
+    #[inline]
+    pub fn clock_offset_micros(&self) -> Option<i64> {
+        self.latency().map(|_| self.clock_offset_micros.load(Ordering::Relaxed))
+    }
+
     #[inline]
     pub fn is_ready(&self) -> bool {
         self.state.is(SessionState::Ready)
     }
+
+    /// Whether the server restored the connection of the previous session,
+    /// keeping its id and rooms and replaying the broadcasts missed while
+    /// reconnecting.
+    ///
+    /// Only set once the session is ready, and only when the server enables
+    /// state recovery for the namespace.
+    #[inline]
+    pub fn is_recovered(&self) -> bool {
+        self.recovered.load(Ordering::Acquire)
+    }
+
+    /// Round-trip time of the last answered ping, or `None` until one has
+    /// been answered or when the server cannot answer pings.
+    #[inline]
+    pub fn latency(&self) -> Option<Duration> {
+        match self.latency_micros.load(Ordering::Acquire) {
+            UNKNOWN_LATENCY => None,
+            latency_micros => Some(Duration::from_micros(latency_micros)),
+        }
+    }
+
+    /// Capabilities negotiated with the server, empty until the init handshake
+    /// has completed.
+    #[inline]
+    pub fn protocol_capabilities(&self) -> WsIoProtocolCapabilities {
+        self.protocol
+            .get()
+            .map_or(WsIoProtocolCapabilities::empty(), |protocol| protocol.capabilities)
+    }
+
+    /// Protocol version negotiated with the server, or `None` until the init
+    /// handshake has completed.
+    #[inline]
+    pub fn protocol_version(&self) -> Option<u16> {
+        self.protocol.get().map(|protocol| protocol.version)
+    }
+
+    /// Converts a server timestamp to local time using the estimated clock
+    /// offset, or `None` until a ping has been answered.
+    #[inline]
+    pub fn to_local_time(&self, server_time: SystemTime) -> Option<SystemTime> {
+        self.clock_offset_micros()
+            .map(|clock_offset_micros| shift_system_time(server_time, -clock_offset_micros))
+    }
 }
 
 // Constants/Statics
+const CLOCK_SAMPLE_WINDOW: usize = 8;
+const UNKNOWN_LATENCY: u64 = u64::MAX;
+
 static PING_MESSAGE: LazyLock<Arc<Message>> = LazyLock::new(|| Arc::new(Message::Binary(vec![0x01].into())));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_samples_keep_offset_of_fastest_round_trip() {
+        let mut clock_samples = ClockSamples::default();
+        assert_eq!(clock_samples.push(2_000, 100), 100);
+
+        // A faster round trip replaces the offset, a slower one does not
+        assert_eq!(clock_samples.push(1_000, 50), 50);
+        assert_eq!(clock_samples.push(90_000, 45_000), 50);
+
+        // The fastest sample leaves the window after `CLOCK_SAMPLE_WINDOW` pushes
+        for _ in 0..CLOCK_SAMPLE_WINDOW - 2 {
+            assert_eq!(clock_samples.push(3_000, 200), 50);
+        }
+
+        assert_eq!(clock_samples.push(3_000, 200), 200);
+    }
+}
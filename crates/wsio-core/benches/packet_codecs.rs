@@ -13,9 +13,12 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use wsio_core::packet::{
-    WsIoPacket,
-    codecs::WsIoPacketCodec,
+use wsio_core::{
+    packet::{
+        WsIoPacket,
+        codecs::WsIoPacketCodec,
+    },
+    traits::packet::codec::PacketCodec,
 };
 
 // Constants/Statics
@@ -46,19 +49,19 @@ fn bench_id(codec_name: &str, operation: &str, payload_size: usize) -> Benchmark
 fn bench_codecs(criterion: &mut Criterion) {
     let mut group = criterion.benchmark_group("packet_codecs");
 
-    let codecs = [
-        ("SerdeJson", WsIoPacketCodec::SerdeJson),
+    let codecs: &[(&str, &dyn PacketCodec)] = &[
+        ("SerdeJson", &WsIoPacketCodec::SerdeJson),
         #[cfg(feature = "packet-codec-cbor")]
-        ("Cbor", WsIoPacketCodec::Cbor),
+        ("Cbor", &WsIoPacketCodec::Cbor),
         #[cfg(feature = "packet-codec-msgpack")]
-        ("Msgpack", WsIoPacketCodec::Msgpack),
+        ("Msgpack", &WsIoPacketCodec::Msgpack),
         #[cfg(feature = "packet-codec-postcard")]
-        ("Postcard", WsIoPacketCodec::Postcard),
+        ("Postcard", &WsIoPacketCodec::Postcard),
         #[cfg(feature = "packet-codec-sonic-rs")]
-        ("SonicRs", WsIoPacketCodec::SonicRs),
+        ("SonicRs", &WsIoPacketCodec::SonicRs),
     ];
 
-    for (name, codec) in codecs {
+    for &(name, codec) in codecs {
         for payload_size in PAYLOAD_SIZES {
             let payload = payload(payload_size);
             let encoded_data = codec.encode_data(&payload).unwrap();
@@ -82,7 +85,7 @@ fn bench_codecs(criterion: &mut Criterion) {
                 &encoded_data,
                 |bencher, encoded_data| {
                     bencher.iter(|| {
-                        let _: BenchPayload = codec.decode_data(black_box(encoded_data)).unwrap();
+                        let _: BenchPayload = codec.decode_data_as(black_box(encoded_data)).unwrap();
                     })
                 },
             );
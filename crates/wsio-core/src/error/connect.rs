@@ -0,0 +1,137 @@
+use std::{
+    error::Error as StdError,
+    fmt::{
+        Display,
+        Formatter,
+        Result as FmtResult,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::value::WsIoValue;
+
+// Enums
+
+/// What a client does after the server rejected its connection.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WsIoConnectErrorAction {
+    /// Keeps reconnecting after the reconnect delay.
+    #[default]
+    Reconnect,
+
+    /// Stops the client as if `disconnect` had been called.
+    Stop,
+}
+
+// Structs
+
+/// Why the server rejected a connection during its handshake.
+///
+/// Server-side middleware, init-response and on-connect handlers reject a
+/// connection with their own code by returning this error, for example
+/// `Err(WsIoConnectError::new("unauthorized", "Invalid token").into())`. Other
+/// handler errors are sent as [`Self::INTERNAL`] without their message, and
+/// handler timeouts as [`Self::TIMEOUT`].
+///
+/// The error is encoded with the packet codec of the connection. Postcard
+/// cannot decode [`WsIoValue`], so only send `data` with self-describing
+/// codecs.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WsIoConnectError {
+    code: String,
+    data: Option<WsIoValue>,
+    message: String,
+}
+
+impl WsIoConnectError {
+    /// Code sent when a handler fails with an error other than
+    /// `WsIoConnectError`.
+    pub const INTERNAL: &str = "internal";
+
+    /// Code an ack fails with when the event has no handler that answers
+    /// acks.
+    pub const NO_ACK_HANDLER: &str = "no_ack_handler";
+
+    /// Code sent when a handler does not finish within its timeout.
+    pub const TIMEOUT: &str = "timeout";
+
+    #[inline]
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            data: None,
+            message: message.into(),
+        }
+    }
+
+    /// Attaches extra details for the client, such as a retry delay.
+    #[inline]
+    pub fn with_data(mut self, data: WsIoValue) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    // Public methods
+    #[inline]
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    #[inline]
+    pub fn data(&self) -> Option<&WsIoValue> {
+        self.data.as_ref()
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for WsIoConnectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl StdError for WsIoConnectError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packet::codecs::WsIoPacketCodec,
+        traits::packet::codec::PacketCodec,
+    };
+
+    #[test]
+    fn test_connect_error_roundtrip() {
+        let error = WsIoConnectError::new("unauthorized", "Invalid token").with_data(WsIoValue::Map(vec![(
+            WsIoValue::String("retry".into()),
+            WsIoValue::Bool(false),
+        )]));
+
+        assert_eq!(error.to_string(), "unauthorized: Invalid token");
+
+        let packet_codec: &dyn PacketCodec = &WsIoPacketCodec::SerdeJson;
+        let decoded = packet_codec
+            .decode_data_as::<WsIoConnectError>(&packet_codec.encode_data(&error).unwrap())
+            .unwrap();
+
+        assert_eq!(decoded, error);
+        assert_eq!(
+            decoded.data().and_then(|data| data.get("retry")),
+            Some(&WsIoValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn test_connect_error_downcasts_from_anyhow() {
+        let error = anyhow::Error::from(WsIoConnectError::new("banned", "Banned"));
+        assert_eq!(error.downcast_ref::<WsIoConnectError>().unwrap().code(), "banned");
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    any::Any,
+    error::Error as StdError,
+    fmt::{
+        Display,
+        Formatter,
+        Result as FmtResult,
+    },
+    panic::{
+        AssertUnwindSafe,
+        catch_unwind,
+    },
+    time::Duration,
+};
+
+use anyhow::{
+    Error,
+    Result,
+};
+use futures_util::FutureExt;
+
+pub mod connect;
+
+// Constants/Statics
+
+/// Longest close frame reason RFC 6455 allows, in bytes.
+const MAX_CLOSE_REASON_LEN: usize = 123;
+
+// Enums
+
+/// What went wrong in a [`WsIoError`].
+#[derive(Debug)]
+pub enum WsIoErrorKind {
+    /// An event payload could not be decoded into the data type of its handlers.
+    Decode(Error),
+
+    /// An argument of an extractor handler could not be resolved, such as a
+    /// payload of the wrong type or a missing extension.
+    Extract(Error),
+
+    /// A handler returned an error.
+    Handler(Error),
+
+    /// A handler panicked; holds the panic message.
+    HandlerPanic(String),
+
+    /// An event middleware rejected an incoming event before its handlers ran.
+    Middleware(Error),
+
+    /// The peer sent a packet that breaks the ws.io protocol, such as one that
+    /// cannot be decoded or an event without a name.
+    ProtocolViolation(Error),
+
+    /// A handler did not finish within its timeout.
+    Timeout(Duration),
+}
+
+impl Display for WsIoErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Decode(error) => write!(f, "failed to decode event data: {error}"),
+            Self::Extract(error) => write!(f, "failed to extract handler arguments: {error:#}"),
+            Self::Handler(error) => write!(f, "handler failed: {error}"),
+            Self::HandlerPanic(message) => write!(f, "handler panicked: {message}"),
+            Self::Middleware(error) => write!(f, "rejected by middleware: {error}"),
+            Self::ProtocolViolation(error) => write!(f, "protocol violation: {error}"),
+            Self::Timeout(duration) => write!(f, "handler timed out after {duration:?}"),
+        }
+    }
+}
+
+/// What a connection does when its peer breaks the ws.io protocol.
+///
+/// The violation is passed to the `on_error` hook either way.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WsIoProtocolViolationPolicy {
+    /// Closes the connection with a protocol error close frame whose reason
+    /// describes the violation.
+    #[default]
+    Disconnect,
+
+    /// Drops the offending packet and keeps the connection open.
+    Report,
+}
+
+// Structs
+
+/// An error passed to `on_error` hooks.
+///
+/// Carries the connection the error happened on and the event it belongs to,
+/// when known. For failures of lifecycle handlers the event is the handler
+/// name, such as `on_connect`.
+#[derive(Debug)]
+pub struct WsIoError {
+    connection_id: Option<u64>,
+    event: Option<String>,
+    kind: WsIoErrorKind,
+}
+
+impl WsIoError {
+    #[inline]
+    pub fn new(kind: WsIoErrorKind) -> Self {
+        Self {
+            connection_id: None,
+            event: None,
+            kind,
+        }
+    }
+
+    #[inline]
+    pub fn with_connection_id(mut self, connection_id: u64) -> Self {
+        self.connection_id = Some(connection_id);
+        self
+    }
+
+    #[inline]
+    pub fn with_event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    // Public methods
+    #[inline]
+    pub fn connection_id(&self) -> Option<u64> {
+        self.connection_id
+    }
+
+    #[inline]
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    #[inline]
+    pub fn into_kind(self) -> WsIoErrorKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &WsIoErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for WsIoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(event) = &self.event {
+            write!(f, "[{event}] ")?;
+        }
+
+        if let Some(connection_id) = self.connection_id {
+            write!(f, "connection {connection_id}: ")?;
+        }
+
+        self.kind.fmt(f)
+    }
+}
+
+impl StdError for WsIoError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match &self.kind {
+            WsIoErrorKind::Decode(error)
+            | WsIoErrorKind::Extract(error)
+            | WsIoErrorKind::Handler(error)
+            | WsIoErrorKind::Middleware(error)
+            | WsIoErrorKind::ProtocolViolation(error) => Some(error.as_ref()),
+            WsIoErrorKind::HandlerPanic(_) | WsIoErrorKind::Timeout(_) => None,
+        }
+    }
+}
+
+// Functions
+
+/// Calls `handler` and awaits the future it returns, turning an error into
+/// [`WsIoErrorKind::Handler`] and a panic in either step into
+/// [`WsIoErrorKind::HandlerPanic`].
+pub async fn catch_handler<T, F, Fut>(handler: F) -> Result<T, WsIoErrorKind>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let future = catch_unwind(AssertUnwindSafe(handler)).map_err(panic_error_kind)?;
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result.map_err(WsIoErrorKind::Handler),
+        Err(payload) => Err(panic_error_kind(payload)),
+    }
+}
+
+#[inline]
+fn panic_error_kind(payload: Box<dyn Any + Send>) -> WsIoErrorKind {
+    WsIoErrorKind::HandlerPanic(match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast_ref::<&str>() {
+            Some(message) => (*message).into(),
+            None => "<non-string panic payload>".into(),
+        },
+    })
+}
+
+/// Cuts `reason` down to the longest prefix that fits in a WebSocket close
+/// frame without splitting a character.
+pub fn truncate_close_reason(reason: &str) -> &str {
+    if reason.len() <= MAX_CLOSE_REASON_LEN {
+        return reason;
+    }
+
+    let mut len = MAX_CLOSE_REASON_LEN;
+    while !reason.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    &reason[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_catch_handler_passes_value_and_error() {
+        assert_eq!(catch_handler(|| async { Ok(7) }).await.unwrap(), 7);
+
+        let kind = catch_handler(|| async { bail!("boom") as Result<()> })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(kind, WsIoErrorKind::Handler(error) if error.to_string() == "boom"));
+    }
+
+    #[tokio::test]
+    async fn test_catch_handler_catches_panics() {
+        let kind = catch_handler(|| async {
+            panic!("panicked in future");
+            #[allow(unreachable_code)]
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+
+        assert!(matches!(kind, WsIoErrorKind::HandlerPanic(message) if message == "panicked in future"));
+
+        let kind = catch_handler(|| -> std::future::Ready<Result<()>> { panic!("panicked with {}", 42) })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(kind, WsIoErrorKind::HandlerPanic(message) if message == "panicked with 42"));
+    }
+
+    #[test]
+    fn test_error_display_and_source() {
+        let error = WsIoError::new(WsIoErrorKind::Timeout(Duration::from_secs(1)))
+            .with_connection_id(3)
+            .with_event("on_connect");
+
+        assert_eq!(
+            error.to_string(),
+            "[on_connect] connection 3: handler timed out after 1s"
+        );
+        assert!(error.source().is_none());
+
+        let error = WsIoError::new(WsIoErrorKind::Decode(Error::msg("invalid type")));
+        assert_eq!(error.to_string(), "failed to decode event data: invalid type");
+        assert_eq!(error.source().unwrap().to_string(), "invalid type");
+    }
+
+    #[test]
+    fn test_truncate_close_reason() {
+        assert_eq!(truncate_close_reason("short"), "short");
+
+        let reason = "é".repeat(100);
+        let truncated = truncate_close_reason(&reason);
+        assert_eq!(truncated.len(), 122);
+        assert!(reason.starts_with(truncated));
+    }
+}
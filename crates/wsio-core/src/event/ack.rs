@@ -0,0 +1,212 @@
+use std::{
+    sync::atomic::{
+        AtomicU64,
+        Ordering,
+    },
+    time::Duration,
+};
+
+use anyhow::{
+    Result,
+    bail,
+};
+use kikiutils::types::fx_collections::FxHashMap;
+use parking_lot::Mutex;
+use serde::de::{
+    DeserializeOwned,
+    IntoDeserializer,
+    value::Error as ValueError,
+};
+use tokio::{
+    sync::oneshot::{
+        Receiver,
+        Sender,
+        channel,
+    },
+    time::timeout,
+};
+
+use crate::traits::packet::codec::PacketCodec;
+
+// Types
+/// Outcome of an ack: the reply data, or the error code the peer sent when its
+/// handler failed.
+pub type WsIoEventAckReply = Result<Option<Vec<u8>>, String>;
+
+// Structs
+#[derive(Debug, Default)]
+pub struct WsIoEventAckRegistry {
+    next_ack_id: AtomicU64,
+    pending_acks: Mutex<FxHashMap<u64, Sender<WsIoEventAckReply>>>,
+}
+
+impl WsIoEventAckRegistry {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Private methods
+    #[inline]
+    fn settle(&self, ack_id: u64, reply: WsIoEventAckReply) -> bool {
+        match self.pending_acks.lock().remove(&ack_id) {
+            Some(tx) => tx.send(reply).is_ok(),
+            None => false,
+        }
+    }
+
+    // Public methods
+
+    /// Drops every pending ack so their waiters fail immediately instead of
+    /// running into their timeout.
+    #[inline]
+    pub fn clear(&self) {
+        self.pending_acks.lock().clear();
+    }
+
+    #[inline]
+    pub fn pending_count(&self) -> usize {
+        self.pending_acks.lock().len()
+    }
+
+    /// Allocates a new ack id and the receiver its reply will be delivered to.
+    #[inline]
+    pub fn register(&self) -> (u64, Receiver<WsIoEventAckReply>) {
+        let ack_id = self.next_ack_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = channel();
+        self.pending_acks.lock().insert(ack_id, tx);
+        (ack_id, rx)
+    }
+
+    /// Fails the waiter of `ack_id` with the error `code` the peer sent when
+    /// its handler failed.
+    ///
+    /// Returns `false` when no waiter is registered for the id.
+    #[inline]
+    pub fn reject(&self, ack_id: u64, code: String) -> bool {
+        self.settle(ack_id, Err(code))
+    }
+
+    #[inline]
+    pub fn remove(&self, ack_id: u64) {
+        self.pending_acks.lock().remove(&ack_id);
+    }
+
+    /// Delivers the reply data for `ack_id`.
+    ///
+    /// Returns `false` when no waiter is registered for the id, for example
+    /// because it already timed out or was answered.
+    #[inline]
+    pub fn resolve(&self, ack_id: u64, data: Option<Vec<u8>>) -> bool {
+        self.settle(ack_id, Ok(data))
+    }
+
+    /// Waits for the reply registered under `ack_id` and decodes it as `R`.
+    ///
+    /// The pending entry is removed when the timeout elapses. A reply without
+    /// data is decoded as a unit value, so `()` and `Option<T>` replies accept
+    /// it. An ack rejected by the peer fails with its error code.
+    pub async fn wait<R: DeserializeOwned>(
+        &self,
+        ack_id: u64,
+        rx: Receiver<WsIoEventAckReply>,
+        duration: Duration,
+        packet_codec: &dyn PacketCodec,
+    ) -> Result<R> {
+        match timeout(duration, rx).await {
+            Ok(Ok(Ok(Some(bytes)))) => packet_codec.decode_data_as(&bytes),
+            Ok(Ok(Ok(None))) => Ok(R::deserialize(IntoDeserializer::<ValueError>::into_deserializer(()))?),
+            Ok(Ok(Err(code))) => bail!("Ack {ack_id} failed on the peer: {code}"),
+            Ok(Err(_)) => bail!("Ack {ack_id} was dropped before a reply arrived"),
+            Err(_) => {
+                self.remove(ack_id);
+                bail!("Timed out waiting for ack {ack_id} after {duration:?}")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::codecs::WsIoPacketCodec;
+
+    #[tokio::test]
+    async fn test_ack_registry_resolve() {
+        let registry = WsIoEventAckRegistry::new();
+        let packet_codec = WsIoPacketCodec::SerdeJson;
+
+        let (ack_id, rx) = registry.register();
+        assert_eq!(registry.pending_count(), 1);
+
+        assert!(registry.resolve(ack_id, Some(packet_codec.encode_data(&"pong").unwrap())));
+        assert_eq!(registry.pending_count(), 0);
+
+        // Resolving the same id again has no waiter
+        assert!(!registry.resolve(ack_id, None));
+
+        let reply: String = registry
+            .wait(ack_id, rx, Duration::from_secs(1), &packet_codec)
+            .await
+            .unwrap();
+
+        assert_eq!(reply, "pong");
+    }
+
+    #[tokio::test]
+    async fn test_ack_registry_resolve_without_data() {
+        let registry = WsIoEventAckRegistry::new();
+        let (ack_id, rx) = registry.register();
+        registry.resolve(ack_id, None);
+
+        let reply: Option<String> = registry
+            .wait(ack_id, rx, Duration::from_secs(1), &WsIoPacketCodec::SerdeJson)
+            .await
+            .unwrap();
+
+        assert_eq!(reply, None);
+    }
+
+    #[tokio::test]
+    async fn test_ack_registry_reject_fails_waiter() {
+        let registry = WsIoEventAckRegistry::new();
+        let (ack_id, rx) = registry.register();
+        assert!(registry.reject(ack_id, "internal".into()));
+        assert_eq!(registry.pending_count(), 0);
+
+        let result = registry
+            .wait::<()>(ack_id, rx, Duration::from_secs(1), &WsIoPacketCodec::SerdeJson)
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("failed on the peer: internal"));
+    }
+
+    #[tokio::test]
+    async fn test_ack_registry_timeout_removes_pending_ack() {
+        let registry = WsIoEventAckRegistry::new();
+        let (ack_id, rx) = registry.register();
+
+        let result = registry
+            .wait::<()>(ack_id, rx, Duration::from_millis(10), &WsIoPacketCodec::SerdeJson)
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("Timed out"));
+        assert_eq!(registry.pending_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_ack_registry_clear_fails_waiters() {
+        let registry = WsIoEventAckRegistry::new();
+        let (first_ack_id, rx) = registry.register();
+        let (second_ack_id, _rx) = registry.register();
+        assert_ne!(first_ack_id, second_ack_id);
+
+        registry.clear();
+
+        let result = registry
+            .wait::<()>(first_ack_id, rx, Duration::from_secs(1), &WsIoPacketCodec::SerdeJson)
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("dropped"));
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use tokio::sync::{
+    Semaphore,
+    mpsc::Sender,
+};
+
+// Types
+pub(crate) type QueuedHandlerTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+// Constants/Statics
+
+/// Maximum number of events a blocking executor runs at once.
+pub const BLOCKING_TASK_LIMIT: usize = 8;
+
+/// Maximum number of events a sequential executor queues behind the running
+/// one.
+pub const SEQUENTIAL_QUEUE_CAPACITY: usize = 64;
+
+// Enums
+
+/// How the handlers of an incoming event are run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WsIoHandlerExecutionMode {
+    /// Runs the handlers on tokio's blocking thread pool, for CPU-heavy work
+    /// that would otherwise stall the async workers. Handles at most
+    /// [`BLOCKING_TASK_LIMIT`] events at once, like [`Self::Bounded`].
+    Blocking,
+
+    /// Handles at most this many events at once; further events wait for a
+    /// running one to finish before the connection reads on, slowing down the
+    /// peer. Handlers of one event run one after another.
+    Bounded(usize),
+
+    /// Spawns a task per handler without any limit.
+    #[default]
+    Concurrent,
+
+    /// Handles events one at a time in the order they arrived. Handlers of one
+    /// event run one after another. Once [`SEQUENTIAL_QUEUE_CAPACITY`] events
+    /// wait, the connection reads on only as the queue drains.
+    Sequential,
+}
+
+// Structs
+
+/// Per-connection state backing a [`WsIoHandlerExecutionMode`].
+///
+/// Holds the semaphore of a bounded or blocking mode or the queue of a
+/// sequential one, so
+/// every event handled through the same executor shares its limit or order.
+#[derive(Debug)]
+pub struct WsIoHandlerExecutor {
+    mode: WsIoHandlerExecutionMode,
+    pub(crate) semaphore: Option<Arc<Semaphore>>,
+    pub(crate) sequential_task_tx: Mutex<Option<Sender<QueuedHandlerTask>>>,
+}
+
+impl WsIoHandlerExecutor {
+    #[inline]
+    pub fn new(mode: WsIoHandlerExecutionMode) -> Self {
+        Self {
+            mode,
+            semaphore: match mode {
+                WsIoHandlerExecutionMode::Blocking => Some(Arc::new(Semaphore::new(BLOCKING_TASK_LIMIT))),
+                WsIoHandlerExecutionMode::Bounded(limit) => Some(Arc::new(Semaphore::new(limit.max(1)))),
+                _ => None,
+            },
+            sequential_task_tx: Mutex::new(None),
+        }
+    }
+
+    // Public methods
+    #[inline]
+    pub fn mode(&self) -> WsIoHandlerExecutionMode {
+        self.mode
+    }
+}
+
+impl Default for WsIoHandlerExecutor {
+    #[inline]
+    fn default() -> Self {
+        Self::new(WsIoHandlerExecutionMode::default())
+    }
+}
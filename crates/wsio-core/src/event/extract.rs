@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use anyhow::{
+    Context,
+    Result,
+};
+use serde::{
+    Serialize,
+    de::DeserializeOwned,
+};
+
+use super::raw::WsIoRawEvent;
+use crate::traits::{
+    ack::sender::AckSender,
+    event::extract::FromEventContext,
+};
+
+// Structs
+
+/// Everything extractors can read about an incoming event: the connection or
+/// session it arrived on, the raw event and the ack id it was sent with.
+#[derive(Debug)]
+pub struct WsIoEventContext<C> {
+    ack_id: Option<u64>,
+    ctx: Arc<C>,
+    raw_event: Arc<WsIoRawEvent>,
+}
+
+impl<C> WsIoEventContext<C> {
+    #[inline]
+    pub(crate) fn new(ack_id: Option<u64>, ctx: Arc<C>, raw_event: Arc<WsIoRawEvent>) -> Self {
+        Self { ack_id, ctx, raw_event }
+    }
+
+    // Public methods
+
+    /// Id the peer is waiting on a reply for, if the event was emitted with an
+    /// ack.
+    #[inline]
+    pub fn ack_id(&self) -> Option<u64> {
+        self.ack_id
+    }
+
+    #[inline]
+    pub fn ctx(&self) -> &Arc<C> {
+        &self.ctx
+    }
+
+    #[inline]
+    pub fn raw_event(&self) -> &Arc<WsIoRawEvent> {
+        &self.raw_event
+    }
+}
+
+/// Replies to an event emitted with an ack.
+///
+/// Extracting it never fails; when the peer did not ask for an ack, replying
+/// is a no-op.
+#[derive(Debug)]
+pub struct WsIoAck<C> {
+    ack_id: Option<u64>,
+    ctx: Arc<C>,
+    raw_event: Arc<WsIoRawEvent>,
+}
+
+impl<C: AckSender> WsIoAck<C> {
+    // Public methods
+    #[inline]
+    pub fn is_requested(&self) -> bool {
+        self.ack_id.is_some()
+    }
+
+    /// Encodes `data` with the codec of the event and sends it as the ack reply.
+    pub async fn reply<R: Serialize>(self, data: &R) -> Result<()> {
+        let Some(ack_id) = self.ack_id else {
+            return Ok(());
+        };
+
+        self.ctx
+            .send_ack(ack_id, Some(self.raw_event.packet_codec().encode_data(data)?))
+            .await
+    }
+}
+
+impl<C> FromEventContext<C> for WsIoAck<C> {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<C>) -> Result<Self> {
+        Ok(Self {
+            ack_id: event_ctx.ack_id,
+            ctx: event_ctx.ctx.clone(),
+            raw_event: event_ctx.raw_event.clone(),
+        })
+    }
+}
+
+/// The event payload decoded as `D`.
+///
+/// Each handler decodes the payload on its own, so handlers of one event may
+/// take different payload types. An event without data fails to extract.
+#[derive(Debug)]
+pub struct WsIoData<D>(pub D);
+
+impl<C, D: DeserializeOwned> FromEventContext<C> for WsIoData<D> {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<C>) -> Result<Self> {
+        event_ctx
+            .raw_event
+            .decode()
+            .context("Failed to decode event data")?
+            .map(Self)
+            .context("Event carried no data")
+    }
+}
+
+/// Name of the incoming event, useful for handlers shared by several events.
+#[derive(Clone, Debug)]
+pub struct WsIoEventName(pub Arc<str>);
+
+impl<C> FromEventContext<C> for WsIoEventName {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<C>) -> Result<Self> {
+        Ok(Self(event_ctx.raw_event.name().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::{
+        packet::codecs::WsIoPacketCodec,
+        traits::packet::codec::PacketCodec,
+    };
+
+    #[derive(Default)]
+    struct DummyConnection {
+        sent_acks: Mutex<Vec<(u64, Option<Vec<u8>>)>>,
+    }
+
+    impl AckSender for DummyConnection {
+        async fn send_ack(&self, ack_id: u64, data: Option<Vec<u8>>) -> Result<()> {
+            self.sent_acks.lock().push((ack_id, data));
+            Ok(())
+        }
+
+        async fn send_ack_error(&self, _ack_id: u64, _code: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn create_event_ctx<D: Serialize>(ack_id: Option<u64>, data: Option<&D>) -> WsIoEventContext<DummyConnection> {
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let data = data.map(|data| Bytes::from(packet_codec.encode_data(data).unwrap()));
+        WsIoEventContext::new(
+            ack_id,
+            Arc::new(DummyConnection::default()),
+            Arc::new(WsIoRawEvent::new("chat".into(), data, packet_codec)),
+        )
+    }
+
+    #[test]
+    fn test_extract_data_and_event_name() {
+        let event_ctx = create_event_ctx(None, Some(&(1, "hi")));
+        let WsIoData((id, text)) = WsIoData::<(u32, String)>::from_event_context(&event_ctx).unwrap();
+        assert_eq!((id, text.as_str()), (1, "hi"));
+        assert_eq!(&*WsIoEventName::from_event_context(&event_ctx).unwrap().0, "chat");
+
+        // Mismatched and missing payloads fail, unless the extractor is optional
+        assert!(WsIoData::<bool>::from_event_context(&event_ctx).is_err());
+        let event_ctx = create_event_ctx::<()>(None, None);
+        let err = WsIoData::<u32>::from_event_context(&event_ctx).unwrap_err();
+        assert_eq!(err.to_string(), "Event carried no data");
+        assert!(
+            Option::<WsIoData<u32>>::from_event_context(&event_ctx)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_ack_replies_only_when_requested() {
+        let event_ctx = create_event_ctx::<()>(None, None);
+        let ack = WsIoAck::from_event_context(&event_ctx).unwrap();
+        assert!(!ack.is_requested());
+        ack.reply(&1).await.unwrap();
+        assert!(event_ctx.ctx().sent_acks.lock().is_empty());
+
+        let event_ctx = create_event_ctx::<()>(Some(7), None);
+        WsIoAck::from_event_context(&event_ctx)
+            .unwrap()
+            .reply(&"done")
+            .await
+            .unwrap();
+
+        assert_eq!(*event_ctx.ctx().sent_acks.lock(), [(7, Some(br#""done""#.to_vec()))]);
+    }
+}
@@ -1 +1,5 @@
+pub mod ack;
+pub mod execution;
+pub mod extract;
+pub mod raw;
 pub mod registry;
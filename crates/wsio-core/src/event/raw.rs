@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+
+use crate::traits::packet::codec::PacketCodec;
+
+// Structs
+
+/// An incoming event passed to handlers before its payload is decoded.
+///
+/// Keeps the event name, the encoded payload bytes and the packet codec of the
+/// connection it arrived on, so handlers can forward the bytes untouched or
+/// decode them on demand.
+#[derive(Clone, Debug)]
+pub struct WsIoRawEvent {
+    data: Option<Bytes>,
+    name: Arc<str>,
+    packet_codec: Arc<dyn PacketCodec>,
+}
+
+impl WsIoRawEvent {
+    #[inline]
+    pub fn new(name: Arc<str>, data: Option<Bytes>, packet_codec: Arc<dyn PacketCodec>) -> Self {
+        Self {
+            data,
+            name,
+            packet_codec,
+        }
+    }
+
+    // Public methods
+
+    /// Encoded payload bytes, or `None` when the event carried no data.
+    #[inline]
+    pub fn data(&self) -> Option<&Bytes> {
+        self.data.as_ref()
+    }
+
+    /// Decodes the payload as `D` with the packet codec it was encoded with.
+    #[inline]
+    pub fn decode<D: DeserializeOwned>(&self) -> Result<Option<D>> {
+        self.data
+            .as_deref()
+            .map(|bytes| self.packet_codec.decode_data_as(bytes))
+            .transpose()
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn packet_codec(&self) -> &dyn PacketCodec {
+        &*self.packet_codec
+    }
+}
@@ -3,7 +3,6 @@ use std::{
         Any,
         TypeId,
     },
-    collections::hash_map::Entry,
     fmt::{
         Debug as FmtDebug,
         Formatter,
@@ -19,22 +18,58 @@ use std::{
             Ordering,
         },
     },
+    time::Duration,
 };
 
 use anyhow::Result;
+use arc_swap::ArcSwap;
+use bytes::Bytes;
 use kikiutils::types::fx_collections::FxHashMap;
-use parking_lot::RwLock;
-use serde::de::DeserializeOwned;
+use serde::{
+    Serialize,
+    de::DeserializeOwned,
+};
+use tokio::time::timeout;
 
+use super::{
+    execution::{
+        WsIoHandlerExecutionMode,
+        WsIoHandlerExecutor,
+    },
+    extract::WsIoEventContext,
+    raw::WsIoRawEvent,
+};
 use crate::{
-    packet::codecs::WsIoPacketCodec,
-    traits::task::spawner::TaskSpawner,
+    error::{
+        WsIoError,
+        WsIoErrorKind,
+        catch_handler,
+        connect::WsIoConnectError,
+    },
+    traits::{
+        ack::sender::AckSender,
+        error::reporter::ErrorReporter,
+        event::extract::EventHandler,
+        packet::codec::PacketCodec,
+        task::spawner::TaskSpawner,
+    },
 };
 
 // Types
-type DataDecoder = fn(&[u8], WsIoPacketCodec) -> Result<Arc<dyn Any + Send + Sync>>;
+type DataDecoder = fn(&[u8], &dyn PacketCodec) -> Result<Arc<dyn Any + Send + Sync>>;
 type Handler<C> = Arc<
-    dyn Fn(Arc<C>, Arc<dyn Any + Send + Sync>) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>
+    dyn Fn(
+            Arc<C>,
+            Arc<dyn Any + Send + Sync>,
+            Arc<dyn PacketCodec>,
+        ) -> Pin<Box<dyn Future<Output = Result<Option<Vec<u8>>>> + Send + 'static>>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+type RawHandler<C> = Arc<
+    dyn Fn(Arc<C>, Arc<WsIoRawEvent>, Option<u64>) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>
         + Send
         + Sync
         + 'static,
@@ -45,36 +80,227 @@ static EMPTY_EVENT_DATA_ANY_ARC: LazyLock<Arc<dyn Any + Send + Sync>> = LazyLock
 
 // Structs
 struct EventEntry<C> {
+    ack_handler_id: Option<u32>,
     data_decoder: DataDecoder,
     data_type_id: TypeId,
-    handlers: RwLock<FxHashMap<u32, Handler<C>>>,
+    event: Arc<str>,
+    handlers: Vec<(u32, Handler<C>)>,
+}
+
+impl<C> EventEntry<C> {
+    /// The ack id `handler_id` answers, which only the ack handler of the
+    /// event gets.
+    #[inline]
+    fn handler_ack_id(&self, handler_id: u32, ack_id: Option<u64>) -> Option<u64> {
+        ack_id.filter(|_| self.ack_handler_id == Some(handler_id))
+    }
+}
+
+impl<C> Clone for EventEntry<C> {
+    fn clone(&self) -> Self {
+        Self {
+            ack_handler_id: self.ack_handler_id,
+            data_decoder: self.data_decoder,
+            data_type_id: self.data_type_id,
+            event: self.event.clone(),
+            handlers: self.handlers.clone(),
+        }
+    }
 }
 
 impl<C> FmtDebug for EventEntry<C> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        let mut debug = f.debug_struct("EventEntry");
-        debug
+        f.debug_struct("EventEntry")
+            .field("ack_handler_id", &self.ack_handler_id)
             .field("data_decoder", &self.data_decoder)
-            .field("data_type_id", &self.data_type_id);
+            .field("data_type_id", &self.data_type_id)
+            .field("event", &self.event)
+            .field("handlers_len", &self.handlers.len())
+            .finish()
+    }
+}
 
-        match self.handlers.try_read() {
-            Some(handlers) => {
-                debug.field("handlers_len", &handlers.len());
-            },
-            None => {
-                debug.field("handlers", &"<locked>");
-            },
+struct PatternEntry<C> {
+    handler: RawHandler<C>,
+    handler_id: u32,
+    pattern: String,
+}
+
+impl<C> Clone for PatternEntry<C> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: self.handler.clone(),
+            handler_id: self.handler_id,
+            pattern: self.pattern.clone(),
+        }
+    }
+}
+
+impl<C> FmtDebug for PatternEntry<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("PatternEntry")
+            .field("handler", &"<handler>")
+            .field("handler_id", &self.handler_id)
+            .field("pattern", &self.pattern)
+            .finish()
+    }
+}
+
+struct RawEventEntry<C> {
+    /// Ids of the extractor handlers, which may answer an ack through
+    /// `WsIoAck`.
+    ack_handler_ids: Vec<u32>,
+    handlers: Vec<(u32, RawHandler<C>)>,
+}
+
+impl<C> Clone for RawEventEntry<C> {
+    fn clone(&self) -> Self {
+        Self {
+            ack_handler_ids: self.ack_handler_ids.clone(),
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<C> Default for RawEventEntry<C> {
+    fn default() -> Self {
+        Self {
+            ack_handler_ids: Vec::new(),
+            handlers: Vec::new(),
+        }
+    }
+}
+
+impl<C> FmtDebug for RawEventEntry<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("RawEventEntry")
+            .field("ack_handler_ids", &self.ack_handler_ids)
+            .field("handlers_len", &self.handlers.len())
+            .finish()
+    }
+}
+
+/// Immutable snapshot of every handler in a registry.
+///
+/// Dispatch reads the current snapshot without locking; registering or removing
+/// a handler clones the table, edits the copy and swaps it in.
+#[derive(Debug)]
+struct RegistryTable<C> {
+    event_entries: FxHashMap<String, Arc<EventEntry<C>>>,
+    event_executors: FxHashMap<String, Arc<WsIoHandlerExecutor>>,
+    event_handler_timeouts: FxHashMap<String, Duration>,
+    pattern_entries: Vec<PatternEntry<C>>,
+    raw_event_entries: FxHashMap<String, RawEventEntry<C>>,
+}
+
+impl<C> RegistryTable<C> {
+    // Private methods
+
+    /// Adds the pattern handlers whose pattern matches `event` to `handlers`,
+    /// followed by the raw handlers registered for it when `include_raw` is set.
+    ///
+    /// Returns whether any added handler may answer an ack.
+    #[inline]
+    fn extend_matching_raw_handlers(&self, handlers: &mut Vec<RawHandler<C>>, event: &str, include_raw: bool) -> bool {
+        handlers.extend(
+            self.pattern_entries
+                .iter()
+                .filter(|pattern_entry| matches_event_pattern(&pattern_entry.pattern, event))
+                .map(|pattern_entry| pattern_entry.handler.clone()),
+        );
+
+        if include_raw && let Some(raw_event_entry) = self.raw_event_entries.get(event) {
+            handlers.extend(raw_event_entry.handlers.iter().map(|(_, handler)| handler.clone()));
+            return !raw_event_entry.ack_handler_ids.is_empty();
+        }
+
+        false
+    }
+
+    /// Whether any typed, raw or extractor handler is registered for exactly
+    /// `event`.
+    #[inline]
+    fn has_event_handlers(&self, event: &str) -> bool {
+        self.event_entries.contains_key(event) || self.raw_event_entries.contains_key(event)
+    }
+
+    fn remove_handler(&mut self, event: &str, handler_id: u32) {
+        if let Some(index) = self
+            .pattern_entries
+            .iter()
+            .position(|pattern_entry| pattern_entry.handler_id == handler_id)
+        {
+            self.pattern_entries.remove(index);
+            return;
         }
 
-        debug.finish()
+        if let Some(raw_event_entry) = self.raw_event_entries.get_mut(event)
+            && let Some(index) = raw_event_entry.handlers.iter().position(|(id, _)| *id == handler_id)
+        {
+            raw_event_entry.handlers.remove(index);
+            raw_event_entry.ack_handler_ids.retain(|id| *id != handler_id);
+            if raw_event_entry.handlers.is_empty() {
+                self.raw_event_entries.remove(event);
+            }
+
+            return;
+        }
+
+        let Some(event_entry) = self.event_entries.get(event) else {
+            return;
+        };
+
+        let Some(index) = event_entry.handlers.iter().position(|(id, _)| *id == handler_id) else {
+            return;
+        };
+
+        if event_entry.handlers.len() == 1 {
+            self.event_entries.remove(event);
+            return;
+        }
+
+        let mut event_entry = (**event_entry).clone();
+        event_entry.handlers.remove(index);
+        if event_entry.ack_handler_id == Some(handler_id) {
+            event_entry.ack_handler_id = None;
+        }
+
+        self.event_entries.insert(event.into(), Arc::new(event_entry));
+    }
+}
+
+impl<C> Clone for RegistryTable<C> {
+    fn clone(&self) -> Self {
+        Self {
+            event_entries: self.event_entries.clone(),
+            event_executors: self.event_executors.clone(),
+            event_handler_timeouts: self.event_handler_timeouts.clone(),
+            pattern_entries: self.pattern_entries.clone(),
+            raw_event_entries: self.raw_event_entries.clone(),
+        }
+    }
+}
+
+impl<C> Default for RegistryTable<C> {
+    fn default() -> Self {
+        Self {
+            event_entries: FxHashMap::default(),
+            event_executors: FxHashMap::default(),
+            event_handler_timeouts: FxHashMap::default(),
+            pattern_entries: Vec::new(),
+            raw_event_entries: FxHashMap::default(),
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct WsIoEventRegistry<C: Send + Sync + 'static, S: TaskSpawner> {
     _task_spawner: PhantomData<S>,
-    event_entries: RwLock<FxHashMap<String, Arc<EventEntry<C>>>>,
+    default_executor: Arc<WsIoHandlerExecutor>,
+    default_handler_timeout: Option<Duration>,
     next_handler_id: AtomicU32,
+    shared: Option<Arc<WsIoEventRegistry<C, S>>>,
+    table: ArcSwap<RegistryTable<C>>,
 }
 
 impl<C: Send + Sync + 'static, S: TaskSpawner> Default for WsIoEventRegistry<C, S> {
@@ -86,68 +312,345 @@ impl<C: Send + Sync + 'static, S: TaskSpawner> Default for WsIoEventRegistry<C,
 impl<C: Send + Sync + 'static, S: TaskSpawner> WsIoEventRegistry<C, S> {
     #[inline]
     pub fn new() -> Self {
+        Self::with_execution_mode(WsIoHandlerExecutionMode::Concurrent)
+    }
+
+    /// Creates a registry whose events run in `mode` unless overridden per
+    /// event with [`set_execution_mode`](Self::set_execution_mode).
+    #[inline]
+    pub fn with_execution_mode(mode: WsIoHandlerExecutionMode) -> Self {
         Self {
             _task_spawner: PhantomData,
-            event_entries: RwLock::new(FxHashMap::default()),
+            default_executor: Arc::new(WsIoHandlerExecutor::new(mode)),
+            default_handler_timeout: None,
             next_handler_id: AtomicU32::new(0),
+            shared: None,
+            table: ArcSwap::from_pointee(RegistryTable::default()),
         }
     }
 
-    // Public methods
+    /// Creates a registry that also dispatches to the handlers of `shared`,
+    /// such as handlers registered once for every connection of a namespace.
+    ///
+    /// Handlers of this registry for an event replace the typed, raw and
+    /// extractor handlers `shared` has for the same event; pattern handlers of
+    /// both always run. Events without an execution mode of their own take the
+    /// mode `shared` sets for them, but still run on an executor of this
+    /// registry, so limits and order stay per registry.
+    #[inline]
+    pub fn with_shared(mode: WsIoHandlerExecutionMode, shared: Arc<Self>) -> Self {
+        Self {
+            shared: Some(shared),
+            ..Self::with_execution_mode(mode)
+        }
+    }
+
+    /// Cuts off every handler that runs longer than `duration`, unless its
+    /// event has its own timeout set with
+    /// [`set_handler_timeout`](Self::set_handler_timeout).
+    ///
+    /// A handler that is cut off is dropped and reported as
+    /// [`WsIoErrorKind::Timeout`]; an ack handler answers its ack with
+    /// [`WsIoConnectError::TIMEOUT`]. Without a timeout handlers run
+    /// until they finish or the task spawner is cancelled.
+    #[inline]
+    pub fn with_default_handler_timeout(mut self, duration: Option<Duration>) -> Self {
+        self.default_handler_timeout = duration;
+        self
+    }
+
+    // Private methods
+    fn insert_handler<D: DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        event: &str,
+        handler: Handler<C>,
+        is_ack_handler: bool,
+    ) -> u32 {
+        let data_type_id = TypeId::of::<D>();
+        let handler_id = self.next_handler_id.fetch_add(1, Ordering::Relaxed);
+        let ack_handler_id = is_ack_handler.then_some(handler_id);
+        self.update_table(|table| {
+            let event_entry = match table.event_entries.get(event) {
+                Some(event_entry) => {
+                    assert_eq!(
+                        event_entry.data_type_id, data_type_id,
+                        "Event '{}' already registered with a different data type — each event name must correspond to exactly one payload type.",
+                        event
+                    );
+
+                    assert!(
+                        !is_ack_handler || event_entry.ack_handler_id.is_none(),
+                        "Event '{}' already has an ack handler — each event can be acknowledged by only one handler.",
+                        event
+                    );
+
+                    let mut event_entry = (**event_entry).clone();
+                    event_entry.ack_handler_id = event_entry.ack_handler_id.or(ack_handler_id);
+                    event_entry.handlers.push((handler_id, handler.clone()));
+                    event_entry
+                },
+                None => EventEntry {
+                    ack_handler_id,
+                    data_decoder: decode_data_as_any_arc::<D>,
+                    data_type_id,
+                    event: event.into(),
+                    handlers: vec![(handler_id, handler.clone())],
+                },
+            };
+
+            table.event_entries.insert(event.into(), Arc::new(event_entry));
+        });
+
+        handler_id
+    }
+
+    fn insert_raw_handler(&self, event: &str, handler: RawHandler<C>, is_ack_handler: bool) -> u32 {
+        let handler_id = self.next_handler_id.fetch_add(1, Ordering::Relaxed);
+        self.update_table(|table| {
+            let raw_event_entry = table.raw_event_entries.entry(event.into()).or_default();
+            if is_ack_handler {
+                raw_event_entry.ack_handler_ids.push(handler_id);
+            }
+
+            raw_event_entry.handlers.push((handler_id, handler.clone()));
+        });
+
+        handler_id
+    }
+
+    /// Returns the executor `event` runs on.
+    ///
+    /// An event with a mode set only on the shared registry gets an executor of
+    /// its own here the first time it is dispatched, so its limit or order is
+    /// not shared with other registries.
+    fn resolve_executor(&self, table: &RegistryTable<C>, event: &str) -> Arc<WsIoHandlerExecutor> {
+        if let Some(executor) = table.event_executors.get(event) {
+            return executor.clone();
+        }
+
+        let Some(shared_executor) = self
+            .shared
+            .as_ref()
+            .and_then(|shared| shared.table.load().event_executors.get(event).cloned())
+        else {
+            return self.default_executor.clone();
+        };
+
+        let executor = Arc::new(WsIoHandlerExecutor::new(shared_executor.mode()));
+        self.update_table(|table| {
+            table
+                .event_executors
+                .entry(event.into())
+                .or_insert_with(|| executor.clone());
+        });
+
+        self.table.load().event_executors[event].clone()
+    }
+
     #[inline]
-    pub fn dispatch_event_packet(
+    fn update_table<F: Fn(&mut RegistryTable<C>)>(&self, update: F) {
+        self.table.rcu(|old_table| {
+            let mut new_table = (**old_table).clone();
+            update(&mut new_table);
+            new_table
+        });
+    }
+
+    // Public methods
+
+    /// Runs the handlers of `event` as required by its execution mode.
+    ///
+    /// Returns once the handlers are handed to the task spawner. In a bounded
+    /// mode this waits for a free slot, so a peer sending faster than its
+    /// handlers finish is slowed down instead of piling up tasks.
+    ///
+    /// An ack no handler of `event` can answer fails right away with
+    /// [`WsIoConnectError::NO_ACK_HANDLER`].
+    pub async fn dispatch_event_packet(
         &self,
         ctx: Arc<C>,
         event: &str,
-        packet_codec: &WsIoPacketCodec,
-        packet_data: Option<Vec<u8>>,
+        packet_codec: &Arc<dyn PacketCodec>,
+        packet_data: Option<Bytes>,
+        ack_id: Option<u64>,
         task_spawner: &Arc<S>,
-    ) {
-        let Some(event_entry) = self.event_entries.read().get(event).cloned() else {
+    ) where
+        C: AckSender + ErrorReporter,
+    {
+        let table = self.table.load();
+        let overrides_shared = table.has_event_handlers(event);
+        let mut handler_timeout = table.event_handler_timeouts.get(event).copied();
+        let mut raw_handlers = Vec::new();
+        let mut event_entry = None;
+        let mut has_raw_ack_handler = false;
+        if let Some(shared) = &self.shared {
+            let shared_table = shared.table.load();
+            has_raw_ack_handler =
+                shared_table.extend_matching_raw_handlers(&mut raw_handlers, event, !overrides_shared);
+            if !overrides_shared {
+                event_entry = shared_table.event_entries.get(event).cloned();
+            }
+
+            handler_timeout = handler_timeout.or_else(|| shared_table.event_handler_timeouts.get(event).copied());
+        }
+
+        let handler_timeout = handler_timeout.or(self.default_handler_timeout);
+
+        has_raw_ack_handler |= table.extend_matching_raw_handlers(&mut raw_handlers, event, true);
+        if overrides_shared {
+            event_entry = table.event_entries.get(event).cloned();
+        }
+
+        // Nothing would ever answer the ack, so fail it now instead of leaving
+        // the peer waiting until its ack timeout
+        if let Some(ack_id) = ack_id
+            && !has_raw_ack_handler
+            && event_entry
+                .as_ref()
+                .is_none_or(|event_entry| event_entry.ack_handler_id.is_none())
+        {
+            let ctx = ctx.clone();
+            task_spawner.spawn_task(async move { ctx.send_ack_error(ack_id, WsIoConnectError::NO_ACK_HANDLER).await });
+        }
+
+        if raw_handlers.is_empty() && event_entry.is_none() {
+            return;
+        }
+
+        let raw_event = (!raw_handlers.is_empty()).then(|| {
+            Arc::new(WsIoRawEvent::new(
+                event.into(),
+                packet_data.clone(),
+                packet_codec.clone(),
+            ))
+        });
+
+        let executor = self.resolve_executor(&table, event);
+        drop(table);
+
+        let packet_codec = packet_codec.clone();
+        if executor.mode() != WsIoHandlerExecutionMode::Concurrent {
+            // Every handler of the event runs inside a single task, so the
+            // executor can order or limit whole events
+            let future = async move {
+                if let Some(raw_event) = raw_event {
+                    for handler in raw_handlers {
+                        run_raw_handler(handler, ctx.clone(), raw_event.clone(), ack_id, handler_timeout).await;
+                    }
+                }
+
+                let Some(event_entry) = event_entry else {
+                    return Ok(());
+                };
+
+                let Some(data) = decode_event_data(&event_entry, &ctx, packet_data, &*packet_codec, ack_id).await
+                else {
+                    return Ok(());
+                };
+
+                for (handler_id, handler) in &event_entry.handlers {
+                    let _ = run_handler(
+                        handler.clone(),
+                        ctx.clone(),
+                        data.clone(),
+                        packet_codec.clone(),
+                        event_entry.handler_ack_id(*handler_id, ack_id),
+                        event_entry.event.clone(),
+                        handler_timeout,
+                    )
+                    .await;
+                }
+
+                Ok(())
+            };
+
+            // Boxed because the compiler cannot prove the opaque future of the
+            // trait method `Send` for every lifetime of the read loops awaiting it
+            let spawn: Pin<Box<dyn Future<Output = ()> + Send + '_>> =
+                Box::pin(task_spawner.spawn_handler_task(&executor, future));
+
+            spawn.await;
+            return;
+        }
+
+        if let Some(raw_event) = raw_event {
+            for handler in raw_handlers {
+                let future = run_raw_handler(handler, ctx.clone(), raw_event.clone(), ack_id, handler_timeout);
+                task_spawner.spawn_task(async move {
+                    future.await;
+                    Ok(())
+                });
+            }
+        }
+
+        let Some(event_entry) = event_entry else {
             return;
         };
 
-        let packet_codec = *packet_codec;
-        let task_spawner_clone = task_spawner.clone();
+        let extra_task_spawner = (event_entry.handlers.len() > 1).then(|| task_spawner.clone());
         task_spawner.spawn_task(async move {
-            let data = match packet_data {
-                Some(bytes) => match (event_entry.data_decoder)(&bytes, packet_codec) {
-                    Ok(data) => data,
-                    Err(_) => return Ok(()),
-                },
-                None => EMPTY_EVENT_DATA_ANY_ARC.clone(),
+            let Some(data) = decode_event_data(&event_entry, &ctx, packet_data, &*packet_codec, ack_id).await else {
+                return Ok(());
+            };
+
+            let Some(((last_handler_id, last_handler), handlers)) = event_entry.handlers.split_last() else {
+                return Ok(());
             };
 
-            let handlers = event_entry.handlers.read().values().cloned().collect::<Vec<_>>();
-            for handler in handlers {
-                let ctx = ctx.clone();
-                let data = data.clone();
-                task_spawner_clone.spawn_task(handler(ctx, data));
+            // The last handler runs in this task, so a single handler costs no
+            // further spawn
+            if let Some(task_spawner) = extra_task_spawner {
+                for (handler_id, handler) in handlers {
+                    task_spawner.spawn_task(run_handler(
+                        handler.clone(),
+                        ctx.clone(),
+                        data.clone(),
+                        packet_codec.clone(),
+                        event_entry.handler_ack_id(*handler_id, ack_id),
+                        event_entry.event.clone(),
+                        handler_timeout,
+                    ));
+                }
             }
 
-            Ok(())
+            let ack_id = event_entry.handler_ack_id(*last_handler_id, ack_id);
+            run_handler(
+                last_handler.clone(),
+                ctx,
+                data,
+                packet_codec,
+                ack_id,
+                event_entry.event.clone(),
+                handler_timeout,
+            )
+            .await
         });
     }
 
+    /// Removes every typed and raw handler registered for `event`.
     #[inline]
     pub fn off(&self, event: &str) {
-        self.event_entries.write().remove(event);
+        self.update_table(|table| {
+            table.event_entries.remove(event);
+            table.raw_event_entries.remove(event);
+        });
     }
 
+    /// Removes the handler registered under `handler_id`, either for `event` or
+    /// as a pattern handler for the pattern `event`.
     #[inline]
     pub fn off_by_handler_id(&self, event: &str, handler_id: u32) {
-        if let Some(event_entry) = self.event_entries.read().get(event) {
-            event_entry.handlers.write().remove(&handler_id);
-            if !event_entry.handlers.read().is_empty() {
-                return;
-            }
-        }
+        self.update_table(|table| table.remove_handler(event, handler_id));
+    }
 
-        if let Entry::Occupied(entry) = self.event_entries.write().entry(event.into())
-            && entry.get().handlers.read().is_empty()
-        {
-            entry.remove();
-        }
+    /// Removes every pattern handler registered with exactly `pattern`.
+    #[inline]
+    pub fn off_pattern(&self, pattern: &str) {
+        self.update_table(|table| {
+            table
+                .pattern_entries
+                .retain(|pattern_entry| pattern_entry.pattern != pattern);
+        });
     }
 
     #[inline]
@@ -157,74 +660,352 @@ impl<C: Send + Sync + 'static, S: TaskSpawner> WsIoEventRegistry<C, S> {
         Fut: Future<Output = Result<()>> + Send + 'static,
         D: DeserializeOwned + Send + Sync + 'static,
     {
-        let data_type_id = TypeId::of::<D>();
+        self.insert_handler::<D>(
+            event,
+            Arc::new(move |ctx, data, _| {
+                let Some(data) = downcast_data::<D>(data) else {
+                    return Box::pin(async { Ok(None) });
+                };
+
+                let future = handler(ctx, data);
+                Box::pin(async move {
+                    future.await?;
+                    Ok(None)
+                })
+            }),
+            false,
+        )
+    }
+
+    /// Registers a handler whose arguments are resolved by
+    /// [`FromEventContext`](crate::traits::event::extract::FromEventContext)
+    /// extractors.
+    ///
+    /// Extractor handlers are stored with the raw handlers: each one decodes the
+    /// payload itself, so they do not take part in the payload type check of
+    /// `on` and may share an event name with handlers of any payload type. A
+    /// failed extraction skips the handler and is reported as
+    /// [`WsIoErrorKind::Extract`].
+    #[inline]
+    pub fn on_extract<H, T>(&self, event: &str, handler: H) -> u32
+    where
+        C: ErrorReporter,
+        H: EventHandler<C, T>,
+    {
+        self.insert_raw_handler(
+            event,
+            Arc::new(move |ctx, raw_event, ack_id| {
+                let event_ctx = WsIoEventContext::new(ack_id, ctx, raw_event);
+                handler.call(&event_ctx).unwrap_or_else(|err| {
+                    event_ctx.ctx().report_error(
+                        WsIoError::new(WsIoErrorKind::Extract(err)).with_event(event_ctx.raw_event().name()),
+                    );
+
+                    Box::pin(async { Ok(()) })
+                })
+            }),
+            true,
+        )
+    }
+
+    /// Registers a handler for every event whose name matches `pattern`.
+    ///
+    /// In patterns `*` matches any run of characters, so `chat:*` matches
+    /// `chat:message` and `*` matches every event. Pattern handlers receive the
+    /// event name and undecoded payload, run alongside the handlers registered
+    /// for the exact name, and never reply to acks.
+    #[inline]
+    pub fn on_pattern<H, Fut>(&self, pattern: &str, handler: H) -> u32
+    where
+        H: Fn(Arc<C>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let handler: RawHandler<C> = Arc::new(move |ctx, raw_event, _| Box::pin(handler(ctx, raw_event)));
+        let handler_id = self.next_handler_id.fetch_add(1, Ordering::Relaxed);
+        self.update_table(|table| {
+            table.pattern_entries.push(PatternEntry {
+                handler: handler.clone(),
+                handler_id,
+                pattern: pattern.into(),
+            });
+        });
+
+        handler_id
+    }
+
+    /// Registers a handler that receives `event` with its payload undecoded.
+    ///
+    /// Raw handlers do not take part in the payload type check of `on`, so they
+    /// can share an event name with typed handlers. They never reply to acks.
+    #[inline]
+    pub fn on_raw<H, Fut>(&self, event: &str, handler: H) -> u32
+    where
+        H: Fn(Arc<C>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.insert_raw_handler(
+            event,
+            Arc::new(move |ctx, raw_event, _| Box::pin(handler(ctx, raw_event))),
+            false,
+        )
+    }
+
+    /// Runs the handlers of `event` in `mode` instead of the registry default.
+    ///
+    /// Events with their own mode are ordered or limited independently of the
+    /// rest, e.g. a sequential event only waits for earlier events of the same
+    /// name. Modes set on a shared registry apply to every registry created
+    /// from it that has no mode of its own for `event`.
+    #[inline]
+    pub fn set_execution_mode(&self, event: &str, mode: WsIoHandlerExecutionMode) {
+        let executor = Arc::new(WsIoHandlerExecutor::new(mode));
+        self.update_table(|table| {
+            table.event_executors.insert(event.into(), executor.clone());
+        });
+    }
+
+    /// Cuts off the handlers of `event` after `duration` instead of the
+    /// registry default.
+    ///
+    /// Overrides set on a shared registry apply to every registry created
+    /// from it that has no override of its own for `event`.
+    #[inline]
+    pub fn set_handler_timeout(&self, event: &str, duration: Duration) {
+        self.update_table(|table| {
+            table.event_handler_timeouts.insert(event.into(), duration);
+        });
+    }
+
+    /// Registers a handler whose return value is encoded and sent back as the
+    /// ack reply when the incoming event requested one.
+    ///
+    /// When the handler fails, panics or times out, the ack is answered with
+    /// the error code [`WsIoConnectError::INTERNAL`] or
+    /// [`WsIoConnectError::TIMEOUT`] so the emitter fails right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `event` already has an ack handler, since an ack can only be
+    /// answered once.
+    #[inline]
+    pub fn on_with_ack<H, Fut, D, R>(&self, event: &str, handler: H) -> u32
+    where
+        H: Fn(Arc<C>, Arc<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+        D: DeserializeOwned + Send + Sync + 'static,
+        R: Serialize + Send + 'static,
+    {
+        self.insert_handler::<D>(
+            event,
+            Arc::new(move |ctx, data, packet_codec| {
+                let Some(data) = downcast_data::<D>(data) else {
+                    return Box::pin(async { Ok(None) });
+                };
+
+                let future = handler(ctx, data);
+                Box::pin(async move { Ok(Some(packet_codec.encode_data(&future.await?)?)) })
+            }),
+            true,
+        )
+    }
+}
+
+// Functions
+/// Decodes the event payload for the handlers of `event_entry`, reporting a
+/// payload that does not match their data type to `ctx` and failing the ack an
+/// ack handler would have answered.
+#[inline]
+async fn decode_event_data<C: AckSender + ErrorReporter>(
+    event_entry: &EventEntry<C>,
+    ctx: &C,
+    packet_data: Option<Bytes>,
+    packet_codec: &dyn PacketCodec,
+    ack_id: Option<u64>,
+) -> Option<Arc<dyn Any + Send + Sync>> {
+    let Some(bytes) = packet_data else {
+        return Some(EMPTY_EVENT_DATA_ANY_ARC.clone());
+    };
+
+    match (event_entry.data_decoder)(&bytes, packet_codec) {
+        Ok(data) => Some(data),
+        Err(err) => {
+            ctx.report_error(WsIoError::new(WsIoErrorKind::Decode(err)).with_event(&*event_entry.event));
+            if let Some(ack_id) = ack_id
+                && event_entry.ack_handler_id.is_some()
+            {
+                let _ = ctx.send_ack_error(ack_id, WsIoConnectError::INTERNAL).await;
+            }
 
-        let mut event_entries = self.event_entries.write();
-        let event_entry = match event_entries.entry(event.into()) {
-            Entry::Occupied(occupied) => {
-                let event_entry = occupied.into_mut();
-                assert_eq!(
-                    event_entry.data_type_id, data_type_id,
-                    "Event '{}' already registered with a different data type — each event name must correspond to exactly one payload type.",
-                    event
-                );
-
-                event_entry
+            None
+        },
+    }
+}
+
+#[inline]
+fn downcast_data<D: Send + Sync + 'static>(data: Arc<dyn Any + Send + Sync>) -> Option<Arc<D>> {
+    data.downcast().ok()
+}
+
+/// Matches `event` against `pattern`, where `*` matches any run of characters.
+pub(crate) fn matches_event_pattern(pattern: &str, event: &str) -> bool {
+    let (pattern, event) = (pattern.as_bytes(), event.as_bytes());
+    let (mut pattern_index, mut event_index) = (0, 0);
+    let mut backtrack = None;
+    while event_index < event.len() {
+        match pattern.get(pattern_index) {
+            Some(b'*') => {
+                backtrack = Some((pattern_index, event_index));
+                pattern_index += 1;
             },
-            Entry::Vacant(vacant) => vacant.insert(Arc::new(EventEntry {
-                data_decoder: decode_data_as_any_arc::<D>,
-                data_type_id,
-                handlers: RwLock::new(FxHashMap::default()),
-            })),
-        };
+            Some(&byte) if byte == event[event_index] => {
+                pattern_index += 1;
+                event_index += 1;
+            },
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and retry
+                Some((star_index, star_event_index)) => {
+                    pattern_index = star_index + 1;
+                    event_index = star_event_index + 1;
+                    backtrack = Some((star_index, star_event_index + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|&byte| byte == b'*')
+}
+
+/// Like [`catch_handler`], but gives up on the handler once `handler_timeout`
+/// elapses.
+#[inline]
+async fn catch_handler_within<T, F, Fut>(handler_timeout: Option<Duration>, handler: F) -> Result<T, WsIoErrorKind>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    match handler_timeout {
+        Some(duration) => timeout(duration, catch_handler(handler))
+            .await
+            .unwrap_or(Err(WsIoErrorKind::Timeout(duration))),
+        None => catch_handler(handler).await,
+    }
+}
+
+/// Runs `handler` and sends its reply when the event requested an ack.
+///
+/// Errors, panics and timeouts of the handler are reported to `ctx` and answer
+/// the ack with an error code.
+async fn run_handler<C: AckSender + ErrorReporter>(
+    handler: Handler<C>,
+    ctx: Arc<C>,
+    data: Arc<dyn Any + Send + Sync>,
+    packet_codec: Arc<dyn PacketCodec>,
+    ack_id: Option<u64>,
+    event: Arc<str>,
+    handler_timeout: Option<Duration>,
+) -> Result<()> {
+    let reply_data = match catch_handler_within(handler_timeout, || handler(ctx.clone(), data, packet_codec)).await {
+        Ok(reply_data) => reply_data,
+        Err(kind) => {
+            let error_code = match kind {
+                WsIoErrorKind::Timeout(_) => WsIoConnectError::TIMEOUT,
+                _ => WsIoConnectError::INTERNAL,
+            };
+
+            ctx.report_error(WsIoError::new(kind).with_event(&*event));
+            if let Some(ack_id) = ack_id {
+                ctx.send_ack_error(ack_id, error_code).await?;
+            }
+
+            return Ok(());
+        },
+    };
 
-        let handler_id = self.next_handler_id.fetch_add(1, Ordering::Relaxed);
-        event_entry.handlers.write().insert(
-            handler_id,
-            Arc::new(move |connection, data| {
-                if (*data).type_id() != data_type_id {
-                    return Box::pin(async { Ok(()) });
-                }
+    if let Some(ack_id) = ack_id
+        && let Some(reply_data) = reply_data
+    {
+        ctx.send_ack(ack_id, Some(reply_data)).await?;
+    }
 
-                Box::pin(handler(
-                    connection,
-                    #[allow(clippy::expect_used)]
-                    data.downcast()
-                        .expect("data type id matched handler registration but Arc::downcast failed"),
-                ))
-            }),
-        );
+    Ok(())
+}
 
-        handler_id
+/// Runs a raw or pattern handler, reporting its error, panic or timeout to
+/// `ctx`.
+async fn run_raw_handler<C: ErrorReporter>(
+    handler: RawHandler<C>,
+    ctx: Arc<C>,
+    raw_event: Arc<WsIoRawEvent>,
+    ack_id: Option<u64>,
+    handler_timeout: Option<Duration>,
+) {
+    if let Err(kind) = catch_handler_within(handler_timeout, || handler(ctx.clone(), raw_event.clone(), ack_id)).await {
+        ctx.report_error(WsIoError::new(kind).with_event(raw_event.name()));
     }
 }
 
-// Functions
 #[inline]
 fn decode_data_as_any_arc<D: DeserializeOwned + Send + Sync + 'static>(
     bytes: &[u8],
-    packet_codec: WsIoPacketCodec,
+    packet_codec: &dyn PacketCodec,
 ) -> Result<Arc<dyn Any + Send + Sync>> {
-    Ok(Arc::new(packet_codec.decode_data::<D>(bytes)?))
+    Ok(Arc::new(packet_codec.decode_data_as::<D>(bytes)?))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::{
-        AtomicBool,
-        AtomicU32,
-        Ordering,
+    use std::{
+        sync::atomic::{
+            AtomicBool,
+            AtomicU32,
+            Ordering,
+        },
+        time::Duration,
     };
 
     use tokio::{
         spawn,
         task::yield_now,
+        time::sleep,
     };
     use tokio_util::sync::CancellationToken;
 
     use super::*;
+    use crate::{
+        event::extract::{
+            WsIoAck,
+            WsIoData,
+            WsIoEventName,
+        },
+        packet::codecs::WsIoPacketCodec,
+        value::WsIoValue,
+    };
 
-    struct DummyConnection;
+    #[derive(Default)]
+    struct DummyConnection {
+        reported_errors: parking_lot::Mutex<Vec<String>>,
+        sent_ack_errors: parking_lot::Mutex<Vec<(u64, String)>>,
+        sent_acks: parking_lot::Mutex<Vec<(u64, Option<Vec<u8>>)>>,
+    }
+
+    impl AckSender for DummyConnection {
+        async fn send_ack(&self, ack_id: u64, data: Option<Vec<u8>>) -> Result<()> {
+            self.sent_acks.lock().push((ack_id, data));
+            Ok(())
+        }
+
+        async fn send_ack_error(&self, ack_id: u64, code: &str) -> Result<()> {
+            self.sent_ack_errors.lock().push((ack_id, code.into()));
+            Ok(())
+        }
+    }
+
+    impl ErrorReporter for DummyConnection {
+        fn report_error(&self, error: WsIoError) {
+            self.reported_errors.lock().push(error.to_string());
+        }
+    }
 
     struct DummySpawner {
         cancel_token: Arc<CancellationToken>,
@@ -247,7 +1028,7 @@ mod tests {
             cancel_token: Arc::new(CancellationToken::new()),
         });
 
-        let ctx = Arc::new(DummyConnection);
+        let ctx = Arc::new(DummyConnection::default());
 
         let count = Arc::new(AtomicU32::new(0));
         let count_clone1 = count.clone();
@@ -267,16 +1048,186 @@ mod tests {
         });
 
         // Dispatch
-        let packet_codec = WsIoPacketCodec::SerdeJson;
-        let packet_data = packet_codec.encode_data(&"hello").unwrap();
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&"hello").unwrap());
 
-        registry.dispatch_event_packet(ctx.clone(), "ping", &packet_codec, Some(packet_data), &spawner);
+        registry
+            .dispatch_event_packet(ctx.clone(), "ping", &packet_codec, Some(packet_data), None, &spawner)
+            .await;
 
         // Yield to let the spawned Tokio tasks run
         yield_now().await;
 
         // Verify both handlers ran
         assert_eq!(count.load(Ordering::Relaxed), 2);
+        assert!(ctx.sent_acks.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatch_with_ack() {
+        let registry = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        registry.on_with_ack("add", |_ctx, numbers: Arc<(u32, u32)>| async move {
+            Ok(numbers.0 + numbers.1)
+        });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&(1, 2)).unwrap());
+
+        // Without an ack id the reply is discarded
+        registry
+            .dispatch_event_packet(
+                ctx.clone(),
+                "add",
+                &packet_codec,
+                Some(packet_data.clone()),
+                None,
+                &spawner,
+            )
+            .await;
+        yield_now().await;
+        assert!(ctx.sent_acks.lock().is_empty());
+
+        registry
+            .dispatch_event_packet(ctx.clone(), "add", &packet_codec, Some(packet_data), Some(42), &spawner)
+            .await;
+        yield_now().await;
+
+        let sent_acks = ctx.sent_acks.lock();
+        assert_eq!(sent_acks.len(), 1);
+        assert_eq!(sent_acks[0].0, 42);
+        assert_eq!(
+            packet_codec
+                .decode_data_as::<u32>(sent_acks[0].1.as_deref().unwrap())
+                .unwrap(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_failed_ack_handlers_send_error_acks() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        registry.on_with_ack("count", |_ctx, count: Arc<u32>| async move { Ok(*count) });
+        registry.on_with_ack::<_, _, (), ()>("fail", |_ctx, _data| async { anyhow::bail!("rejected") });
+        registry.on_with_ack::<_, _, (), ()>("panic", |_ctx, _data| async { panic!("exploded") });
+
+        // Only the ack handler answers the ack, so a failing plain handler does
+        // not fail it as internal
+        registry.on("fail", |_ctx, _data: Arc<()>| async { anyhow::bail!("also rejected") });
+        registry.on("plain", |_ctx, _data: Arc<()>| async { anyhow::bail!("rejected") });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&"not a number").unwrap());
+        registry
+            .dispatch_event_packet(
+                ctx.clone(),
+                "count",
+                &packet_codec,
+                Some(packet_data),
+                Some(1),
+                &spawner,
+            )
+            .await;
+        for (ack_id, event) in [(2, "fail"), (3, "panic"), (4, "plain")] {
+            registry
+                .dispatch_event_packet(ctx.clone(), event, &packet_codec, None, Some(ack_id), &spawner)
+                .await;
+        }
+
+        sleep(Duration::from_millis(50)).await;
+
+        let mut sent_ack_errors = ctx.sent_ack_errors.lock().clone();
+        sent_ack_errors.sort();
+        assert_eq!(
+            sent_ack_errors,
+            [
+                (1, WsIoConnectError::INTERNAL.to_owned()),
+                (2, WsIoConnectError::INTERNAL.to_owned()),
+                (3, WsIoConnectError::INTERNAL.to_owned()),
+                (4, WsIoConnectError::NO_ACK_HANDLER.to_owned()),
+            ]
+        );
+        assert!(ctx.sent_acks.lock().is_empty());
+        assert_eq!(ctx.reported_errors.lock().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_registry_acks_without_ack_handler_fail() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        registry.on_pattern("chat:*", |_ctx, _raw_event| async { Ok(()) });
+        registry.on_raw("raw", |_ctx, _raw_event| async { Ok(()) });
+        registry.on_with_ack("add", |_ctx, _data: Arc<()>| async { Ok(1) });
+        registry.on_extract(
+            "extract",
+            |_ctx: Arc<DummyConnection>, ack: WsIoAck<DummyConnection>| async move { ack.reply(&2).await },
+        );
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        for (ack_id, event) in [
+            (1, "chat:message"),
+            (2, "raw"),
+            (3, "unknown"),
+            (4, "add"),
+            (5, "extract"),
+        ] {
+            registry
+                .dispatch_event_packet(ctx.clone(), event, &packet_codec, None, Some(ack_id), &spawner)
+                .await;
+        }
+
+        sleep(Duration::from_millis(50)).await;
+
+        let mut sent_ack_errors = ctx.sent_ack_errors.lock().clone();
+        sent_ack_errors.sort();
+        assert_eq!(
+            sent_ack_errors,
+            [1, 2, 3].map(|ack_id| (ack_id, WsIoConnectError::NO_ACK_HANDLER.to_owned()))
+        );
+
+        let mut answered_ack_ids = ctx
+            .sent_acks
+            .lock()
+            .iter()
+            .map(|(ack_id, _)| *ack_id)
+            .collect::<Vec<_>>();
+        answered_ack_ids.sort();
+        assert_eq!(answered_ack_ids, [4, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "already has an ack handler")]
+    fn test_registry_rejects_second_ack_handler() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        registry.on_with_ack("add", |_ctx, _data: Arc<()>| async { Ok(1) });
+        registry.on_with_ack("add", |_ctx, _data: Arc<()>| async { Ok(2) });
+    }
+
+    #[test]
+    fn test_registry_ack_handler_can_be_replaced_after_removal() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        registry.on("add", |_ctx, _data: Arc<()>| async { Ok(()) });
+        let handler_id = registry.on_with_ack("add", |_ctx, _data: Arc<()>| async { Ok(1) });
+        registry.off_by_handler_id("add", handler_id);
+
+        let handler_id = registry.on_with_ack("add", |_ctx, _data: Arc<()>| async { Ok(2) });
+        assert_eq!(
+            registry.table.load().event_entries["add"].ack_handler_id,
+            Some(handler_id)
+        );
     }
 
     #[tokio::test]
@@ -295,29 +1246,528 @@ mod tests {
 
         // Verify the handler was registered
         assert_eq!(handler_id, 0);
-        let event_entries = registry.event_entries.read();
-        assert!(event_entries.contains_key("test_event"));
-        assert_eq!(event_entries.get("test_event").unwrap().handlers.read().len(), 1);
-        drop(event_entries);
+        let table = registry.table.load();
+        assert!(table.event_entries.contains_key("test_event"));
+        assert_eq!(table.event_entries.get("test_event").unwrap().handlers.len(), 1);
 
         // Remove by handler ID
         registry.off_by_handler_id("test_event", handler_id);
 
         // Verify it was removed and the event entry was cleaned up since it's empty
-        let event_entries = registry.event_entries.read();
-        assert!(!event_entries.contains_key("test_event"));
-        drop(event_entries);
+        assert!(!registry.table.load().event_entries.contains_key("test_event"));
 
         // Register multiple and test full off
         registry.on("multi_event", |_ctx, _data: Arc<String>| async { Ok(()) });
         registry.on("multi_event", |_ctx, _data: Arc<String>| async { Ok(()) });
 
-        let event_entries = registry.event_entries.read();
-        assert_eq!(event_entries.get("multi_event").unwrap().handlers.read().len(), 2);
-        drop(event_entries);
+        let table = registry.table.load();
+        assert_eq!(table.event_entries.get("multi_event").unwrap().handlers.len(), 2);
 
         registry.off("multi_event");
-        let event_entries = registry.event_entries.read();
-        assert!(!event_entries.contains_key("multi_event"));
+        assert!(!registry.table.load().event_entries.contains_key("multi_event"));
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatch_pattern_handlers() {
+        let registry = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let seen_events = Arc::new(parking_lot::Mutex::new(Vec::new()));
+
+        let seen_events_clone = seen_events.clone();
+        registry.on_pattern("chat:*", move |_ctx, raw_event| {
+            let seen_events = seen_events_clone.clone();
+            async move {
+                let message = raw_event.decode::<String>()?.unwrap();
+                seen_events
+                    .lock()
+                    .push(format!("chat:* {} {message}", raw_event.name()));
+                Ok(())
+            }
+        });
+
+        let seen_events_clone = seen_events.clone();
+        let any_handler_id = registry.on_pattern("*", move |_ctx, raw_event| {
+            let seen_events = seen_events_clone.clone();
+            async move {
+                seen_events.lock().push(format!("* {}", raw_event.name()));
+                Ok(())
+            }
+        });
+
+        // No typed handler is registered for either event
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&"hi").unwrap());
+        registry
+            .dispatch_event_packet(
+                ctx.clone(),
+                "chat:message",
+                &packet_codec,
+                Some(packet_data),
+                None,
+                &spawner,
+            )
+            .await;
+        registry
+            .dispatch_event_packet(ctx.clone(), "presence", &packet_codec, None, None, &spawner)
+            .await;
+        yield_now().await;
+
+        let mut events = seen_events.lock().drain(..).collect::<Vec<_>>();
+        events.sort();
+        assert_eq!(events, ["* chat:message", "* presence", "chat:* chat:message hi"]);
+
+        registry.off_by_handler_id("*", any_handler_id);
+        registry.off_pattern("chat:*");
+        assert!(registry.table.load().pattern_entries.is_empty());
+    }
+
+    #[test]
+    fn test_matches_event_pattern() {
+        assert!(matches_event_pattern("*", ""));
+        assert!(matches_event_pattern("*", "chat"));
+        assert!(matches_event_pattern("chat", "chat"));
+        assert!(matches_event_pattern("chat:*", "chat:"));
+        assert!(matches_event_pattern("chat:*", "chat:room:message"));
+        assert!(matches_event_pattern("*:message", "chat:room:message"));
+        assert!(matches_event_pattern("a*b*c", "aXbYbZc"));
+        assert!(!matches_event_pattern("chat", "chat:message"));
+        assert!(!matches_event_pattern("chat:*", "chatroom"));
+        assert!(!matches_event_pattern("*:message", "chat:messages"));
+        assert!(!matches_event_pattern("a*b*c", "aXbYcZ"));
+    }
+
+    #[tokio::test]
+    async fn test_registry_raw_handlers_share_event_with_typed_handlers() {
+        let registry = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let raw_payloads = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let typed_count = Arc::new(AtomicU32::new(0));
+
+        let typed_count_clone = typed_count.clone();
+        registry.on("route", move |_ctx, _data: Arc<u32>| {
+            typed_count_clone.fetch_add(1, Ordering::Relaxed);
+            async { Ok(()) }
+        });
+
+        let raw_payloads_clone = raw_payloads.clone();
+        let raw_handler_id = registry.on_raw("route", move |_ctx, raw_event| {
+            let raw_payloads = raw_payloads_clone.clone();
+            async move {
+                let value = raw_event.decode::<WsIoValue>()?.unwrap();
+                raw_payloads.lock().push((raw_event.data().cloned(), value));
+                Ok(())
+            }
+        });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&5u32).unwrap());
+        registry
+            .dispatch_event_packet(
+                ctx.clone(),
+                "route",
+                &packet_codec,
+                Some(packet_data.clone()),
+                None,
+                &spawner,
+            )
+            .await;
+        yield_now().await;
+
+        assert_eq!(typed_count.load(Ordering::Relaxed), 1);
+        assert_eq!(*raw_payloads.lock(), [(Some(packet_data), WsIoValue::U64(5))]);
+
+        registry.off_by_handler_id("route", raw_handler_id);
+        assert!(registry.table.load().raw_event_entries.is_empty());
+        assert!(registry.table.load().event_entries.contains_key("route"));
+
+        registry.on_raw("route", |_ctx, _raw_event| async { Ok(()) });
+        registry.off("route");
+        assert!(registry.table.load().raw_event_entries.is_empty());
+        assert!(registry.table.load().event_entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registry_extract_handlers() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let received = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        registry.on_extract(
+            "add",
+            move |_ctx: Arc<DummyConnection>,
+                  WsIoEventName(event): WsIoEventName,
+                  WsIoData((a, b)): WsIoData<(u32, u32)>,
+                  ack: WsIoAck<DummyConnection>| {
+                received_clone.lock().push(event);
+                async move { ack.reply(&(a + b)).await }
+            },
+        );
+
+        // Extractor handlers may take a different payload type than typed handlers
+        registry.on("add", |_ctx, _data: Arc<WsIoValue>| async { Ok(()) });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&(1, 2)).unwrap());
+        registry
+            .dispatch_event_packet(ctx.clone(), "add", &packet_codec, Some(packet_data), Some(9), &spawner)
+            .await;
+        yield_now().await;
+
+        assert_eq!(*received.lock(), [Arc::from("add")]);
+        assert_eq!(*ctx.sent_acks.lock(), [(9, Some(b"3".to_vec()))]);
+
+        // A payload the extractor cannot decode skips the handler and is reported
+        let packet_data = Bytes::from(packet_codec.encode_data(&"1 + 2").unwrap());
+        registry
+            .dispatch_event_packet(ctx.clone(), "add", &packet_codec, Some(packet_data), None, &spawner)
+            .await;
+        yield_now().await;
+
+        assert_eq!(received.lock().len(), 1);
+        let reported_errors = ctx.reported_errors.lock();
+        assert_eq!(reported_errors.len(), 1);
+        assert!(
+            reported_errors[0]
+                .starts_with("[add] failed to extract handler arguments: Failed to decode event data: invalid type")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_shared_handlers_are_overridden_per_event() {
+        let shared = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        let registry = WsIoEventRegistry::with_shared(WsIoHandlerExecutionMode::Concurrent, shared.clone());
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let calls = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        for (registry, label) in [(&*shared, "shared"), (&registry, "own")] {
+            let calls_clone = calls.clone();
+            registry.on("chat", move |_ctx, _data: Arc<()>| {
+                calls_clone.lock().push(format!("{label} chat"));
+                async { Ok(()) }
+            });
+
+            let calls_clone = calls.clone();
+            registry.on_pattern("*", move |_ctx, raw_event| {
+                calls_clone.lock().push(format!("{label} * {}", raw_event.name()));
+                async { Ok(()) }
+            });
+        }
+
+        let calls_clone = calls.clone();
+        shared.on_raw("join", move |_ctx, _raw_event| {
+            calls_clone.lock().push("shared join".into());
+            async { Ok(()) }
+        });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        for event in ["chat", "join"] {
+            registry
+                .dispatch_event_packet(ctx.clone(), event, &packet_codec, None, None, &spawner)
+                .await;
+        }
+
+        sleep(Duration::from_millis(50)).await;
+
+        // The own `chat` handler replaces the shared one; pattern handlers of both run
+        let mut calls = calls.lock().clone();
+        calls.sort();
+        assert_eq!(
+            calls,
+            [
+                "own * chat",
+                "own * join",
+                "own chat",
+                "shared * chat",
+                "shared * join",
+                "shared join"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatch_sequential_preserves_order() {
+        let registry = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::with_execution_mode(
+            WsIoHandlerExecutionMode::Sequential,
+        ));
+
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let order = Arc::new(parking_lot::Mutex::new(Vec::new()));
+
+        let order_clone = order.clone();
+        registry.on("step", move |_ctx, index: Arc<u64>| {
+            let order = order_clone.clone();
+            async move {
+                // Earlier events sleep longer, so any overlap would reorder them
+                sleep(Duration::from_millis(10 * (5 - *index))).await;
+                order.lock().push(*index);
+                Ok(())
+            }
+        });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        for index in 0..5u64 {
+            let packet_data = Bytes::from(packet_codec.encode_data(&index).unwrap());
+            registry
+                .dispatch_event_packet(ctx.clone(), "step", &packet_codec, Some(packet_data), None, &spawner)
+                .await;
+        }
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(*order.lock(), [0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_registry_set_execution_mode_per_event() {
+        let registry = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        registry.set_execution_mode("add", WsIoHandlerExecutionMode::Bounded(1));
+        let table = registry.table.load();
+        assert_eq!(
+            table.event_executors.get("add").map(|executor| executor.mode()),
+            Some(WsIoHandlerExecutionMode::Bounded(1))
+        );
+        assert!(!table.event_executors.contains_key("other"));
+
+        let ctx = Arc::new(DummyConnection::default());
+        registry.on_with_ack("add", |_ctx, numbers: Arc<(u32, u32)>| async move {
+            Ok(numbers.0 + numbers.1)
+        });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&(1, 2)).unwrap());
+        registry
+            .dispatch_event_packet(ctx.clone(), "add", &packet_codec, Some(packet_data), Some(7), &spawner)
+            .await;
+        sleep(Duration::from_millis(50)).await;
+
+        let sent_acks = ctx.sent_acks.lock();
+        assert_eq!(sent_acks.len(), 1);
+        assert_eq!(sent_acks[0].0, 7);
+    }
+
+    #[tokio::test]
+    async fn test_registry_shared_execution_modes_run_on_own_executors() {
+        let shared = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        shared.set_execution_mode("tick", WsIoHandlerExecutionMode::Sequential);
+
+        let order = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let order_clone = order.clone();
+        shared.on("tick", move |_ctx, index: Arc<u64>| {
+            let order = order_clone.clone();
+            async move {
+                // Earlier events sleep longer, so any overlap would reorder them
+                sleep(Duration::from_millis(10 * (5 - *index))).await;
+                order.lock().push(*index);
+                Ok(())
+            }
+        });
+
+        let first = WsIoEventRegistry::with_shared(WsIoHandlerExecutionMode::Concurrent, shared.clone());
+        let second = WsIoEventRegistry::with_shared(WsIoHandlerExecutionMode::Concurrent, shared.clone());
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        for index in 0..5u64 {
+            let packet_data = Bytes::from(packet_codec.encode_data(&index).unwrap());
+            first
+                .dispatch_event_packet(ctx.clone(), "tick", &packet_codec, Some(packet_data), None, &spawner)
+                .await;
+        }
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(*order.lock(), [0, 1, 2, 3, 4]);
+
+        // Each registry keeps its own queue for the shared mode
+        let executor = first.resolve_executor(&first.table.load(), "tick");
+        assert_eq!(executor.mode(), WsIoHandlerExecutionMode::Sequential);
+        assert!(Arc::ptr_eq(
+            &executor,
+            &first.resolve_executor(&first.table.load(), "tick")
+        ));
+        assert!(!Arc::ptr_eq(
+            &executor,
+            &second.resolve_executor(&second.table.load(), "tick")
+        ));
+        assert!(!Arc::ptr_eq(&executor, &shared.table.load().event_executors["tick"]));
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatch_single_handler_spawns_once() {
+        struct CountingSpawner {
+            cancel_token: Arc<CancellationToken>,
+            spawn_count: AtomicU32,
+        }
+
+        impl TaskSpawner for CountingSpawner {
+            fn cancel_token(&self) -> Arc<CancellationToken> {
+                self.cancel_token.clone()
+            }
+
+            fn spawn_task<F: Future<Output = Result<()>> + Send + 'static>(&self, future: F) {
+                self.spawn_count.fetch_add(1, Ordering::Relaxed);
+                spawn(future);
+            }
+        }
+
+        let registry = WsIoEventRegistry::<DummyConnection, CountingSpawner>::new();
+        let spawner = Arc::new(CountingSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+            spawn_count: AtomicU32::new(0),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        registry.on_with_ack(
+            "echo",
+            |_ctx, message: Arc<String>| async move { Ok((*message).clone()) },
+        );
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&"hi").unwrap());
+        registry
+            .dispatch_event_packet(ctx.clone(), "echo", &packet_codec, Some(packet_data), Some(1), &spawner)
+            .await;
+        yield_now().await;
+
+        assert_eq!(spawner.spawn_count.load(Ordering::Relaxed), 1);
+        assert_eq!(ctx.sent_acks.lock().len(), 1);
+
+        // Events without handlers are dropped without spawning
+        registry
+            .dispatch_event_packet(ctx.clone(), "unknown", &packet_codec, None, None, &spawner)
+            .await;
+        assert_eq!(spawner.spawn_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_registry_handlers_keep_registration_order() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        let first_handler_id = registry.on("tick", |_ctx, _data: Arc<u32>| async { Ok(()) });
+        let second_handler_id = registry.on("tick", |_ctx, _data: Arc<u32>| async { Ok(()) });
+        let third_handler_id = registry.on("tick", |_ctx, _data: Arc<u32>| async { Ok(()) });
+
+        // Snapshots taken before a mutation stay untouched
+        let old_table = registry.table.load_full();
+        registry.off_by_handler_id("tick", second_handler_id);
+
+        let handler_ids = |table: &RegistryTable<DummyConnection>| {
+            table.event_entries["tick"]
+                .handlers
+                .iter()
+                .map(|(handler_id, _)| *handler_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            handler_ids(&old_table),
+            [first_handler_id, second_handler_id, third_handler_id]
+        );
+        assert_eq!(
+            handler_ids(&registry.table.load()),
+            [first_handler_id, third_handler_id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_reports_decode_failures_and_handler_errors() {
+        let registry = WsIoEventRegistry::<DummyConnection, DummySpawner>::new();
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        registry.on("count", |_ctx, _count: Arc<u32>| async { Ok(()) });
+        registry.on("fail", |_ctx, _data: Arc<()>| async { anyhow::bail!("rejected") });
+        registry.on("panic", |_ctx, _data: Arc<()>| async { panic!("exploded") });
+        registry.on_raw("raw", |_ctx, _raw_event| async { anyhow::bail!("raw rejected") });
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        let packet_data = Bytes::from(packet_codec.encode_data(&"not a number").unwrap());
+        registry
+            .dispatch_event_packet(ctx.clone(), "count", &packet_codec, Some(packet_data), None, &spawner)
+            .await;
+        for event in ["fail", "panic", "raw"] {
+            registry
+                .dispatch_event_packet(ctx.clone(), event, &packet_codec, None, None, &spawner)
+                .await;
+        }
+
+        sleep(Duration::from_millis(50)).await;
+
+        let mut reported_errors = ctx.reported_errors.lock().clone();
+        reported_errors.sort();
+        assert_eq!(reported_errors.len(), 4);
+        assert!(reported_errors[0].starts_with("[count] failed to decode event data"));
+        assert_eq!(reported_errors[1], "[fail] handler failed: rejected");
+        assert_eq!(reported_errors[2], "[panic] handler panicked: exploded");
+        assert_eq!(reported_errors[3], "[raw] handler failed: raw rejected");
+    }
+
+    #[tokio::test]
+    async fn test_registry_handler_timeouts_cut_off_stuck_handlers() {
+        let shared = Arc::new(WsIoEventRegistry::<DummyConnection, DummySpawner>::new());
+        let registry = WsIoEventRegistry::with_shared(WsIoHandlerExecutionMode::Sequential, shared.clone())
+            .with_default_handler_timeout(Some(Duration::from_millis(20)));
+
+        let spawner = Arc::new(DummySpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        });
+
+        let ctx = Arc::new(DummyConnection::default());
+        let finished = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        for event in ["slow", "stuck"] {
+            let finished = finished.clone();
+            shared.on_with_ack(event, move |_ctx, _data: Arc<()>| {
+                let finished = finished.clone();
+                async move {
+                    sleep(Duration::from_millis(60)).await;
+                    finished.lock().push(event);
+                    Ok(event)
+                }
+            });
+        }
+
+        // The shared per-event override outlives the registry default
+        shared.set_handler_timeout("slow", Duration::from_millis(200));
+
+        let packet_codec: Arc<dyn PacketCodec> = Arc::new(WsIoPacketCodec::SerdeJson);
+        registry
+            .dispatch_event_packet(ctx.clone(), "stuck", &packet_codec, None, Some(1), &spawner)
+            .await;
+        registry
+            .dispatch_event_packet(ctx.clone(), "slow", &packet_codec, None, Some(2), &spawner)
+            .await;
+
+        sleep(Duration::from_millis(150)).await;
+
+        // The cut-off handler never finishes and fails its ack, and later events still run
+        assert_eq!(*finished.lock(), ["slow"]);
+        assert_eq!(*ctx.sent_acks.lock(), [(2, Some(br#""slow""#.to_vec()))]);
+        assert_eq!(*ctx.sent_ack_errors.lock(), [(1, WsIoConnectError::TIMEOUT.to_owned())]);
+        assert_eq!(*ctx.reported_errors.lock(), ["[stuck] handler timed out after 20ms"]);
     }
 }
@@ -2,11 +2,27 @@
 
 use tungstenite::protocol::WebSocketConfig;
 
+pub mod error;
 pub mod event;
 pub mod packet;
+pub mod protocol;
 pub mod traits;
 pub mod types;
 pub mod utils;
+pub mod value;
+
+/// Helpers the server and client crates share with this crate.
+///
+/// Not part of the public API; anything here may change in any release.
+#[doc(hidden)]
+pub mod __private {
+    /// Matches `event` against `pattern`, where `*` matches any run of
+    /// characters.
+    #[inline]
+    pub fn matches_event_pattern(pattern: &str, event: &str) -> bool {
+        crate::event::registry::matches_event_pattern(pattern, event)
+    }
+}
 
 pub fn channel_capacity_from_websocket_config(websocket_config: &WebSocketConfig) -> usize {
     let ratio = (websocket_config.max_write_buffer_size as f64 / websocket_config.write_buffer_size as f64).max(1.0);
@@ -5,40 +5,67 @@ use ciborium::{
     de::from_reader,
     ser::into_writer,
 };
-use serde::{
-    Serialize,
-    de::DeserializeOwned,
+use erased_serde::{
+    Deserializer as ErasedDeserializer,
+    Serialize as ErasedSerialize,
 };
+use serde_value::Value;
 
-use super::super::WsIoPacket;
+use super::super::{
+    WsIoPacket,
+    WsIoPacketView,
+};
+use crate::traits::packet::codec::{
+    DataVisitor,
+    PacketCodec,
+};
 
 // Structs
-pub(super) struct WsIoPacketCborCodec;
-
-impl WsIoPacketCborCodec {
-    pub(super) const IS_TEXT: bool = false;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WsIoPacketCborCodec;
 
+impl PacketCodec for WsIoPacketCborCodec {
     #[inline]
-    pub(super) fn decode(bytes: &[u8]) -> Result<WsIoPacket> {
+    fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
         Ok(WsIoPacket::from_inner(from_reader(Cursor::new(bytes))?))
     }
 
+    /// Ciborium does not expose its deserializer, so data is first decoded into
+    /// an intermediate value that is then deserialized into the target type.
+    #[inline]
+    fn decode_data(&self, bytes: &[u8], visitor: &mut DataVisitor<'_>) -> Result<()> {
+        let value: Value = from_reader(Cursor::new(bytes))?;
+        visitor(&mut <dyn ErasedDeserializer>::erase(value))
+    }
+
+    /// Ciborium only deserializes owned values, so the view always owns its key
+    /// and data.
     #[inline]
-    pub(super) fn decode_data<D: DeserializeOwned>(bytes: &[u8]) -> Result<D> {
-        Ok(from_reader(Cursor::new(bytes))?)
+    fn decode_view<'a>(&self, bytes: &'a [u8]) -> Result<WsIoPacketView<'a>> {
+        Ok(self.decode(bytes)?.into())
     }
 
     #[inline]
-    pub(super) fn encode(packet: &WsIoPacket) -> Result<Vec<u8>> {
+    fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
         let mut buffer = Vec::new();
         into_writer(&packet.to_inner_ref(), &mut buffer)?;
         Ok(buffer)
     }
 
     #[inline]
-    pub(super) fn encode_data<D: Serialize>(data: &D) -> Result<Vec<u8>> {
+    fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>> {
         let mut buffer = Vec::new();
         into_writer(data, &mut buffer)?;
         Ok(buffer)
     }
+
+    #[inline]
+    fn is_text(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        "cbor"
+    }
 }
@@ -1,8 +1,5 @@
 use anyhow::Result;
-use serde::{
-    Serialize,
-    de::DeserializeOwned,
-};
+use erased_serde::Serialize as ErasedSerialize;
 
 #[cfg(feature = "packet-codec-cbor")]
 mod cbor;
@@ -19,17 +16,25 @@ mod serde_json;
 mod sonic_rs;
 
 #[cfg(feature = "packet-codec-cbor")]
-use self::cbor::WsIoPacketCborCodec;
+pub use self::cbor::WsIoPacketCborCodec;
 #[cfg(feature = "packet-codec-msgpack")]
-use self::msgpack::WsIoPacketMsgpackCodec;
+pub use self::msgpack::WsIoPacketMsgpackCodec;
 #[cfg(feature = "packet-codec-postcard")]
-use self::postcard::WsIoPacketPostcardCodec;
-use self::serde_json::WsIoPacketSerdeJsonCodec;
+pub use self::postcard::WsIoPacketPostcardCodec;
+pub use self::serde_json::WsIoPacketSerdeJsonCodec;
 #[cfg(feature = "packet-codec-sonic-rs")]
-use self::sonic_rs::WsIoPacketSonicRsCodec;
-use super::WsIoPacket;
+pub use self::sonic_rs::WsIoPacketSonicRsCodec;
+use super::{
+    WsIoPacket,
+    WsIoPacketView,
+};
+use crate::traits::packet::codec::{
+    DataVisitor,
+    PacketCodec,
+};
 
 // Enums
+/// Built-in packet codecs, each delegating to its codec struct.
 #[derive(Clone, Copy, Debug)]
 pub enum WsIoPacketCodec {
     #[cfg(feature = "packet-codec-cbor")]
@@ -48,104 +53,69 @@ pub enum WsIoPacketCodec {
 }
 
 impl WsIoPacketCodec {
+    // Private methods
     #[inline]
-    pub fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
+    fn as_codec(&self) -> &dyn PacketCodec {
         match self {
             #[cfg(feature = "packet-codec-cbor")]
-            Self::Cbor => WsIoPacketCborCodec::decode(bytes),
+            Self::Cbor => &WsIoPacketCborCodec,
 
             #[cfg(feature = "packet-codec-msgpack")]
-            Self::Msgpack => WsIoPacketMsgpackCodec::decode(bytes),
+            Self::Msgpack => &WsIoPacketMsgpackCodec,
 
             #[cfg(feature = "packet-codec-postcard")]
-            Self::Postcard => WsIoPacketPostcardCodec::decode(bytes),
+            Self::Postcard => &WsIoPacketPostcardCodec,
 
-            Self::SerdeJson => WsIoPacketSerdeJsonCodec::decode(bytes),
+            Self::SerdeJson => &WsIoPacketSerdeJsonCodec,
 
             #[cfg(feature = "packet-codec-sonic-rs")]
-            Self::SonicRs => WsIoPacketSonicRsCodec::decode(bytes),
+            Self::SonicRs => &WsIoPacketSonicRsCodec,
         }
     }
+}
 
+impl PacketCodec for WsIoPacketCodec {
     #[inline]
-    pub fn decode_data<D: DeserializeOwned>(&self, bytes: &[u8]) -> Result<D> {
-        match self {
-            #[cfg(feature = "packet-codec-cbor")]
-            Self::Cbor => WsIoPacketCborCodec::decode_data(bytes),
-
-            #[cfg(feature = "packet-codec-msgpack")]
-            Self::Msgpack => WsIoPacketMsgpackCodec::decode_data(bytes),
-
-            #[cfg(feature = "packet-codec-postcard")]
-            Self::Postcard => WsIoPacketPostcardCodec::decode_data(bytes),
-
-            Self::SerdeJson => WsIoPacketSerdeJsonCodec::decode_data(bytes),
-
-            #[cfg(feature = "packet-codec-sonic-rs")]
-            Self::SonicRs => WsIoPacketSonicRsCodec::decode_data(bytes),
-        }
+    fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
+        self.as_codec().decode(bytes)
     }
 
     #[inline]
-    pub fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
-        match self {
-            #[cfg(feature = "packet-codec-cbor")]
-            Self::Cbor => WsIoPacketCborCodec::encode(packet),
-
-            #[cfg(feature = "packet-codec-msgpack")]
-            Self::Msgpack => WsIoPacketMsgpackCodec::encode(packet),
-
-            #[cfg(feature = "packet-codec-postcard")]
-            Self::Postcard => WsIoPacketPostcardCodec::encode(packet),
-
-            Self::SerdeJson => WsIoPacketSerdeJsonCodec::encode(packet),
-
-            #[cfg(feature = "packet-codec-sonic-rs")]
-            Self::SonicRs => WsIoPacketSonicRsCodec::encode(packet),
-        }
+    fn decode_data(&self, bytes: &[u8], visitor: &mut DataVisitor<'_>) -> Result<()> {
+        self.as_codec().decode_data(bytes, visitor)
     }
 
     #[inline]
-    pub fn encode_data<D: Serialize>(&self, data: &D) -> Result<Vec<u8>> {
-        match self {
-            #[cfg(feature = "packet-codec-cbor")]
-            Self::Cbor => WsIoPacketCborCodec::encode_data(data),
-
-            #[cfg(feature = "packet-codec-msgpack")]
-            Self::Msgpack => WsIoPacketMsgpackCodec::encode_data(data),
-
-            #[cfg(feature = "packet-codec-postcard")]
-            Self::Postcard => WsIoPacketPostcardCodec::encode_data(data),
-
-            Self::SerdeJson => WsIoPacketSerdeJsonCodec::encode_data(data),
-
-            #[cfg(feature = "packet-codec-sonic-rs")]
-            Self::SonicRs => WsIoPacketSonicRsCodec::encode_data(data),
-        }
+    fn decode_view<'a>(&self, bytes: &'a [u8]) -> Result<WsIoPacketView<'a>> {
+        self.as_codec().decode_view(bytes)
     }
 
     #[inline]
-    pub fn is_text(&self) -> bool {
-        match self {
-            #[cfg(feature = "packet-codec-cbor")]
-            Self::Cbor => WsIoPacketCborCodec::IS_TEXT,
-
-            #[cfg(feature = "packet-codec-msgpack")]
-            Self::Msgpack => WsIoPacketMsgpackCodec::IS_TEXT,
+    fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
+        self.as_codec().encode(packet)
+    }
 
-            #[cfg(feature = "packet-codec-postcard")]
-            Self::Postcard => WsIoPacketPostcardCodec::IS_TEXT,
+    #[inline]
+    fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>> {
+        self.as_codec().encode_data(data)
+    }
 
-            Self::SerdeJson => WsIoPacketSerdeJsonCodec::IS_TEXT,
+    #[inline]
+    fn is_text(&self) -> bool {
+        self.as_codec().is_text()
+    }
 
-            #[cfg(feature = "packet-codec-sonic-rs")]
-            Self::SonicRs => WsIoPacketSonicRsCodec::IS_TEXT,
-        }
+    #[inline]
+    fn name(&self) -> &str {
+        self.as_codec().name()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
+    use bytes::Bytes;
     use serde::{
         Deserialize,
         Serialize,
@@ -167,7 +137,7 @@ mod tests {
         ($codec:expr, $name:ident) => {
             #[test]
             fn $name() {
-                let codec = $codec;
+                let codec: &dyn PacketCodec = &$codec;
 
                 // 1. Test encoding/decoding raw data
                 let original_data = TestPayload {
@@ -176,7 +146,9 @@ mod tests {
                 };
 
                 let encoded_data = codec.encode_data(&original_data).expect("Failed to encode data");
-                let decoded_data: TestPayload = codec.decode_data(&encoded_data).expect("Failed to decode data");
+                let decoded_data: TestPayload = codec
+                    .decode_data_as(&encoded_data)
+                    .expect("Failed to decode data");
                 assert_eq!(
                     original_data, decoded_data,
                     "Data decoding did not match original"
@@ -193,7 +165,32 @@ mod tests {
                 );
 
                 assert_eq!(decoded_packet.key.as_deref(), Some("chat"), "Packet key mismatch");
-                assert_eq!(decoded_packet.data, Some(encoded_data), "Packet data mismatch");
+                assert_eq!(
+                    decoded_packet.data,
+                    Some(encoded_data.clone()),
+                    "Packet data mismatch"
+                );
+
+                let decoded_view = codec
+                    .decode_view(&encoded_packet)
+                    .expect("Failed to decode packet view");
+                assert!(
+                    matches!(decoded_view.r#type, WsIoPacketType::Event),
+                    "Packet view type mismatch"
+                );
+
+                assert_eq!(
+                    decoded_view.key.as_deref(),
+                    Some("chat"),
+                    "Packet view key mismatch"
+                );
+                assert_eq!(
+                    decoded_view.data.as_deref(),
+                    Some(&encoded_data[..]),
+                    "Packet view data mismatch"
+                );
+
+                assert_eq!(decoded_view.ack_id, None, "Packet view ack id should be None");
 
                 // 3. Test encoding/decoding a Disconnect packet (no data, no key)
                 let packet = WsIoPacket::new_disconnect();
@@ -209,10 +206,141 @@ mod tests {
 
                 assert_eq!(decoded_packet.key, None, "Packet key should be None");
                 assert_eq!(decoded_packet.data, None, "Packet data should be None");
+                assert_eq!(decoded_packet.ack_id, None, "Packet ack id should be None");
+
+                // 4. Test encoding/decoding an Event packet requesting an ack
+                let packet = WsIoPacket::new_event_with_ack("rpc", Some(vec![1, 2]), u64::MAX);
+                let encoded_packet = codec
+                    .encode(&packet)
+                    .expect("Failed to encode event packet with ack");
+                let decoded_packet = codec
+                    .decode(&encoded_packet)
+                    .expect("Failed to decode event packet with ack");
+
+                assert!(
+                    matches!(decoded_packet.r#type, WsIoPacketType::Event),
+                    "Packet type mismatch"
+                );
+
+                assert_eq!(decoded_packet.key.as_deref(), Some("rpc"), "Packet key mismatch");
+                assert_eq!(decoded_packet.ack_id, Some(u64::MAX), "Packet ack id mismatch");
+
+                let decoded_view = codec
+                    .decode_view(&encoded_packet)
+                    .expect("Failed to decode event packet view with ack");
+
+                assert_eq!(decoded_view.ack_id, Some(u64::MAX), "Packet view ack id mismatch");
+                assert_eq!(
+                    decoded_view.data.as_deref(),
+                    Some(&[1, 2][..]),
+                    "Packet view data mismatch"
+                );
+
+                // Disconnect packet view
+                let encoded_packet = codec
+                    .encode(&WsIoPacket::new_disconnect())
+                    .expect("Failed to encode disconnect packet");
+                let decoded_view = codec
+                    .decode_view(&encoded_packet)
+                    .expect("Failed to decode disconnect packet view");
+
+                assert_eq!(decoded_view.key, None, "Packet view key should be None");
+                assert_eq!(decoded_view.data, None, "Packet view data should be None");
+
+                // 5. Test encoding/decoding an Ack packet
+                let packet = WsIoPacket::new_ack(3, Some(vec![4]));
+                let encoded_packet = codec.encode(&packet).expect("Failed to encode ack packet");
+                let decoded_packet = codec
+                    .decode(&encoded_packet)
+                    .expect("Failed to decode ack packet");
+
+                assert!(
+                    matches!(decoded_packet.r#type, WsIoPacketType::Ack),
+                    "Packet type mismatch"
+                );
+                assert_eq!(decoded_packet.ack_id, Some(3), "Packet ack id mismatch");
+                assert_eq!(decoded_packet.data, Some(vec![4]), "Packet data mismatch");
             }
         };
     }
 
+    #[test]
+    fn test_serde_json_codec_keeps_three_field_layout_without_ack_id() {
+        let codec = WsIoPacketCodec::SerdeJson;
+
+        let encoded_packet = codec.encode(&WsIoPacket::new_event("chat", None)).unwrap();
+        assert_eq!(encoded_packet, br#"[1,"chat",null]"#);
+
+        let decoded_packet = codec.decode(br#"[1,"chat",null]"#).unwrap();
+        assert_eq!(decoded_packet.ack_id, None);
+
+        let encoded_packet = codec.encode(&WsIoPacket::new_event_with_ack("chat", None, 5)).unwrap();
+        assert_eq!(encoded_packet, br#"[1,"chat",null,5]"#);
+    }
+
+    #[test]
+    fn test_serde_json_codec_decode_view_borrows_key() {
+        let encoded_packet = br#"[1,"chat",[1,2,3]]"#;
+        let mut packet_view = WsIoPacketCodec::SerdeJson.decode_view(encoded_packet).unwrap();
+        assert!(matches!(packet_view.key, Some(Cow::Borrowed("chat"))));
+
+        let source = Bytes::from_static(encoded_packet);
+        assert_eq!(packet_view.take_data_bytes(&source).as_deref(), Some(&[1, 2, 3][..]));
+        assert_eq!(packet_view.data, None);
+    }
+
+    #[cfg(feature = "packet-codec-postcard")]
+    #[test]
+    fn test_postcard_codec_decode_view_borrows_data() {
+        let codec = WsIoPacketCodec::Postcard;
+        let encoded_packet = Bytes::from(codec.encode(&WsIoPacket::new_event("chat", Some(vec![7; 32]))).unwrap());
+
+        let mut packet_view = codec.decode_view(&encoded_packet).unwrap();
+        assert!(matches!(packet_view.key, Some(Cow::Borrowed("chat"))));
+        assert!(matches!(packet_view.data, Some(Cow::Borrowed(_))));
+
+        // Borrowed data is sliced out of the source frame instead of copied
+        let data = packet_view.take_data_bytes(&encoded_packet).unwrap();
+        assert_eq!(data, vec![7; 32]);
+        assert!(encoded_packet.as_ptr_range().contains(&data.as_ptr()));
+    }
+
+    #[test]
+    fn test_codec_is_text() {
+        assert!(WsIoPacketCodec::SerdeJson.is_text());
+        assert!(WsIoPacketSerdeJsonCodec.is_text());
+
+        #[cfg(feature = "packet-codec-cbor")]
+        assert!(!WsIoPacketCodec::Cbor.is_text());
+
+        #[cfg(feature = "packet-codec-msgpack")]
+        assert!(!WsIoPacketCodec::Msgpack.is_text());
+
+        #[cfg(feature = "packet-codec-postcard")]
+        assert!(!WsIoPacketCodec::Postcard.is_text());
+
+        #[cfg(feature = "packet-codec-sonic-rs")]
+        assert!(WsIoPacketCodec::SonicRs.is_text());
+    }
+
+    #[test]
+    fn test_codec_name() {
+        assert_eq!(WsIoPacketCodec::SerdeJson.name(), "json");
+
+        #[cfg(feature = "packet-codec-cbor")]
+        assert_eq!(WsIoPacketCodec::Cbor.name(), "cbor");
+
+        #[cfg(feature = "packet-codec-msgpack")]
+        assert_eq!(WsIoPacketCodec::Msgpack.name(), "msgpack");
+
+        #[cfg(feature = "packet-codec-postcard")]
+        assert_eq!(WsIoPacketCodec::Postcard.name(), "postcard");
+
+        // sonic-rs writes the same JSON wire format, so both codecs share a name
+        #[cfg(feature = "packet-codec-sonic-rs")]
+        assert_eq!(WsIoPacketCodec::SonicRs.name(), "json");
+    }
+
     #[cfg(feature = "packet-codec-cbor")]
     test_codec!(WsIoPacketCodec::Cbor, test_cbor_codec);
 
@@ -1,38 +1,62 @@
 use anyhow::Result;
+use erased_serde::{
+    Deserializer as ErasedDeserializer,
+    Serialize as ErasedSerialize,
+};
 use rmp_serde::{
+    Deserializer,
     from_slice,
     to_vec_named,
 };
-use serde::{
-    Serialize,
-    de::DeserializeOwned,
-};
 
-use super::super::WsIoPacket;
+use super::super::{
+    WsIoPacket,
+    WsIoPacketView,
+};
+use crate::traits::packet::codec::{
+    DataVisitor,
+    PacketCodec,
+};
 
 // Structs
-pub(super) struct WsIoPacketMsgpackCodec;
-
-impl WsIoPacketMsgpackCodec {
-    pub(super) const IS_TEXT: bool = false;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WsIoPacketMsgpackCodec;
 
+impl PacketCodec for WsIoPacketMsgpackCodec {
     #[inline]
-    pub(super) fn decode(bytes: &[u8]) -> Result<WsIoPacket> {
+    fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
         Ok(WsIoPacket::from_inner(from_slice(bytes)?))
     }
 
     #[inline]
-    pub(super) fn decode_data<D: DeserializeOwned>(bytes: &[u8]) -> Result<D> {
-        Ok(from_slice(bytes)?)
+    fn decode_data(&self, bytes: &[u8], visitor: &mut DataVisitor<'_>) -> Result<()> {
+        visitor(&mut <dyn ErasedDeserializer>::erase(&mut Deserializer::from_read_ref(
+            bytes,
+        )))
+    }
+
+    #[inline]
+    fn decode_view<'a>(&self, bytes: &'a [u8]) -> Result<WsIoPacketView<'a>> {
+        Ok(WsIoPacketView::from_inner(from_slice(bytes)?))
     }
 
     #[inline]
-    pub(super) fn encode(packet: &WsIoPacket) -> Result<Vec<u8>> {
+    fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
         Ok(to_vec_named(&packet.to_inner_ref())?)
     }
 
     #[inline]
-    pub(super) fn encode_data<D: Serialize>(data: &D) -> Result<Vec<u8>> {
+    fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>> {
         Ok(to_vec_named(data)?)
     }
+
+    #[inline]
+    fn is_text(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        "msgpack"
+    }
 }
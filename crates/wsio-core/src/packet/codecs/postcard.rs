@@ -1,38 +1,93 @@
+use std::borrow::Cow;
+
 use anyhow::Result;
+use erased_serde::{
+    Deserializer as ErasedDeserializer,
+    Serialize as ErasedSerialize,
+};
 use postcard::{
+    Deserializer,
     from_bytes,
+    take_from_bytes,
     to_allocvec,
 };
-use serde::{
-    Serialize,
-    de::DeserializeOwned,
-};
+use serde::Deserialize;
 
-use super::super::WsIoPacket;
+use super::super::{
+    InnerPacket,
+    InnerPacketView,
+    WsIoPacket,
+    WsIoPacketType,
+    WsIoPacketView,
+    deserialize_optional_cow_bytes,
+    deserialize_optional_cow_str,
+};
+use crate::traits::packet::codec::{
+    DataVisitor,
+    PacketCodec,
+};
 
 // Structs
-pub(super) struct WsIoPacketPostcardCodec;
+#[derive(Deserialize)]
+struct InnerPacketViewHead<'a>(
+    WsIoPacketType,
+    #[serde(borrow, deserialize_with = "deserialize_optional_cow_str")] Option<Cow<'a, str>>,
+    #[serde(borrow, deserialize_with = "deserialize_optional_cow_bytes")] Option<Cow<'a, [u8]>>,
+);
 
-impl WsIoPacketPostcardCodec {
-    pub(super) const IS_TEXT: bool = false;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WsIoPacketPostcardCodec;
 
+impl PacketCodec for WsIoPacketPostcardCodec {
     #[inline]
-    pub(super) fn decode(bytes: &[u8]) -> Result<WsIoPacket> {
-        Ok(WsIoPacket::from_inner(from_bytes(bytes)?))
+    fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
+        // Postcard is not self-describing, so the optional trailing ack id is read from any remaining bytes.
+        let ((r#type, key, data), remaining_bytes) =
+            take_from_bytes::<(WsIoPacketType, Option<String>, Option<Vec<u8>>)>(bytes)?;
+
+        let ack_id = match remaining_bytes.is_empty() {
+            true => None,
+            false => from_bytes(remaining_bytes)?,
+        };
+
+        Ok(WsIoPacket::from_inner(InnerPacket(r#type, key, data, ack_id)))
     }
 
     #[inline]
-    pub(super) fn decode_data<D: DeserializeOwned>(bytes: &[u8]) -> Result<D> {
-        Ok(from_bytes::<D>(bytes)?)
+    fn decode_data(&self, bytes: &[u8], visitor: &mut DataVisitor<'_>) -> Result<()> {
+        visitor(&mut <dyn ErasedDeserializer>::erase(&mut Deserializer::from_bytes(
+            bytes,
+        )))
     }
 
     #[inline]
-    pub(super) fn encode(packet: &WsIoPacket) -> Result<Vec<u8>> {
+    fn decode_view<'a>(&self, bytes: &'a [u8]) -> Result<WsIoPacketView<'a>> {
+        let (InnerPacketViewHead(r#type, key, data), remaining_bytes) = take_from_bytes::<InnerPacketViewHead>(bytes)?;
+        let ack_id = match remaining_bytes.is_empty() {
+            true => None,
+            false => from_bytes(remaining_bytes)?,
+        };
+
+        Ok(WsIoPacketView::from_inner(InnerPacketView(r#type, key, data, ack_id)))
+    }
+
+    #[inline]
+    fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
         Ok(to_allocvec(&packet.to_inner_ref())?)
     }
 
     #[inline]
-    pub(super) fn encode_data<D: Serialize>(data: &D) -> Result<Vec<u8>> {
+    fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>> {
         Ok(to_allocvec(data)?)
     }
+
+    #[inline]
+    fn is_text(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        "postcard"
+    }
 }
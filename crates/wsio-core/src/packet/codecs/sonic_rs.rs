@@ -1,38 +1,62 @@
 use ::sonic_rs::{
+    Deserializer,
     from_slice,
     to_vec,
 };
 use anyhow::Result;
-use serde::{
-    Serialize,
-    de::DeserializeOwned,
+use erased_serde::{
+    Deserializer as ErasedDeserializer,
+    Serialize as ErasedSerialize,
 };
 
-use super::super::WsIoPacket;
+use super::super::{
+    WsIoPacket,
+    WsIoPacketView,
+};
+use crate::traits::packet::codec::{
+    DataVisitor,
+    PacketCodec,
+};
 
 // Structs
-pub(super) struct WsIoPacketSonicRsCodec;
-
-impl WsIoPacketSonicRsCodec {
-    pub(super) const IS_TEXT: bool = true;
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WsIoPacketSonicRsCodec;
 
+impl PacketCodec for WsIoPacketSonicRsCodec {
     #[inline]
-    pub(super) fn decode(bytes: &[u8]) -> Result<WsIoPacket> {
+    fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
         Ok(WsIoPacket::from_inner(from_slice(bytes)?))
     }
 
     #[inline]
-    pub(super) fn decode_data<D: DeserializeOwned>(bytes: &[u8]) -> Result<D> {
-        Ok(from_slice(bytes)?)
+    fn decode_data(&self, bytes: &[u8], visitor: &mut DataVisitor<'_>) -> Result<()> {
+        let mut deserializer = Deserializer::from_slice(bytes);
+        visitor(&mut <dyn ErasedDeserializer>::erase(&mut deserializer))?;
+        Ok(deserializer.end()?)
+    }
+
+    #[inline]
+    fn decode_view<'a>(&self, bytes: &'a [u8]) -> Result<WsIoPacketView<'a>> {
+        Ok(WsIoPacketView::from_inner(from_slice(bytes)?))
     }
 
     #[inline]
-    pub(super) fn encode(packet: &WsIoPacket) -> Result<Vec<u8>> {
+    fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
         Ok(to_vec(&packet.to_inner_ref())?)
     }
 
     #[inline]
-    pub(super) fn encode_data<D: Serialize>(data: &D) -> Result<Vec<u8>> {
+    fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>> {
         Ok(to_vec(data)?)
     }
+
+    #[inline]
+    fn is_text(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        "json"
+    }
 }
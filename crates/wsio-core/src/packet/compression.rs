@@ -0,0 +1,141 @@
+use std::io::{
+    Read,
+    Write,
+};
+
+use anyhow::{
+    Result,
+    bail,
+};
+use bytes::Bytes;
+use flate2::{
+    Compression,
+    read::DeflateDecoder,
+    write::DeflateEncoder,
+};
+use tungstenite::Message;
+
+// Constants/Statics
+/// Flag byte leading a binary packet frame whose packet is deflated.
+pub const WSIO_FRAME_FLAG_DEFLATE: u8 = 1;
+
+/// Flag byte leading a binary packet frame whose packet is sent as it is.
+pub const WSIO_FRAME_FLAG_PLAIN: u8 = 0;
+
+// Functions
+/// Prepares an encoded packet message for a peer that negotiated
+/// [`COMPRESSION`](crate::protocol::WsIoProtocolCapabilities::COMPRESSION).
+///
+/// Packets of at least `threshold` bytes are deflated on their own and sent as
+/// a binary frame led by [`WSIO_FRAME_FLAG_DEFLATE`], unless that does not make
+/// them smaller. Other binary packets are led by [`WSIO_FRAME_FLAG_PLAIN`],
+/// while text packets and every other message, including single-byte
+/// heartbeats, are left as they are.
+pub fn compress_packet_message(message: Message, threshold: usize) -> Message {
+    let (bytes, is_text) = match &message {
+        Message::Binary(bytes) if bytes.len() > 1 => (bytes.as_ref(), false),
+        Message::Text(text) => (text.as_bytes(), true),
+        _ => return message,
+    };
+
+    if bytes.len() >= threshold
+        && let Some(frame) = deflate_frame(bytes)
+        && frame.len() < bytes.len()
+    {
+        return Message::Binary(frame.into());
+    }
+
+    if is_text {
+        return message;
+    }
+
+    let mut frame = Vec::with_capacity(bytes.len() + 1);
+    frame.push(WSIO_FRAME_FLAG_PLAIN);
+    frame.extend_from_slice(bytes);
+    Message::Binary(frame.into())
+}
+
+/// Unwraps a binary packet frame received from a peer that negotiated
+/// compression, inflating it to at most `max_size` bytes.
+pub fn decompress_packet_frame(frame: Bytes, max_size: Option<usize>) -> Result<Bytes> {
+    match frame.first() {
+        Some(&WSIO_FRAME_FLAG_DEFLATE) => {
+            let max_size = max_size.unwrap_or(usize::MAX);
+            let mut bytes = Vec::new();
+            DeflateDecoder::new(&frame[1..])
+                .take((max_size as u64).saturating_add(1))
+                .read_to_end(&mut bytes)?;
+
+            if bytes.len() > max_size {
+                bail!("Inflated packet exceeds {max_size} bytes");
+            }
+
+            Ok(bytes.into())
+        },
+        Some(&WSIO_FRAME_FLAG_PLAIN) => Ok(frame.slice(1..)),
+        Some(flag) => bail!("Unknown packet frame flag: {flag}"),
+        None => bail!("Empty packet frame"),
+    }
+}
+
+#[inline]
+fn deflate_frame(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(vec![WSIO_FRAME_FLAG_DEFLATE], Compression::default());
+    encoder.write_all(bytes).ok()?;
+    encoder.finish().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_bytes(message: &Message) -> Bytes {
+        match message {
+            Message::Binary(bytes) => bytes.clone(),
+            _ => panic!("expected a binary frame, got {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_compress_packet_message_round_trip() {
+        let text = format!(r#"[1,"chat",{:?}]"#, "hello ".repeat(100));
+        let message = compress_packet_message(Message::Text(text.clone().into()), 64);
+        let frame = frame_bytes(&message);
+        assert_eq!(frame[0], WSIO_FRAME_FLAG_DEFLATE);
+        assert!(frame.len() < text.len());
+        assert_eq!(decompress_packet_frame(frame, None).unwrap(), text.as_bytes());
+
+        let packet = vec![7; 512];
+        let frame = frame_bytes(&compress_packet_message(Message::Binary(packet.clone().into()), 64));
+        assert_eq!(frame[0], WSIO_FRAME_FLAG_DEFLATE);
+        assert_eq!(decompress_packet_frame(frame, Some(512)).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_compress_packet_message_below_threshold() {
+        let message = compress_packet_message(Message::Text(r#"[1,"chat",null]"#.into()), 64);
+        assert_eq!(message, Message::Text(r#"[1,"chat",null]"#.into()));
+
+        let frame = frame_bytes(&compress_packet_message(Message::Binary(vec![1, 2, 3].into()), 64));
+        assert_eq!(frame, [WSIO_FRAME_FLAG_PLAIN, 1, 2, 3][..]);
+        assert_eq!(decompress_packet_frame(frame, None).unwrap(), [1, 2, 3][..]);
+
+        // Heartbeats and control frames are not packets
+        let heartbeat = Message::Binary(vec![1].into());
+        assert_eq!(compress_packet_message(heartbeat.clone(), 0), heartbeat);
+        assert_eq!(
+            compress_packet_message(Message::Ping(Bytes::new()), 0),
+            Message::Ping(Bytes::new())
+        );
+    }
+
+    #[test]
+    fn test_decompress_packet_frame_rejects_invalid_frames() {
+        let frame = frame_bytes(&compress_packet_message(Message::Binary(vec![0; 4096].into()), 0));
+        let err = decompress_packet_frame(frame, Some(1024)).unwrap_err();
+        assert!(err.to_string().contains("exceeds 1024 bytes"));
+
+        assert!(decompress_packet_frame(Bytes::from_static(&[9, 1]), None).is_err());
+        assert!(decompress_packet_frame(Bytes::new(), None).is_err());
+    }
+}
@@ -1,13 +1,36 @@
+use std::{
+    borrow::Cow,
+    fmt::{
+        Formatter,
+        Result as FmtResult,
+    },
+};
+
+use bytes::Bytes;
 use serde::{
     Deserialize,
+    Deserializer,
     Serialize,
+    Serializer,
+    de::{
+        Error as DeError,
+        SeqAccess,
+        Visitor,
+    },
+    ser::SerializeTupleStruct,
 };
 use serde_repr::{
     Deserialize_repr,
     Serialize_repr,
 };
 
+use crate::protocol::{
+    WsIoProtocolHandshake,
+    WsIoSessionRecovery,
+};
+
 pub mod codecs;
+pub mod compression;
 
 // Enums
 #[repr(u8)]
@@ -17,17 +40,54 @@ pub enum WsIoPacketType {
     Event = 1,
     Init = 2,
     Ready = 3,
+    Ack = 4,
+    Error = 5,
+    Ping = 6,
+    Pong = 7,
 }
 
 // Structs
 #[derive(Deserialize)]
-struct InnerPacket(WsIoPacketType, Option<String>, Option<Vec<u8>>);
+struct InnerPacket(
+    WsIoPacketType,
+    Option<String>,
+    Option<Vec<u8>>,
+    #[serde(default)] Option<u64>,
+);
+
+#[derive(Deserialize)]
+struct InnerPacketView<'a>(
+    WsIoPacketType,
+    #[serde(borrow, deserialize_with = "deserialize_optional_cow_str")] Option<Cow<'a, str>>,
+    #[serde(borrow, deserialize_with = "deserialize_optional_cow_bytes")] Option<Cow<'a, [u8]>>,
+    #[serde(default)] Option<u64>,
+);
+
+struct InnerPacketRef<'a>(
+    &'a WsIoPacketType,
+    &'a Option<String>,
+    &'a Option<Vec<u8>>,
+    &'a Option<u64>,
+);
 
-#[derive(Serialize)]
-struct InnerPacketRef<'a>(&'a WsIoPacketType, &'a Option<String>, &'a Option<Vec<u8>>);
+impl Serialize for InnerPacketRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The ack id is only written when present so packets without one keep the original three-field layout.
+        let mut tuple = serializer.serialize_tuple_struct("InnerPacketRef", 3 + self.3.is_some() as usize)?;
+        tuple.serialize_field(self.0)?;
+        tuple.serialize_field(self.1)?;
+        tuple.serialize_field(self.2)?;
+        if self.3.is_some() {
+            tuple.serialize_field(self.3)?;
+        }
+
+        tuple.end()
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct WsIoPacket {
+    pub ack_id: Option<u64>,
     pub data: Option<Vec<u8>>,
     pub key: Option<String>,
     pub r#type: WsIoPacketType,
@@ -37,6 +97,7 @@ impl WsIoPacket {
     #[inline]
     pub fn new(r#type: WsIoPacketType, key: Option<&str>, data: Option<Vec<u8>>) -> Self {
         Self {
+            ack_id: None,
             data,
             key: key.map(|k| k.into()),
             r#type,
@@ -47,6 +108,7 @@ impl WsIoPacket {
     #[inline]
     pub(self) fn from_inner(inner: InnerPacket) -> Self {
         Self {
+            ack_id: inner.3,
             data: inner.2,
             key: inner.1,
             r#type: inner.0,
@@ -55,29 +117,266 @@ impl WsIoPacket {
 
     #[inline]
     pub(self) fn to_inner_ref(&self) -> InnerPacketRef<'_> {
-        InnerPacketRef(&self.r#type, &self.key, &self.data)
+        InnerPacketRef(&self.r#type, &self.key, &self.data, &self.ack_id)
     }
 
     // Public methods
+    #[inline]
+    pub fn new_ack(ack_id: u64, data: Option<Vec<u8>>) -> Self {
+        Self {
+            ack_id: Some(ack_id),
+            ..Self::new(WsIoPacketType::Ack, None, data)
+        }
+    }
+
+    /// Creates an ack packet telling the emitter that its handler failed.
+    ///
+    /// The failure code travels in the key, so the packet never carries reply
+    /// data.
+    #[inline]
+    pub fn new_ack_error(ack_id: u64, code: &str) -> Self {
+        Self {
+            ack_id: Some(ack_id),
+            ..Self::new(WsIoPacketType::Ack, Some(code), None)
+        }
+    }
+
     #[inline]
     pub fn new_disconnect() -> Self {
         Self::new(WsIoPacketType::Disconnect, None, None)
     }
 
+    /// Creates an error packet carrying an encoded
+    /// [`WsIoConnectError`](crate::error::connect::WsIoConnectError).
+    #[inline]
+    pub fn new_error(data: Vec<u8>) -> Self {
+        Self::new(WsIoPacketType::Error, None, Some(data))
+    }
+
     #[inline]
     pub fn new_event(event: &str, data: Option<Vec<u8>>) -> Self {
         Self::new(WsIoPacketType::Event, Some(event), data)
     }
 
+    #[inline]
+    pub fn new_event_with_ack(event: &str, data: Option<Vec<u8>>, ack_id: u64) -> Self {
+        Self {
+            ack_id: Some(ack_id),
+            ..Self::new_event(event, data)
+        }
+    }
+
     #[inline]
     pub fn new_init(data: Option<Vec<u8>>) -> Self {
         Self::new(WsIoPacketType::Init, None, data)
     }
 
+    /// Creates an init packet whose key carries the protocol handshake.
+    #[inline]
+    pub fn new_init_with_handshake(data: Option<Vec<u8>>, handshake: &WsIoProtocolHandshake) -> Self {
+        Self {
+            key: Some(handshake.to_string()),
+            ..Self::new_init(data)
+        }
+    }
+
+    /// Creates a ping packet; the ack id slot carries the ping id and `data`
+    /// the latency the client measured last, if any.
+    #[inline]
+    pub fn new_ping(ping_id: u64, data: Option<Vec<u8>>) -> Self {
+        Self {
+            ack_id: Some(ping_id),
+            ..Self::new(WsIoPacketType::Ping, None, data)
+        }
+    }
+
+    /// Creates a pong packet answering the ping `ping_id`, with `data`
+    /// carrying the encoded server time.
+    #[inline]
+    pub fn new_pong(ping_id: u64, data: Vec<u8>) -> Self {
+        Self {
+            ack_id: Some(ping_id),
+            ..Self::new(WsIoPacketType::Pong, None, Some(data))
+        }
+    }
+
     #[inline]
     pub fn new_ready() -> Self {
         Self::new(WsIoPacketType::Ready, None, None)
     }
+
+    /// Creates a ready packet whose key carries the session recovery
+    /// information.
+    #[inline]
+    pub fn new_ready_with_recovery(recovery: &WsIoSessionRecovery) -> Self {
+        Self {
+            key: Some(recovery.to_string()),
+            ..Self::new_ready()
+        }
+    }
+}
+
+/// Packet decoded without copying its key or data out of the frame where the
+/// codec allows it.
+///
+/// Self-describing text codecs encode data as a number array, so `data` is
+/// only borrowed for codecs that write it as a byte string.
+#[derive(Clone, Debug)]
+pub struct WsIoPacketView<'a> {
+    pub ack_id: Option<u64>,
+    pub data: Option<Cow<'a, [u8]>>,
+    pub key: Option<Cow<'a, str>>,
+    pub r#type: WsIoPacketType,
+}
+
+impl<'a> WsIoPacketView<'a> {
+    // Protected methods
+    #[inline]
+    pub(self) fn from_inner(inner: InnerPacketView<'a>) -> Self {
+        Self {
+            ack_id: inner.3,
+            data: inner.2,
+            key: inner.1,
+            r#type: inner.0,
+        }
+    }
+
+    // Public methods
+    #[inline]
+    pub fn into_owned(self) -> WsIoPacket {
+        WsIoPacket {
+            ack_id: self.ack_id,
+            data: self.data.map(Cow::into_owned),
+            key: self.key.map(Cow::into_owned),
+            r#type: self.r#type,
+        }
+    }
+
+    /// Takes the packet data as [`Bytes`].
+    ///
+    /// Borrowed data is sliced out of `source`, the frame this view was decoded
+    /// from, without copying.
+    #[inline]
+    pub fn take_data_bytes(&mut self, source: &Bytes) -> Option<Bytes> {
+        self.data.take().map(|data| match data {
+            Cow::Borrowed(data) => source.slice_ref(data),
+            Cow::Owned(data) => data.into(),
+        })
+    }
+}
+
+impl From<WsIoPacket> for WsIoPacketView<'_> {
+    #[inline]
+    fn from(packet: WsIoPacket) -> Self {
+        Self {
+            ack_id: packet.ack_id,
+            data: packet.data.map(Cow::Owned),
+            key: packet.key.map(Cow::Owned),
+            r#type: packet.r#type,
+        }
+    }
+}
+
+struct CowBytesVisitor;
+
+impl<'de> Visitor<'de> for CowBytesVisitor {
+    type Value = Option<Cow<'de, [u8]>>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("optional bytes")
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E: DeError>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Borrowed(v)))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: DeError>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(v)))
+    }
+
+    #[inline]
+    fn visit_bytes<E: DeError>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(v.to_vec())))
+    }
+
+    #[inline]
+    fn visit_none<E: DeError>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+
+        Ok(Some(Cow::Owned(bytes)))
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_bytes(self)
+    }
+
+    #[inline]
+    fn visit_unit<E: DeError>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+}
+
+struct CowStrVisitor;
+
+impl<'de> Visitor<'de> for CowStrVisitor {
+    type Value = Option<Cow<'de, str>>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("optional string")
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: DeError>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Borrowed(v)))
+    }
+
+    #[inline]
+    fn visit_none<E: DeError>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+
+    #[inline]
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(v.into())))
+    }
+
+    #[inline]
+    fn visit_string<E: DeError>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Some(Cow::Owned(v)))
+    }
+
+    #[inline]
+    fn visit_unit<E: DeError>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+}
+
+// Functions
+#[inline]
+fn deserialize_optional_cow_bytes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'de, [u8]>>, D::Error> {
+    deserializer.deserialize_option(CowBytesVisitor)
+}
+
+#[inline]
+fn deserialize_optional_cow_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error> {
+    deserializer.deserialize_option(CowStrVisitor)
 }
 
 #[cfg(test)]
@@ -86,12 +385,32 @@ mod tests {
 
     #[test]
     fn test_new_packet_constructors() {
+        // Ack
+        let packet = WsIoPacket::new_ack(7, Some(vec![7]));
+        assert!(matches!(packet.r#type, WsIoPacketType::Ack));
+        assert_eq!(packet.ack_id, Some(7));
+        assert_eq!(packet.key, None);
+        assert_eq!(packet.data.as_deref(), Some(&[7][..]));
+
+        // Ack error
+        let packet = WsIoPacket::new_ack_error(9, "timeout");
+        assert!(matches!(packet.r#type, WsIoPacketType::Ack));
+        assert_eq!(packet.ack_id, Some(9));
+        assert_eq!(packet.key.as_deref(), Some("timeout"));
+        assert_eq!(packet.data, None);
+
         // Disconnect
         let packet = WsIoPacket::new_disconnect();
         assert!(matches!(packet.r#type, WsIoPacketType::Disconnect));
         assert_eq!(packet.key, None);
         assert_eq!(packet.data, None);
 
+        // Error
+        let packet = WsIoPacket::new_error(vec![8]);
+        assert!(matches!(packet.r#type, WsIoPacketType::Error));
+        assert_eq!(packet.key, None);
+        assert_eq!(packet.data.as_deref(), Some(&[8][..]));
+
         // Event without data
         let packet = WsIoPacket::new_event("chat", None);
         assert!(matches!(packet.r#type, WsIoPacketType::Event));
@@ -103,6 +422,13 @@ mod tests {
         assert!(matches!(packet.r#type, WsIoPacketType::Event));
         assert_eq!(packet.key.as_deref(), Some("chat"));
         assert_eq!(packet.data.as_deref(), Some(&[1, 2, 3][..]));
+        assert_eq!(packet.ack_id, None);
+
+        // Event with ack id
+        let packet = WsIoPacket::new_event_with_ack("chat", None, 9);
+        assert!(matches!(packet.r#type, WsIoPacketType::Event));
+        assert_eq!(packet.key.as_deref(), Some("chat"));
+        assert_eq!(packet.ack_id, Some(9));
 
         // Init with data
         let packet = WsIoPacket::new_init(Some(vec![4, 5, 6]));
@@ -110,10 +436,41 @@ mod tests {
         assert_eq!(packet.key, None);
         assert_eq!(packet.data.as_deref(), Some(&[4, 5, 6][..]));
 
+        // Init with handshake
+        let packet = WsIoPacket::new_init_with_handshake(None, &WsIoProtocolHandshake::LOCAL);
+        assert!(matches!(packet.r#type, WsIoPacketType::Init));
+        assert_eq!(packet.key, Some(WsIoProtocolHandshake::LOCAL.to_string()));
+        assert_eq!(packet.data, None);
+
+        // Ping
+        let packet = WsIoPacket::new_ping(3, None);
+        assert!(matches!(packet.r#type, WsIoPacketType::Ping));
+        assert_eq!(packet.ack_id, Some(3));
+        assert_eq!(packet.key, None);
+        assert_eq!(packet.data, None);
+
+        // Pong
+        let packet = WsIoPacket::new_pong(3, vec![1]);
+        assert!(matches!(packet.r#type, WsIoPacketType::Pong));
+        assert_eq!(packet.ack_id, Some(3));
+        assert_eq!(packet.key, None);
+        assert_eq!(packet.data.as_deref(), Some(&[1][..]));
+
         // Ready
         let packet = WsIoPacket::new_ready();
         assert!(matches!(packet.r#type, WsIoPacketType::Ready));
         assert_eq!(packet.key, None);
         assert_eq!(packet.data, None);
+
+        // Ready with recovery
+        let recovery = WsIoSessionRecovery {
+            recovered: false,
+            token: "1.a".into(),
+        };
+
+        let packet = WsIoPacket::new_ready_with_recovery(&recovery);
+        assert!(matches!(packet.r#type, WsIoPacketType::Ready));
+        assert_eq!(packet.key, Some(recovery.to_string()));
+        assert_eq!(packet.data, None);
     }
 }
@@ -0,0 +1,341 @@
+use std::{
+    fmt::{
+        Display,
+        Formatter,
+        Result as FmtResult,
+    },
+    ops::{
+        BitAnd,
+        BitOr,
+    },
+    str::FromStr,
+};
+
+use anyhow::{
+    Error,
+    Result,
+    bail,
+};
+
+// Constants/Statics
+/// Oldest protocol version this build can still talk to.
+///
+/// Version `0` is the handshake-less protocol spoken by peers that predate
+/// version negotiation.
+pub const WSIO_PROTOCOL_MIN_VERSION: u16 = 0;
+
+/// Protocol version spoken by this build.
+pub const WSIO_PROTOCOL_VERSION: u16 = 1;
+
+/// Request header a reconnecting client presents its session token in.
+///
+/// The token is a bearer credential for the dropped session, so it is sent as
+/// a header rather than in the URL, which proxies and access logs record.
+pub const WSIO_SESSION_HEADER: &str = "x-wsio-session";
+
+// Structs
+/// Set of optional protocol features a peer supports.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WsIoProtocolCapabilities(u32);
+
+impl WsIoProtocolCapabilities {
+    /// Events may carry an ack id and be answered with ack packets.
+    pub const ACKS: Self = Self(1 << 0);
+
+    /// Reserved for batching several packets into one frame.
+    pub const BATCHING: Self = Self(1 << 2);
+
+    /// Packets above the sender's compression threshold are deflated, and
+    /// binary packet frames after the handshake start with a flag byte saying
+    /// whether they are.
+    ///
+    /// Only advertised by peers that enable compression, so it is negotiated
+    /// when both sides opt in. See
+    /// [`compress_packet_message`](crate::packet::compression::compress_packet_message).
+    pub const COMPRESSION: Self = Self(1 << 1);
+
+    /// A rejected handshake is explained with an error packet before the
+    /// connection closes.
+    pub const CONNECT_ERRORS: Self = Self(1 << 3);
+
+    /// Ping packets are answered with pong packets carrying the server time,
+    /// so clients can measure latency and estimate their clock offset.
+    pub const LATENCY: Self = Self(1 << 4);
+
+    /// Ready packets carry a session token the client presents when it
+    /// reconnects, so the server can restore a connection that dropped.
+    pub const STATE_RECOVERY: Self = Self(1 << 5);
+
+    /// Capabilities implemented by this build.
+    pub const SUPPORTED: Self =
+        Self(Self::ACKS.0 | Self::COMPRESSION.0 | Self::CONNECT_ERRORS.0 | Self::LATENCY.0 | Self::STATE_RECOVERY.0);
+
+    #[inline]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    // Public methods
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// This set without the capabilities in `other`.
+    #[inline]
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitAnd for WsIoProtocolCapabilities {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for WsIoProtocolCapabilities {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Protocol information a peer sends in the key of its init packet.
+///
+/// It is encoded as `<version>:<min_version>:<capabilities>` so peers that
+/// predate negotiation, which never read init packet keys, ignore it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WsIoProtocolHandshake {
+    pub capabilities: WsIoProtocolCapabilities,
+    pub min_version: u16,
+    pub version: u16,
+}
+
+impl WsIoProtocolHandshake {
+    /// Handshake assumed for peers that did not send one.
+    pub const LEGACY: Self = Self {
+        capabilities: WsIoProtocolCapabilities::empty(),
+        min_version: 0,
+        version: 0,
+    };
+
+    /// Handshake describing this build.
+    pub const LOCAL: Self = Self {
+        capabilities: WsIoProtocolCapabilities::SUPPORTED,
+        min_version: WSIO_PROTOCOL_MIN_VERSION,
+        version: WSIO_PROTOCOL_VERSION,
+    };
+
+    // Public methods
+
+    /// Negotiates the protocol spoken with `peer`, treating a missing handshake
+    /// as [`LEGACY`](Self::LEGACY).
+    ///
+    /// The highest version both sides speak is picked and the capabilities are
+    /// the intersection of both sets. Fails with a human-readable reason when
+    /// the supported version ranges do not overlap.
+    pub fn negotiate(&self, peer: Option<&Self>) -> Result<WsIoNegotiatedProtocol> {
+        let peer = peer.unwrap_or(&Self::LEGACY);
+        let version = self.version.min(peer.version);
+        if version < self.min_version.max(peer.min_version) {
+            bail!(
+                "Incompatible protocol version: local supports {}..={}, peer supports {}..={}",
+                self.min_version,
+                self.version,
+                peer.min_version,
+                peer.version
+            );
+        }
+
+        Ok(WsIoNegotiatedProtocol {
+            capabilities: self.capabilities & peer.capabilities,
+            version,
+        })
+    }
+
+    /// This handshake without advertising `capabilities`, for features the
+    /// local side leaves disabled.
+    #[inline]
+    pub const fn without_capabilities(self, capabilities: WsIoProtocolCapabilities) -> Self {
+        Self {
+            capabilities: self.capabilities.difference(capabilities),
+            ..self
+        }
+    }
+}
+
+impl Display for WsIoProtocolHandshake {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}:{}:{}", self.version, self.min_version, self.capabilities.bits())
+    }
+}
+
+impl FromStr for WsIoProtocolHandshake {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':');
+        let (Some(version), Some(min_version), Some(capabilities), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("Invalid protocol handshake: {s:?}");
+        };
+
+        Ok(Self {
+            capabilities: WsIoProtocolCapabilities::from_bits(capabilities.parse()?),
+            min_version: min_version.parse()?,
+            version: version.parse()?,
+        })
+    }
+}
+
+/// Session recovery information the server sends in the key of its ready
+/// packet when state recovery is enabled.
+///
+/// It is encoded as `<recovered>:<token>`, where `recovered` is `1` when the
+/// connection was restored from a previous session and `0` otherwise.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WsIoSessionRecovery {
+    pub recovered: bool,
+    pub token: String,
+}
+
+impl Display for WsIoSessionRecovery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}:{}", u8::from(self.recovered), self.token)
+    }
+}
+
+impl FromStr for WsIoSessionRecovery {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (recovered, token) = match s.split_once(':') {
+            Some(("0", token)) if !token.is_empty() => (false, token),
+            Some(("1", token)) if !token.is_empty() => (true, token),
+            _ => bail!("Invalid session recovery: {s:?}"),
+        };
+
+        Ok(Self {
+            recovered,
+            token: token.into(),
+        })
+    }
+}
+
+/// Protocol agreed on by both peers of a connection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WsIoNegotiatedProtocol {
+    pub capabilities: WsIoProtocolCapabilities,
+    pub version: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_set_operations() {
+        let capabilities = WsIoProtocolCapabilities::ACKS | WsIoProtocolCapabilities::BATCHING;
+        assert!(capabilities.contains(WsIoProtocolCapabilities::ACKS));
+        assert!(!capabilities.contains(WsIoProtocolCapabilities::COMPRESSION));
+        assert_eq!(
+            capabilities & WsIoProtocolCapabilities::BATCHING,
+            WsIoProtocolCapabilities::BATCHING
+        );
+
+        assert!(WsIoProtocolCapabilities::empty().is_empty());
+    }
+
+    #[test]
+    fn test_handshake_string_roundtrip() {
+        let handshake = WsIoProtocolHandshake {
+            capabilities: WsIoProtocolCapabilities::ACKS | WsIoProtocolCapabilities::COMPRESSION,
+            min_version: 1,
+            version: 3,
+        };
+
+        assert_eq!(handshake.to_string(), "3:1:3");
+        assert_eq!(
+            handshake.to_string().parse::<WsIoProtocolHandshake>().unwrap(),
+            handshake
+        );
+
+        assert!("1:0".parse::<WsIoProtocolHandshake>().is_err());
+        assert!("1:0:1:0".parse::<WsIoProtocolHandshake>().is_err());
+        assert!("a:0:1".parse::<WsIoProtocolHandshake>().is_err());
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let local = WsIoProtocolHandshake {
+            capabilities: WsIoProtocolCapabilities::ACKS | WsIoProtocolCapabilities::BATCHING,
+            min_version: 1,
+            version: 3,
+        };
+
+        let peer = WsIoProtocolHandshake {
+            capabilities: WsIoProtocolCapabilities::ACKS | WsIoProtocolCapabilities::COMPRESSION,
+            min_version: 0,
+            version: 2,
+        };
+
+        let negotiated = local.negotiate(Some(&peer)).unwrap();
+        assert_eq!(negotiated.version, 2);
+        assert_eq!(negotiated.capabilities, WsIoProtocolCapabilities::ACKS);
+
+        // Local requires at least version 1, the legacy peer only speaks 0
+        let err = local.negotiate(None).unwrap_err();
+        assert!(err.to_string().contains("Incompatible protocol version"));
+
+        let negotiated = WsIoProtocolHandshake::LOCAL.negotiate(None).unwrap();
+        assert_eq!(negotiated.version, 0);
+        assert!(negotiated.capabilities.is_empty());
+
+        // Compression is only negotiated when both sides advertise it
+        let without_compression =
+            WsIoProtocolHandshake::LOCAL.without_capabilities(WsIoProtocolCapabilities::COMPRESSION);
+        let negotiated = WsIoProtocolHandshake::LOCAL
+            .negotiate(Some(&without_compression))
+            .unwrap();
+        assert!(!negotiated.capabilities.contains(WsIoProtocolCapabilities::COMPRESSION));
+        assert!(negotiated.capabilities.contains(WsIoProtocolCapabilities::ACKS));
+    }
+
+    #[test]
+    fn test_session_recovery_string_roundtrip() {
+        let recovery = WsIoSessionRecovery {
+            recovered: true,
+            token: "7.abc".into(),
+        };
+
+        assert_eq!(recovery.to_string(), "1:7.abc");
+        assert_eq!(recovery.to_string().parse::<WsIoSessionRecovery>().unwrap(), recovery);
+        assert!(!"0:7.abc".parse::<WsIoSessionRecovery>().unwrap().recovered);
+
+        assert!("2:7.abc".parse::<WsIoSessionRecovery>().is_err());
+        assert!("1:".parse::<WsIoSessionRecovery>().is_err());
+        assert!("7.abc".parse::<WsIoSessionRecovery>().is_err());
+    }
+}
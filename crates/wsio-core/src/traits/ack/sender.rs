@@ -0,0 +1,9 @@
+use anyhow::Result;
+
+pub trait AckSender: Send + Sync + 'static {
+    fn send_ack(&self, ack_id: u64, data: Option<Vec<u8>>) -> impl Future<Output = Result<()>> + Send;
+
+    /// Tells the emitter that the handler answering `ack_id` failed with
+    /// `code` instead of replying.
+    fn send_ack_error(&self, ack_id: u64, code: &str) -> impl Future<Output = Result<()>> + Send;
+}
@@ -0,0 +1,6 @@
+use crate::error::WsIoError;
+
+pub trait ErrorReporter: Send + Sync + 'static {
+    /// Hands `error` to the user's error hook, if one is configured.
+    fn report_error(&self, error: WsIoError);
+}
@@ -0,0 +1,51 @@
+use serde::{
+    Serialize,
+    de::DeserializeOwned,
+};
+#[cfg(feature = "derive")]
+pub use wsio_macros::WsIoEvent;
+
+/// An event name bound to its payload type.
+///
+/// Sharing implementations between client and server, for example in a
+/// common crate, lets `emit_typed` and `on_typed` check names and payloads at
+/// compile time. Usually derived with the `derive` feature.
+pub trait WsIoEvent: Send + Sync + 'static {
+    const NAME: &'static str;
+
+    type Data: DeserializeOwned + Serialize + Send + Sync + 'static;
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Serialize, WsIoEvent)]
+    #[wsio(crate = "crate")]
+    struct ChatMessage {
+        text: String,
+    }
+
+    #[derive(WsIoEvent)]
+    #[wsio(crate = "crate", data = bool, name = "chat:typing")]
+    struct Typing;
+
+    fn encode<E: WsIoEvent>(data: &E::Data) -> String {
+        serde_json::to_string(data).unwrap()
+    }
+
+    #[test]
+    fn test_derived_events() {
+        assert_eq!(ChatMessage::NAME, "chat_message");
+        assert_eq!(Typing::NAME, "chat:typing");
+
+        // Payload types follow the `data` attribute, defaulting to the type itself
+        assert_eq!(
+            encode::<ChatMessage>(&ChatMessage { text: "hi".into() }),
+            r#"{"text":"hi"}"#
+        );
+        assert_eq!(encode::<Typing>(&true), "true");
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+};
+
+use anyhow::Result;
+
+use crate::event::extract::WsIoEventContext;
+
+// Types
+pub type EventHandlerFuture = Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>;
+
+/// A handler argument resolved from the incoming event.
+///
+/// Implemented by `Arc<C>` for the connection or session the event arrived
+/// on, by the extractors in [`crate::event::extract`] and by `Option<T>` to
+/// make any extractor optional. Implement it for your own types to pull
+/// per-event values out of the context. A failed extraction skips the handler
+/// and is reported to the `on_error` hook.
+pub trait FromEventContext<C>: Sized {
+    fn from_event_context(event_ctx: &WsIoEventContext<C>) -> Result<Self>;
+}
+
+/// An async function whose arguments are all [`FromEventContext`] extractors.
+///
+/// Implemented for closures and functions of up to eight arguments; `T` is
+/// the tuple of their types.
+pub trait EventHandler<C, T>: Send + Sync + 'static {
+    fn call(&self, event_ctx: &WsIoEventContext<C>) -> Result<EventHandlerFuture>;
+}
+
+impl<C, T: FromEventContext<C>> FromEventContext<C> for Option<T> {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<C>) -> Result<Self> {
+        Ok(T::from_event_context(event_ctx).ok())
+    }
+}
+
+impl<C> FromEventContext<C> for Arc<C> {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<C>) -> Result<Self> {
+        Ok(event_ctx.ctx().clone())
+    }
+}
+
+macro_rules! impl_event_handler {
+    ($($extractor:ident),+) => {
+        impl<C, F, Fut, $($extractor),+> EventHandler<C, ($($extractor,)+)> for F
+        where
+            F: Fn($($extractor),+) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<()>> + Send + 'static,
+            $($extractor: FromEventContext<C>),+
+        {
+            #[allow(non_snake_case)]
+            fn call(&self, event_ctx: &WsIoEventContext<C>) -> Result<EventHandlerFuture> {
+                $(let $extractor = $extractor::from_event_context(event_ctx)?;)+
+                Ok(Box::pin(self($($extractor),+)))
+            }
+        }
+    };
+}
+
+impl_event_handler!(T1);
+impl_event_handler!(T1, T2);
+impl_event_handler!(T1, T2, T3);
+impl_event_handler!(T1, T2, T3, T4);
+impl_event_handler!(T1, T2, T3, T4, T5);
+impl_event_handler!(T1, T2, T3, T4, T5, T6);
+impl_event_handler!(T1, T2, T3, T4, T5, T6, T7);
+impl_event_handler!(T1, T2, T3, T4, T5, T6, T7, T8);
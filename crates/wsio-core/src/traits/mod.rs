@@ -1 +1,5 @@
+pub mod ack;
+pub mod error;
+pub mod event;
+pub mod packet;
 pub mod task;
@@ -0,0 +1,122 @@
+use std::fmt::Debug;
+
+use anyhow::{
+    Result,
+    anyhow,
+};
+use erased_serde::{
+    Deserializer as ErasedDeserializer,
+    Serialize as ErasedSerialize,
+    deserialize,
+};
+use serde::de::DeserializeOwned;
+
+use crate::packet::{
+    WsIoPacket,
+    WsIoPacketView,
+};
+
+// Types
+pub type DataVisitor<'a> = dyn FnMut(&mut dyn ErasedDeserializer<'_>) -> Result<()> + 'a;
+
+/// Wire format used to encode packets and the event data they carry.
+///
+/// The trait is object safe so configs can hold any implementation behind an
+/// `Arc<dyn PacketCodec>`. Data goes through `erased_serde`: `encode_data`
+/// receives the value as `&dyn erased_serde::Serialize`, and `decode_data`
+/// hands an erased deserializer over the bytes to `visitor`, which typed
+/// callers reach through [`decode_data_as`](Self::decode_data_as).
+pub trait PacketCodec: Debug + Send + Sync + 'static {
+    fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket>;
+
+    fn decode_data(&self, bytes: &[u8], visitor: &mut DataVisitor<'_>) -> Result<()>;
+
+    /// Decodes a packet, borrowing its key and data from `bytes` where the
+    /// format allows it. Defaults to an owned [`decode`](Self::decode).
+    #[inline]
+    fn decode_view<'a>(&self, bytes: &'a [u8]) -> Result<WsIoPacketView<'a>> {
+        Ok(self.decode(bytes)?.into())
+    }
+
+    fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>>;
+
+    fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>>;
+
+    /// Whether encoded packets are sent as text frames instead of binary ones.
+    fn is_text(&self) -> bool;
+
+    /// Identifier clients use to request this codec, for example through the
+    /// `codec` query parameter of the upgrade request.
+    ///
+    /// Codecs producing the same wire format may share a name.
+    fn name(&self) -> &str;
+}
+
+impl dyn PacketCodec {
+    pub fn decode_data_as<D: DeserializeOwned>(&self, bytes: &[u8]) -> Result<D> {
+        let mut data = None;
+        self.decode_data(bytes, &mut |deserializer| {
+            data = Some(deserialize(deserializer)?);
+            Ok(())
+        })?;
+
+        data.ok_or_else(|| anyhow!("Packet codec {self:?} did not decode any data"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::codecs::WsIoPacketCodec;
+
+    #[derive(Debug)]
+    struct SkippingCodec;
+
+    impl PacketCodec for SkippingCodec {
+        fn decode(&self, bytes: &[u8]) -> Result<WsIoPacket> {
+            WsIoPacketCodec::SerdeJson.decode(bytes)
+        }
+
+        fn decode_data(&self, _bytes: &[u8], _visitor: &mut DataVisitor<'_>) -> Result<()> {
+            Ok(())
+        }
+
+        fn encode(&self, packet: &WsIoPacket) -> Result<Vec<u8>> {
+            WsIoPacketCodec::SerdeJson.encode(packet)
+        }
+
+        fn encode_data(&self, data: &dyn ErasedSerialize) -> Result<Vec<u8>> {
+            WsIoPacketCodec::SerdeJson.encode_data(data)
+        }
+
+        fn is_text(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            "skipping"
+        }
+    }
+
+    #[test]
+    fn test_decode_data_as() {
+        let codec: &dyn PacketCodec = &WsIoPacketCodec::SerdeJson;
+        let bytes = codec.encode_data(&(1, "one")).unwrap();
+
+        let data: (u32, String) = codec.decode_data_as(&bytes).unwrap();
+        assert_eq!(data, (1, "one".into()));
+
+        assert!(codec.decode_data_as::<u32>(b"1 2").is_err());
+    }
+
+    #[test]
+    fn test_decode_data_as_fails_when_visitor_is_not_called() {
+        let codec: &dyn PacketCodec = &SkippingCodec;
+        let result = codec.decode_data_as::<u32>(b"1");
+        assert!(result.unwrap_err().to_string().contains("did not decode any data"));
+
+        // Packets still go through the default view implementation
+        let packet_view = codec.decode_view(br#"[1,"chat",null]"#).unwrap();
+        assert_eq!(packet_view.key.as_deref(), Some("chat"));
+    }
+}
@@ -2,14 +2,123 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use tokio::{
+    runtime::Handle,
     select,
     spawn,
+    sync::mpsc::{
+        channel,
+        error::SendError,
+    },
+    task::spawn_blocking,
 };
 use tokio_util::sync::CancellationToken;
 
+use crate::event::execution::{
+    QueuedHandlerTask,
+    SEQUENTIAL_QUEUE_CAPACITY,
+    WsIoHandlerExecutionMode,
+    WsIoHandlerExecutor,
+};
+
 pub trait TaskSpawner: Send + Sync + 'static {
     fn cancel_token(&self) -> Arc<CancellationToken>;
 
+    /// Runs `future` on tokio's blocking thread pool, driving it with the
+    /// current runtime handle.
+    #[inline]
+    fn spawn_blocking_task<F: Future<Output = Result<()>> + Send + 'static>(&self, future: F) {
+        let cancel_token = self.cancel_token();
+        let handle = Handle::current();
+        spawn_blocking(move || {
+            handle.block_on(async move {
+                select! {
+                    _ = cancel_token.cancelled() => {},
+                    _ = future => {},
+                }
+            })
+        });
+    }
+
+    /// Runs `future` as required by the mode of `executor`.
+    ///
+    /// In a bounded or blocking mode the returned future first waits for a
+    /// permit and only then spawns `future`; in a sequential mode it waits for
+    /// room in the queue. Callers therefore feel a full executor as
+    /// backpressure. It gives up without running `future` once the cancel token
+    /// fires.
+    fn spawn_handler_task<F: Future<Output = Result<()>> + Send + 'static>(
+        &self,
+        executor: &WsIoHandlerExecutor,
+        future: F,
+    ) -> impl Future<Output = ()> + Send {
+        async move {
+            let cancel_token = self.cancel_token();
+            match executor.mode() {
+                WsIoHandlerExecutionMode::Blocking | WsIoHandlerExecutionMode::Bounded(_) => {
+                    let Some(semaphore) = executor.semaphore.clone() else {
+                        return self.spawn_task(future);
+                    };
+
+                    let permit = select! {
+                        _ = cancel_token.cancelled() => return,
+                        permit = semaphore.acquire_owned() => permit,
+                    };
+
+                    let Ok(permit) = permit else {
+                        return;
+                    };
+
+                    let future = async move {
+                        let _permit = permit;
+                        future.await
+                    };
+
+                    match executor.mode() {
+                        WsIoHandlerExecutionMode::Blocking => self.spawn_blocking_task(future),
+                        _ => self.spawn_task(future),
+                    }
+                },
+                WsIoHandlerExecutionMode::Concurrent => self.spawn_task(future),
+                WsIoHandlerExecutionMode::Sequential => {
+                    let mut task: QueuedHandlerTask = Box::pin(async move {
+                        let _ = future.await;
+                    });
+
+                    while !cancel_token.is_cancelled() {
+                        let tx = {
+                            let mut sequential_task_tx = executor.sequential_task_tx.lock();
+                            match sequential_task_tx.as_ref().filter(|tx| !tx.is_closed()) {
+                                Some(tx) => tx.clone(),
+                                None => {
+                                    // No worker is running, or it was cancelled together
+                                    // with its cancel token, so start a new one
+                                    let (tx, mut rx) = channel(SEQUENTIAL_QUEUE_CAPACITY);
+                                    *sequential_task_tx = Some(tx.clone());
+                                    self.spawn_task(async move {
+                                        while let Some(task) = rx.recv().await {
+                                            task.await;
+                                        }
+
+                                        Ok(())
+                                    });
+
+                                    tx
+                                },
+                            }
+                        };
+
+                        // Waits while the queue is full; a worker that stops
+                        // meanwhile hands the task back for a new one
+                        match tx.send(task).await {
+                            Ok(()) => return,
+                            Err(SendError(returned_task)) => task = returned_task,
+                        }
+                    }
+                },
+            }
+        }
+    }
+
     #[inline]
     fn spawn_task<F: Future<Output = Result<()>> + Send + 'static>(&self, future: F) {
         let cancel_token = self.cancel_token();
@@ -24,17 +133,28 @@ pub trait TaskSpawner: Send + Sync + 'static {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::{
-        AtomicBool,
-        Ordering,
+    use std::{
+        sync::atomic::{
+            AtomicBool,
+            AtomicUsize,
+            Ordering,
+        },
+        thread::current,
+        time::Duration,
     };
 
+    use parking_lot::Mutex;
     use tokio::{
         sync::oneshot::channel,
         task::yield_now,
+        time::{
+            sleep,
+            timeout,
+        },
     };
 
     use super::*;
+    use crate::event::execution::BLOCKING_TASK_LIMIT;
 
     struct TestSpawner {
         cancel_token: Arc<CancellationToken>,
@@ -99,4 +219,232 @@ mod tests {
             "Task should have been cancelled before completion"
         );
     }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_sequential_preserves_order() {
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Sequential);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for index in 0..5u64 {
+            let order = order.clone();
+            spawner
+                .spawn_handler_task(&executor, async move {
+                    // Earlier tasks sleep longer, so any overlap would reorder them
+                    sleep(Duration::from_millis(10 * (5 - index))).await;
+                    order.lock().push(index);
+                    Ok(())
+                })
+                .await;
+        }
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(*order.lock(), [0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_sequential_restarts_after_cancel() {
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Sequential);
+        spawner.spawn_handler_task(&executor, async { Ok(()) }).await;
+        spawner.cancel_token.cancel();
+        yield_now().await;
+
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let flag_clone = flag.clone();
+        spawner
+            .spawn_handler_task(&executor, async move {
+                flag_clone.store(true, Ordering::Relaxed);
+                Ok(())
+            })
+            .await;
+
+        yield_now().await;
+        yield_now().await;
+        assert!(flag.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_bounded_limits_concurrency() {
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Bounded(2));
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let finished = Arc::new(AtomicUsize::new(0));
+        for _ in 0..6 {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            let finished = finished.clone();
+            spawner
+                .spawn_handler_task(&executor, async move {
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now_running, Ordering::SeqCst);
+                    sleep(Duration::from_millis(10)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    finished.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                })
+                .await;
+        }
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(finished.load(Ordering::SeqCst), 6);
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_bounded_waits_for_permit_before_spawning() {
+        let cancel_token = Arc::new(CancellationToken::new());
+        let spawner = TestSpawner {
+            cancel_token: cancel_token.clone(),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Bounded(1));
+        let (tx, rx) = channel::<()>();
+        spawner
+            .spawn_handler_task(&executor, async move {
+                let _ = rx.await;
+                Ok(())
+            })
+            .await;
+
+        // The slot is taken, so the next event is held back by the caller
+        let blocked = timeout(
+            Duration::from_millis(20),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await;
+
+        assert!(blocked.is_err());
+
+        let _ = tx.send(());
+        timeout(
+            Duration::from_millis(100),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await
+        .expect("a finished event should free its permit");
+
+        // A cancelled spawner stops waiting for a permit
+        let _permit = executor.semaphore.clone().unwrap().acquire_owned().await.unwrap();
+        cancel_token.cancel();
+        timeout(
+            Duration::from_millis(100),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await
+        .expect("cancellation should end the wait for a permit");
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_sequential_waits_for_room_in_full_queue() {
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Sequential);
+        let (tx, rx) = channel::<()>();
+        spawner
+            .spawn_handler_task(&executor, async move {
+                let _ = rx.await;
+                Ok(())
+            })
+            .await;
+
+        // Let the worker take the running task out of the queue
+        sleep(Duration::from_millis(10)).await;
+        for _ in 0..SEQUENTIAL_QUEUE_CAPACITY {
+            timeout(
+                Duration::from_millis(100),
+                spawner.spawn_handler_task(&executor, async { Ok(()) }),
+            )
+            .await
+            .expect("the queue should have room");
+        }
+
+        let blocked = timeout(
+            Duration::from_millis(20),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await;
+
+        assert!(blocked.is_err());
+
+        let _ = tx.send(());
+        timeout(
+            Duration::from_millis(100),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await
+        .expect("a draining queue should take the next task");
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_blocking_limits_concurrency() {
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Blocking);
+        let mut release_txs = Vec::new();
+        for _ in 0..BLOCKING_TASK_LIMIT {
+            let (tx, rx) = channel::<()>();
+            release_txs.push(tx);
+            spawner
+                .spawn_handler_task(&executor, async move {
+                    let _ = rx.await;
+                    Ok(())
+                })
+                .await;
+        }
+
+        let blocked = timeout(
+            Duration::from_millis(20),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await;
+
+        assert!(blocked.is_err());
+
+        let _ = release_txs.pop().unwrap().send(());
+        timeout(
+            Duration::from_millis(100),
+            spawner.spawn_handler_task(&executor, async { Ok(()) }),
+        )
+        .await
+        .expect("a finished blocking task should free its permit");
+
+        spawner.cancel_token.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_spawn_handler_task_blocking_runs_off_runtime_thread() {
+        let spawner = TestSpawner {
+            cancel_token: Arc::new(CancellationToken::new()),
+        };
+
+        let executor = WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Blocking);
+        let runtime_thread_id = current().id();
+        let (tx, rx) = channel();
+        spawner
+            .spawn_handler_task(&executor, async move {
+                let _ = tx.send(current().id());
+                Ok(())
+            })
+            .await;
+
+        assert_ne!(rx.await.unwrap(), runtime_thread_id);
+    }
 }
@@ -5,8 +5,11 @@ use std::{
 
 use anyhow::Result;
 
+use crate::error::WsIoError;
+
 type AsyncUnaryResultHandler<T> =
     dyn Fn(Arc<T>) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>> + Send + Sync + 'static;
 
 pub type ArcAsyncUnaryResultHandler<T> = Arc<AsyncUnaryResultHandler<T>>;
 pub type BoxAsyncUnaryResultHandler<T> = Box<AsyncUnaryResultHandler<T>>;
+pub type BoxErrorHandler = Box<dyn Fn(WsIoError) + Send + Sync + 'static>;
@@ -0,0 +1,39 @@
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+/// Shifts `time` by a signed number of microseconds.
+#[inline]
+pub fn shift_system_time(time: SystemTime, offset_micros: i64) -> SystemTime {
+    let offset = Duration::from_micros(offset_micros.unsigned_abs());
+    if offset_micros < 0 {
+        time - offset
+    } else {
+        time + offset
+    }
+}
+
+/// Microseconds elapsed since the unix epoch, or `0` if the system clock is
+/// set before it.
+#[inline]
+pub fn unix_time_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_micros() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_system_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(10);
+        assert_eq!(shift_system_time(time, 1_500), time + Duration::from_micros(1_500));
+        assert_eq!(shift_system_time(time, -1_500), time - Duration::from_micros(1_500));
+        assert_eq!(shift_system_time(time, 0), time);
+        assert!(unix_time_micros() > 0);
+    }
+}
@@ -0,0 +1,353 @@
+use std::fmt::{
+    Formatter,
+    Result as FmtResult,
+};
+
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+    de::{
+        Error as DeError,
+        MapAccess,
+        SeqAccess,
+        Visitor,
+    },
+    ser::{
+        SerializeMap,
+        SerializeSeq,
+    },
+};
+
+// Enums
+
+/// Codec-agnostic tree of an event payload.
+///
+/// Decoding into `WsIoValue` works with every self-describing packet codec
+/// (JSON, CBOR, MessagePack), which lets an event be inspected or rewritten
+/// without a Rust type for its payload. Postcard is not self-describing and
+/// cannot decode into it.
+///
+/// Map entries keep their wire order and keys may be any value, since CBOR and
+/// MessagePack allow non-string keys.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum WsIoValue {
+    Array(Vec<WsIoValue>),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    F64(f64),
+    I64(i64),
+    Map(Vec<(WsIoValue, WsIoValue)>),
+    #[default]
+    Null,
+    String(String),
+    U64(u64),
+}
+
+impl WsIoValue {
+    // Public methods
+    #[inline]
+    pub fn as_array(&self) -> Option<&[WsIoValue]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as `f64`, converting integers.
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::F64(value) => Some(*value),
+            Self::I64(value) => Some(*value as f64),
+            Self::U64(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer as `i64` when it fits.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::I64(value) => Some(*value),
+            Self::U64(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_map(&self) -> Option<&[(WsIoValue, WsIoValue)]> {
+        match self {
+            Self::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer as `u64` when it is not negative.
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::I64(value) => u64::try_from(*value).ok(),
+            Self::U64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Looks up the first map entry whose key is the string `key`.
+    pub fn get(&self, key: &str) -> Option<&WsIoValue> {
+        self.as_map()?
+            .iter()
+            .find(|(entry_key, _)| entry_key.as_str() == Some(key))
+            .map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+}
+
+impl<'de> Deserialize<'de> for WsIoValue {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(WsIoValueVisitor)
+    }
+}
+
+impl Serialize for WsIoValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+
+                seq.end()
+            },
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Self::F64(value) => serializer.serialize_f64(*value),
+            Self::I64(value) => serializer.serialize_i64(*value),
+            Self::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+
+                map.end()
+            },
+            Self::Null => serializer.serialize_unit(),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::U64(value) => serializer.serialize_u64(*value),
+        }
+    }
+}
+
+// Structs
+struct WsIoValueVisitor;
+
+impl<'de> Visitor<'de> for WsIoValueVisitor {
+    type Value = WsIoValue;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("any value")
+    }
+
+    #[inline]
+    fn visit_bool<E: DeError>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(WsIoValue::Bool(v))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: DeError>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(WsIoValue::Bytes(v))
+    }
+
+    #[inline]
+    fn visit_bytes<E: DeError>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(WsIoValue::Bytes(v.to_vec()))
+    }
+
+    #[inline]
+    fn visit_f64<E: DeError>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(WsIoValue::F64(v))
+    }
+
+    #[inline]
+    fn visit_i64<E: DeError>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(WsIoValue::I64(v))
+    }
+
+    #[inline]
+    fn visit_i128<E: DeError>(self, v: i128) -> Result<Self::Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(WsIoValue::I64(v)),
+            Err(_) => self.visit_u128(v.try_into().map_err(|_| E::custom("integer out of range"))?),
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(WsIoValue::Map(entries))
+    }
+
+    #[inline]
+    fn visit_none<E: DeError>(self) -> Result<Self::Value, E> {
+        Ok(WsIoValue::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(WsIoValue::Array(values))
+    }
+
+    #[inline]
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    #[inline]
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(WsIoValue::String(v.into()))
+    }
+
+    #[inline]
+    fn visit_string<E: DeError>(self, v: String) -> Result<Self::Value, E> {
+        Ok(WsIoValue::String(v))
+    }
+
+    #[inline]
+    fn visit_u64<E: DeError>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(WsIoValue::U64(v))
+    }
+
+    #[inline]
+    fn visit_u128<E: DeError>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(WsIoValue::U64(
+            v.try_into().map_err(|_| E::custom("integer out of range"))?,
+        ))
+    }
+
+    #[inline]
+    fn visit_unit<E: DeError>(self) -> Result<Self::Value, E> {
+        Ok(WsIoValue::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{
+        packet::codecs::WsIoPacketCodec,
+        traits::packet::codec::PacketCodec,
+    };
+
+    #[derive(Serialize)]
+    struct ChatMessage {
+        id: u64,
+        offset: i64,
+        tags: Vec<&'static str>,
+        text: &'static str,
+        urgent: bool,
+    }
+
+    fn assert_chat_message_value(value: &WsIoValue) {
+        assert_eq!(value.get("id").and_then(WsIoValue::as_u64), Some(7));
+        assert_eq!(value.get("offset").and_then(WsIoValue::as_i64), Some(-3));
+        assert_eq!(value.get("text").and_then(WsIoValue::as_str), Some("hello"));
+        assert_eq!(value.get("urgent").and_then(WsIoValue::as_bool), Some(true));
+        assert_eq!(
+            value.get("tags").and_then(WsIoValue::as_array),
+            Some(&[WsIoValue::String("a".into()), WsIoValue::String("b".into())][..])
+        );
+        assert!(value.get("missing").is_none());
+    }
+
+    fn assert_value_roundtrip(packet_codec: &dyn PacketCodec) {
+        let message = ChatMessage {
+            id: 7,
+            offset: -3,
+            tags: vec!["a", "b"],
+            text: "hello",
+            urgent: true,
+        };
+
+        let value = packet_codec
+            .decode_data_as::<WsIoValue>(&packet_codec.encode_data(&message).unwrap())
+            .unwrap();
+        assert_chat_message_value(&value);
+
+        // Re-encoding the tree produces an equivalent payload
+        let value = packet_codec
+            .decode_data_as::<WsIoValue>(&packet_codec.encode_data(&value).unwrap())
+            .unwrap();
+        assert_chat_message_value(&value);
+    }
+
+    #[test]
+    fn test_value_roundtrip_serde_json() {
+        assert_value_roundtrip(&WsIoPacketCodec::SerdeJson);
+    }
+
+    #[cfg(feature = "packet-codec-cbor")]
+    #[test]
+    fn test_value_roundtrip_cbor() {
+        assert_value_roundtrip(&WsIoPacketCodec::Cbor);
+    }
+
+    #[cfg(feature = "packet-codec-msgpack")]
+    #[test]
+    fn test_value_roundtrip_msgpack() {
+        assert_value_roundtrip(&WsIoPacketCodec::Msgpack);
+    }
+
+    #[cfg(feature = "packet-codec-sonic-rs")]
+    #[test]
+    fn test_value_roundtrip_sonic_rs() {
+        assert_value_roundtrip(&WsIoPacketCodec::SonicRs);
+    }
+
+    #[test]
+    fn test_value_number_conversions() {
+        assert_eq!(WsIoValue::U64(u64::MAX).as_i64(), None);
+        assert_eq!(WsIoValue::I64(-1).as_u64(), None);
+        assert_eq!(WsIoValue::I64(2).as_f64(), Some(2.0));
+        assert!(WsIoValue::default().is_null());
+    }
+}
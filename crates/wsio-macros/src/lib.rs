@@ -0,0 +1,154 @@
+#![cfg_attr(test, allow(clippy::expect_used, clippy::unwrap_used))]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    DeriveInput,
+    Error,
+    LitStr,
+    Path,
+    Result,
+    Type,
+    parse_macro_input,
+    parse_quote,
+};
+
+// Functions
+
+/// Derives `WsIoEvent` for a type, tying an event name to a payload type.
+///
+/// The payload defaults to the type itself and the name to the type name in
+/// snake case. Both can be set with the `wsio` attribute:
+///
+/// ```ignore
+/// #[derive(Deserialize, Serialize, WsIoEvent)]
+/// #[wsio(name = "chat:message")]
+/// struct ChatMessage {
+///     text: String,
+/// }
+///
+/// #[derive(WsIoEvent)]
+/// #[wsio(name = "chat:typing", data = bool)]
+/// struct Typing;
+/// ```
+///
+/// The impl refers to `::wsio_core`. Use `#[wsio(crate = "wsio_client::core")]`
+/// or `#[wsio(crate = "wsio_server::core")]` in crates that only depend on the
+/// client or the server.
+#[proc_macro_derive(WsIoEvent, attributes(wsio))]
+pub fn derive_wsio_event(input: TokenStream) -> TokenStream {
+    expand_wsio_event(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_wsio_event(input: DeriveInput) -> Result<TokenStream2> {
+    let mut crate_path: Option<Path> = None;
+    let mut data: Option<Type> = None;
+    let mut name: Option<LitStr> = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("wsio")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                crate_path = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("data") {
+                data = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("name") {
+                let value = meta.value()?.parse::<LitStr>()?;
+                if value.value().is_empty() {
+                    return Err(Error::new(value.span(), "event name must not be empty"));
+                }
+
+                name = Some(value);
+            } else {
+                return Err(meta.error("unsupported wsio attribute, expected `crate`, `data` or `name`"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let ident = &input.ident;
+    let crate_path = crate_path.unwrap_or_else(|| parse_quote!(::wsio_core));
+    let data = data.unwrap_or_else(|| parse_quote!(Self));
+    let name = name.unwrap_or_else(|| LitStr::new(&to_snake_case(&ident.to_string()), ident.span()));
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #crate_path::traits::event::definition::WsIoEvent for #ident #type_generics #where_clause {
+            const NAME: &'static str = #name;
+            type Data = #data;
+        }
+    })
+}
+
+fn to_snake_case(ident: &str) -> String {
+    let mut snake_case = String::with_capacity(ident.len() + 4);
+    let chars = ident.chars().collect::<Vec<_>>();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // Start a new word at a lowercase-to-uppercase step, or before the last capital of an acronym
+            let prev_is_lower = i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if i > 0 && (prev_is_lower || (chars[i - 1].is_uppercase() && next_is_lower)) {
+                snake_case.push('_');
+            }
+
+            snake_case.extend(c.to_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+    }
+
+    snake_case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: TokenStream2) -> Result<String> {
+        expand_wsio_event(syn::parse2(input)?).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn test_expand_defaults_to_snake_case_name_and_self_data() {
+        let expanded = expand(quote! { struct ChatMessage { text: String } }).unwrap();
+        assert!(expanded.contains(":: wsio_core :: traits :: event :: definition :: WsIoEvent for ChatMessage"));
+        assert!(expanded.contains(r#"const NAME : & 'static str = "chat_message""#));
+        assert!(expanded.contains("type Data = Self"));
+    }
+
+    #[test]
+    fn test_expand_reads_wsio_attributes() {
+        let expanded = expand(quote! {
+            #[wsio(crate = "wsio_client::core", data = Vec<u8>, name = "chat:typing")]
+            struct Typing<T> where T: Clone;
+        })
+        .unwrap();
+
+        assert!(
+            expanded.contains(
+                "impl < T > wsio_client :: core :: traits :: event :: definition :: WsIoEvent for Typing < T >"
+            )
+        );
+        assert!(expanded.contains(r#""chat:typing""#));
+        assert!(expanded.contains("type Data = Vec < u8 >"));
+    }
+
+    #[test]
+    fn test_expand_rejects_unknown_and_empty_attributes() {
+        let err = expand(quote! { #[wsio(rename = "x")] struct Event; }).unwrap_err();
+        assert!(err.to_string().contains("unsupported wsio attribute"));
+
+        let err = expand(quote! { #[wsio(name = "")] struct Event; }).unwrap_err();
+        assert_eq!(err.to_string(), "event name must not be empty");
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("Chat"), "chat");
+        assert_eq!(to_snake_case("ChatMessage"), "chat_message");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(to_snake_case("Room2Joined"), "room2_joined");
+    }
+}
@@ -1,11 +1,23 @@
-use std::time::Duration;
+use std::{
+    any::TypeId,
+    sync::Arc,
+    time::Duration,
+};
 
 use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 
 use crate::{
     WsIoServer,
-    config::WsIoServerConfig,
-    core::packet::codecs::WsIoPacketCodec,
+    config::{
+        StateMap,
+        WsIoServerConfig,
+    },
+    core::{
+        error::WsIoProtocolViolationPolicy,
+        event::execution::WsIoHandlerExecutionMode,
+        packet::codecs::WsIoPacketCodec,
+        traits::packet::codec::PacketCodec,
+    },
     runtime::WsIoServerRuntime,
 };
 
@@ -25,7 +37,14 @@ impl WsIoServerBuilder {
     pub(crate) fn new() -> Self {
         Self {
             config: WsIoServerConfig {
+                ack_timeout: Duration::from_secs(10),
                 broadcast_concurrency_limit: 512,
+                compression_threshold: None,
+                event_handler_timeout: None,
+                event_middleware_timeout: Duration::from_secs(2),
+                handler_execution_mode: WsIoHandlerExecutionMode::Concurrent,
+                heartbeat_interval: None,
+                heartbeat_max_missed: 3,
                 http_request_upgrade_timeout: Duration::from_secs(3),
                 init_request_handler_timeout: Duration::from_secs(3),
                 init_response_handler_timeout: Duration::from_secs(3),
@@ -33,8 +52,14 @@ impl WsIoServerBuilder {
                 middleware_execution_timeout: Duration::from_secs(2),
                 on_close_handler_timeout: Duration::from_secs(2),
                 on_connect_handler_timeout: Duration::from_secs(3),
-                packet_codec: WsIoPacketCodec::SerdeJson,
+                on_disconnect_handler_timeout: Duration::from_secs(2),
+                packet_codec: Arc::new(WsIoPacketCodec::SerdeJson),
+                ping_interval: None,
+                protocol_violation_policy: WsIoProtocolViolationPolicy::Disconnect,
+                recovery_buffer_size: 1024,
+                recovery_window: None,
                 request_path: "/ws.io".into(),
+                states: StateMap::default(),
                 websocket_config: WebSocketConfig::default()
                     .max_frame_size(Some(8 * 1024 * 1024))
                     .max_message_size(Some(16 * 1024 * 1024))
@@ -46,6 +71,15 @@ impl WsIoServerBuilder {
     }
 
     // Public methods
+    /// Sets the default timeout for waiting on event acknowledgements.
+    ///
+    /// This applies to `emit_with_ack` calls on connections. Namespace builders
+    /// inherit this value and may override it.
+    pub fn ack_timeout(mut self, duration: Duration) -> Self {
+        self.config.ack_timeout = duration;
+        self
+    }
+
     /// Sets the default maximum number of broadcast send operations to run at
     /// once.
     ///
@@ -56,11 +90,75 @@ impl WsIoServerBuilder {
         self
     }
 
+    /// Sets the default minimum encoded size in bytes from which packets are
+    /// deflated.
+    ///
+    /// Compression is negotiated through the `COMPRESSION` capability and only
+    /// applies to clients that enable it too. Unset by default. Namespace
+    /// builders inherit this value and may override it.
+    pub fn compression_threshold(mut self, bytes: usize) -> Self {
+        self.config.compression_threshold = Some(bytes);
+        self
+    }
+
+    /// Sets the default maximum duration an event handler may run.
+    ///
+    /// A handler still running after `duration` is dropped, fails its ack with
+    /// a timeout error and is reported to the `on_error` hook as timed out.
+    /// Unset by default. Namespace builders inherit this value and may override
+    /// it.
+    pub fn event_handler_timeout(mut self, duration: Duration) -> Self {
+        self.config.event_handler_timeout = Some(duration);
+        self
+    }
+
     /// Builds a [`WsIoServer`] with the accumulated configuration.
     pub fn build(self) -> WsIoServer {
         WsIoServer(WsIoServerRuntime::new(self.config))
     }
 
+    /// Sets the default maximum duration an incoming event may spend in the
+    /// event middleware stack.
+    ///
+    /// Only the middleware calls count; handlers of an event the stack let
+    /// through are bounded by `event_handler_timeout`. Namespace builders
+    /// inherit this value and may override it.
+    pub fn event_middleware_timeout(mut self, duration: Duration) -> Self {
+        self.config.event_middleware_timeout = duration;
+        self
+    }
+
+    /// Sets the default execution mode for incoming event handlers.
+    ///
+    /// Namespace builders inherit this value and may override it.
+    pub fn handler_execution_mode(mut self, mode: WsIoHandlerExecutionMode) -> Self {
+        self.config.handler_execution_mode = mode;
+        self
+    }
+
+    /// Sets the default interval at which connections are expected to send a
+    /// frame.
+    ///
+    /// A connection that stays silent for [`Self::heartbeat_max_missed`]
+    /// intervals in a row is closed and cleaned up. Any frame counts, including
+    /// client heartbeats and pongs answering [`Self::ping_interval`] pings.
+    /// Unset by default. Namespace builders inherit this value and may
+    /// override it.
+    pub fn heartbeat_interval(mut self, duration: Duration) -> Self {
+        self.config.heartbeat_interval = Some(duration);
+        self
+    }
+
+    /// Sets the default number of heartbeat intervals in a row a connection
+    /// may miss before it is closed.
+    ///
+    /// Defaults to `3`; `0` is treated as `1`. Namespace builders inherit this
+    /// value and may override it.
+    pub fn heartbeat_max_missed(mut self, count: u32) -> Self {
+        self.config.heartbeat_max_missed = count;
+        self
+    }
+
     /// Sets the default timeout for a matched HTTP request to finish the
     /// WebSocket upgrade.
     ///
@@ -126,12 +224,64 @@ impl WsIoServerBuilder {
         self
     }
 
+    /// Sets the default maximum duration allowed for namespace on-disconnect
+    /// handlers.
+    ///
+    /// On-disconnect handlers are registered per namespace with
+    /// `WsIoServerNamespaceBuilder::on_disconnect`.
+    pub fn on_disconnect_handler_timeout(mut self, duration: Duration) -> Self {
+        self.config.on_disconnect_handler_timeout = duration;
+        self
+    }
+
     /// Sets the default packet codec for namespaces.
     ///
     /// The codec is used for ws.io protocol packets and init payload data.
+    /// Namespace builders inherit this value and may override it. Accepts the
+    /// built-in [`WsIoPacketCodec`] or any custom [`PacketCodec`] implementation.
+    pub fn packet_codec<C: PacketCodec>(mut self, packet_codec: C) -> Self {
+        self.config.packet_codec = Arc::new(packet_codec);
+        self
+    }
+
+    /// Sets the default interval at which the server sends WebSocket Ping
+    /// frames to its connections.
+    ///
+    /// Unset by default. Namespace builders inherit this value and may
+    /// override it.
+    pub fn ping_interval(mut self, duration: Duration) -> Self {
+        self.config.ping_interval = Some(duration);
+        self
+    }
+
+    /// Sets the default policy for clients that break the ws.io protocol.
+    ///
+    /// With [`WsIoProtocolViolationPolicy::Disconnect`] the connection is closed
+    /// with a protocol error close frame; with
+    /// [`WsIoProtocolViolationPolicy::Report`] the offending packet is dropped.
     /// Namespace builders inherit this value and may override it.
-    pub fn packet_codec(mut self, packet_codec: WsIoPacketCodec) -> Self {
-        self.config.packet_codec = packet_codec;
+    pub fn protocol_violation_policy(mut self, policy: WsIoProtocolViolationPolicy) -> Self {
+        self.config.protocol_violation_policy = policy;
+        self
+    }
+
+    /// Sets the default maximum number of broadcast packets kept for a
+    /// connection waiting to be recovered.
+    ///
+    /// Defaults to `1024`. Namespace builders inherit this value and may
+    /// override it.
+    pub fn recovery_buffer_size(mut self, recovery_buffer_size: usize) -> Self {
+        self.config.recovery_buffer_size = recovery_buffer_size;
+        self
+    }
+
+    /// Enables connection state recovery, keeping the session of a connection
+    /// that dropped without closing for `duration`.
+    ///
+    /// Unset by default. Namespace builders inherit this value and may
+    /// override it.
+    pub fn recovery_window(mut self, duration: Duration) -> Self {
+        self.config.recovery_window = Some(duration);
         self
     }
 
@@ -163,6 +313,17 @@ impl WsIoServerBuilder {
         f(&mut self.config.websocket_config);
         self
     }
+
+    /// Adds shared application state, such as a database pool, replacing any
+    /// earlier value of the same type.
+    ///
+    /// Namespace builders inherit every state and may add or replace values.
+    /// Handlers read it with `WsIoServerConnection::state` or the
+    /// [`WsIoState`](crate::extract::WsIoState) extractor.
+    pub fn with_state<S: Send + Sync + 'static>(mut self, state: S) -> Self {
+        self.config.states.insert(TypeId::of::<S>(), Arc::new(state));
+        self
+    }
 }
 
 #[cfg(test)]
@@ -174,7 +335,14 @@ mod tests {
     #[test]
     fn test_builder_configuration_chaining() {
         let server = WsIoServer::builder()
+            .ack_timeout(Duration::from_millis(500))
             .broadcast_concurrency_limit(1024)
+            .compression_threshold(512)
+            .event_handler_timeout(Duration::from_secs(8))
+            .event_middleware_timeout(Duration::from_secs(12))
+            .handler_execution_mode(WsIoHandlerExecutionMode::Sequential)
+            .heartbeat_interval(Duration::from_secs(9))
+            .heartbeat_max_missed(5)
             .http_request_upgrade_timeout(Duration::from_millis(750))
             .init_request_handler_timeout(Duration::from_secs(1))
             .init_response_handler_timeout(Duration::from_secs(2))
@@ -182,7 +350,12 @@ mod tests {
             .middleware_execution_timeout(Duration::from_secs(4))
             .on_close_handler_timeout(Duration::from_secs(5))
             .on_connect_handler_timeout(Duration::from_secs(6))
+            .on_disconnect_handler_timeout(Duration::from_secs(7))
             .packet_codec(WsIoPacketCodec::Msgpack)
+            .ping_interval(Duration::from_secs(10))
+            .protocol_violation_policy(WsIoProtocolViolationPolicy::Report)
+            .recovery_buffer_size(64)
+            .recovery_window(Duration::from_secs(11))
             .request_path("/custom")
             .websocket_config_mut(|config| {
                 *config = config.max_frame_size(Some(999));
@@ -191,7 +364,14 @@ mod tests {
 
         // Access internal config through the built runtime
         let config = &server.0.config;
+        assert_eq!(config.ack_timeout, Duration::from_millis(500));
         assert_eq!(config.broadcast_concurrency_limit, 1024);
+        assert_eq!(config.compression_threshold, Some(512));
+        assert_eq!(config.event_handler_timeout, Some(Duration::from_secs(8)));
+        assert_eq!(config.event_middleware_timeout, Duration::from_secs(12));
+        assert_eq!(config.handler_execution_mode, WsIoHandlerExecutionMode::Sequential);
+        assert_eq!(config.heartbeat_interval, Some(Duration::from_secs(9)));
+        assert_eq!(config.heartbeat_max_missed, 5);
         assert_eq!(config.http_request_upgrade_timeout, Duration::from_millis(750));
         assert_eq!(config.init_request_handler_timeout, Duration::from_secs(1));
         assert_eq!(config.init_response_handler_timeout, Duration::from_secs(2));
@@ -199,7 +379,13 @@ mod tests {
         assert_eq!(config.middleware_execution_timeout, Duration::from_secs(4));
         assert_eq!(config.on_close_handler_timeout, Duration::from_secs(5));
         assert_eq!(config.on_connect_handler_timeout, Duration::from_secs(6));
-        assert!(matches!(config.packet_codec, WsIoPacketCodec::Msgpack));
+        assert_eq!(config.on_disconnect_handler_timeout, Duration::from_secs(7));
+        assert_eq!(format!("{:?}", config.packet_codec), "Msgpack");
+        assert!(!config.packet_codec.is_text());
+        assert_eq!(config.ping_interval, Some(Duration::from_secs(10)));
+        assert_eq!(config.protocol_violation_policy, WsIoProtocolViolationPolicy::Report);
+        assert_eq!(config.recovery_buffer_size, 64);
+        assert_eq!(config.recovery_window, Some(Duration::from_secs(11)));
         assert_eq!(config.request_path, "/custom");
         assert_eq!(config.websocket_config.max_frame_size, Some(999));
     }
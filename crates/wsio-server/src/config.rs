@@ -1,12 +1,36 @@
-use std::time::Duration;
+use std::{
+    any::{
+        Any,
+        TypeId,
+    },
+    sync::Arc,
+    time::Duration,
+};
 
+use kikiutils::types::fx_collections::FxHashMap;
 use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 
-use crate::core::packet::codecs::WsIoPacketCodec;
+use crate::core::{
+    error::WsIoProtocolViolationPolicy,
+    event::execution::WsIoHandlerExecutionMode,
+    traits::packet::codec::PacketCodec,
+};
+
+// Types
+pub(crate) type StateMap = FxHashMap<TypeId, Arc<dyn Any + Send + Sync>>;
 
 // Structs
 #[derive(Debug)]
 pub(crate) struct WsIoServerConfig {
+    /// Maximum duration to wait for the peer to acknowledge an event emitted with
+    /// `emit_with_ack`.
+    ///
+    /// When the timeout elapses, the pending ack is dropped and the emit returns
+    /// an error.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) ack_timeout: Duration,
+
     /// Maximum number of namespace broadcast send operations to run at once.
     ///
     /// Higher values can improve fan-out throughput, but also increase the number of
@@ -17,6 +41,61 @@ pub(crate) struct WsIoServerConfig {
     /// Can be overridden by namespace-level configuration.
     pub(crate) broadcast_concurrency_limit: usize,
 
+    /// Minimum encoded size in bytes from which packets are deflated, or
+    /// `None` to leave compression off.
+    ///
+    /// When set, the `COMPRESSION` capability is advertised during the
+    /// handshake and only takes effect with clients that advertise it too.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) compression_threshold: Option<usize>,
+
+    /// Maximum duration an event handler may run before it is cut off and
+    /// reported as timed out, or `None` to let handlers run until they finish
+    /// or their connection closes.
+    ///
+    /// Individual events can get their own timeout with
+    /// `WsIoServerNamespaceBuilder::handler_timeout` or
+    /// `WsIoServerConnection::set_handler_timeout`.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) event_handler_timeout: Option<Duration>,
+
+    /// Maximum duration an incoming event may spend in the event middleware
+    /// stack.
+    ///
+    /// Only the middleware calls count; the event's handlers run after the
+    /// stack hands the event on and are bounded by `event_handler_timeout`.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) event_middleware_timeout: Duration,
+
+    /// How the handlers of incoming events run on a connection.
+    ///
+    /// Defaults to spawning a task per handler. Sequential and bounded modes
+    /// keep their order and limit per connection. Individual events can be
+    /// switched with `WsIoServerConnection::set_execution_mode`.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) handler_execution_mode: WsIoHandlerExecutionMode,
+
+    /// Interval at which connections are expected to send a frame, or `None`
+    /// to never time out idle connections.
+    ///
+    /// A connection silent for `heartbeat_max_missed` intervals in a row is
+    /// considered dead, closed and cleaned up, so half-open connections leave
+    /// the namespace and their rooms. Every frame counts, including client
+    /// heartbeats and the pongs answering `ping_interval` pings.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) heartbeat_interval: Option<Duration>,
+
+    /// Number of `heartbeat_interval`s in a row a connection may stay silent
+    /// before it is closed.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) heartbeat_max_missed: u32,
+
     /// Maximum duration allowed for an accepted HTTP request to finish the
     /// WebSocket upgrade.
     ///
@@ -54,8 +133,9 @@ pub(crate) struct WsIoServerConfig {
 
     /// Maximum duration allowed for namespace middleware execution.
     ///
-    /// Middleware is configured with `WsIoServerNamespaceBuilder::with_middleware`
-    /// and runs during connection setup before the on-connect handler.
+    /// Applies to each middleware added with
+    /// `WsIoServerNamespaceBuilder::with_middleware`, which runs during
+    /// connection setup before the on-connect handler.
     ///
     /// Can be overridden by namespace-level configuration.
     pub(crate) middleware_execution_timeout: Duration,
@@ -76,13 +156,58 @@ pub(crate) struct WsIoServerConfig {
     /// Can be overridden by namespace-level configuration.
     pub(crate) on_connect_handler_timeout: Duration,
 
+    /// Maximum duration allowed for the namespace on-disconnect handler to
+    /// execute.
+    ///
+    /// The on-disconnect handler is configured with
+    /// `WsIoServerNamespaceBuilder::on_disconnect` and runs while a ready
+    /// connection is cleaned up.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) on_disconnect_handler_timeout: Duration,
+
     /// Packet codec used to encode and decode ws.io protocol packets.
     ///
     /// The same codec must be understood by the client. It also controls whether
     /// encoded packets are sent as WebSocket text or binary messages.
     ///
     /// Can be overridden by namespace-level configuration.
-    pub(crate) packet_codec: WsIoPacketCodec,
+    pub(crate) packet_codec: Arc<dyn PacketCodec>,
+
+    /// Interval at which the server sends WebSocket Ping frames to every
+    /// connection, or `None` to rely on client heartbeats alone.
+    ///
+    /// Clients answer with Pong frames, so clients that send no heartbeats of
+    /// their own still count as alive for `heartbeat_interval`.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) ping_interval: Option<Duration>,
+
+    /// What a connection does when its client breaks the ws.io protocol.
+    ///
+    /// Violations are reported to the namespace `on_error` hook under either
+    /// policy.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) protocol_violation_policy: WsIoProtocolViolationPolicy,
+
+    /// Maximum number of broadcast packets kept for a connection waiting to be
+    /// recovered.
+    ///
+    /// A connection that misses more packets within `recovery_window` cannot be
+    /// recovered and is dropped instead.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) recovery_buffer_size: usize,
+
+    /// How long the session of a connection that dropped without closing is
+    /// kept for its client to recover, or `None` to disable recovery.
+    ///
+    /// A recovered connection keeps its id and rooms and receives the
+    /// broadcasts it missed in the meantime.
+    ///
+    /// Can be overridden by namespace-level configuration.
+    pub(crate) recovery_window: Option<Duration>,
 
     /// HTTP request path handled by the server adapter.
     ///
@@ -91,6 +216,13 @@ pub(crate) struct WsIoServerConfig {
     /// `namespace` query parameter.
     pub(crate) request_path: String,
 
+    /// Shared application state, one value per type.
+    ///
+    /// Set with `WsIoServerBuilder::with_state` and read through
+    /// `WsIoServer::state`. Namespace builders start from a copy and may add or
+    /// replace values per namespace.
+    pub(crate) states: StateMap,
+
     /// Tungstenite WebSocket transport limits and buffer sizes.
     ///
     /// This config is passed to `WebSocketStream::from_raw_socket` and is also
@@ -100,3 +232,11 @@ pub(crate) struct WsIoServerConfig {
     /// Can be overridden by namespace-level configuration.
     pub(crate) websocket_config: WebSocketConfig,
 }
+
+// Functions
+#[inline]
+pub(crate) fn get_state<S: Send + Sync + 'static>(states: &StateMap) -> Option<Arc<S>> {
+    states
+        .get(&TypeId::of::<S>())
+        .and_then(|state| state.clone().downcast().ok())
+}
@@ -7,30 +7,37 @@ use std::{
     sync::{
         Arc,
         LazyLock,
+        OnceLock,
         atomic::{
             AtomicU64,
             Ordering,
         },
     },
+    time::Duration,
 };
 
 use anyhow::{
+    Error,
     Result,
     bail,
 };
-use arc_swap::ArcSwap;
+use arc_swap::{
+    ArcSwap,
+    ArcSwapOption,
+};
 use http::{
     HeaderMap,
     Uri,
 };
 use kikiutils::{
     atomic::enum_cell::AtomicEnumCell,
-    types::fx_collections::FxDashSet,
+    types::fx_collections::FxHashSet,
 };
 use num_enum::{
     IntoPrimitive,
     TryFromPrimitive,
 };
+use parking_lot::Mutex as SyncMutex;
 use serde::{
     Serialize,
     de::DeserializeOwned,
@@ -44,6 +51,7 @@ use tokio::{
             Sender,
             channel,
         },
+        oneshot::Receiver as OneshotReceiver,
     },
     task::JoinHandle,
     time::{
@@ -51,7 +59,14 @@ use tokio::{
         timeout,
     },
 };
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::{
+    Bytes,
+    Message,
+    protocol::{
+        CloseFrame,
+        frame::coding::CloseCode,
+    },
+};
 use tokio_util::sync::CancellationToken;
 
 #[cfg(feature = "connection-extensions")]
@@ -63,18 +78,70 @@ use crate::{
     WsIoServer,
     core::{
         channel_capacity_from_websocket_config,
-        event::registry::WsIoEventRegistry,
+        error::{
+            WsIoError,
+            WsIoErrorKind,
+            WsIoProtocolViolationPolicy,
+            catch_handler,
+            connect::WsIoConnectError,
+            truncate_close_reason,
+        },
+        event::{
+            ack::{
+                WsIoEventAckRegistry,
+                WsIoEventAckReply,
+            },
+            execution::{
+                WsIoHandlerExecutionMode,
+                WsIoHandlerExecutor,
+            },
+            raw::WsIoRawEvent,
+            registry::WsIoEventRegistry,
+        },
         packet::{
             WsIoPacket,
             WsIoPacketType,
+            compression::{
+                compress_packet_message,
+                decompress_packet_frame,
+            },
+        },
+        protocol::{
+            WsIoNegotiatedProtocol,
+            WsIoProtocolCapabilities,
+            WsIoProtocolHandshake,
+            WsIoSessionRecovery,
+        },
+        traits::{
+            ack::sender::AckSender,
+            error::reporter::ErrorReporter,
+            event::{
+                definition::WsIoEvent,
+                extract::EventHandler,
+            },
+            packet::codec::PacketCodec,
+            task::spawner::TaskSpawner,
         },
-        traits::task::spawner::TaskSpawner,
         types::BoxAsyncUnaryResultHandler,
-        utils::task::abort_locked_task,
+        utils::{
+            task::abort_locked_task,
+            time::unix_time_micros,
+        },
+    },
+    middleware::{
+        OutgoingInterceptor,
+        WsIoEventNext,
+        WsIoIncomingEvent,
     },
     namespace::{
         WsIoServerNamespace,
+        encode_packet_to_message,
         operators::broadcast::WsIoServerNamespaceBroadcastOperator,
+        recovery::{
+            RecoveryDrain,
+            WsIoServerRecoverySession,
+            generate_recovery_secret,
+        },
     },
 };
 
@@ -93,17 +160,26 @@ enum ConnectionState {
 
 // Structs
 pub struct WsIoServerConnection {
+    ack_registry: WsIoEventAckRegistry,
     cancel_token: ArcSwap<CancellationToken>,
+    event_middleware_executor: WsIoHandlerExecutor,
     event_registry: WsIoEventRegistry<WsIoServerConnection, WsIoServerConnection>,
     #[cfg(feature = "connection-extensions")]
     extensions: ConnectionExtensions,
     headers: HeaderMap,
     id: u64,
     init_timeout_task: Mutex<Option<JoinHandle<()>>>,
-    joined_rooms: FxDashSet<String>,
+    joined_rooms: SyncMutex<Option<FxHashSet<String>>>,
+    latency_micros: AtomicU64,
     message_tx: Sender<Arc<Message>>,
     namespace: Arc<WsIoServerNamespace>,
     on_close_handler: Mutex<Option<BoxAsyncUnaryResultHandler<Self>>>,
+    outgoing_interceptors: ArcSwap<Vec<OutgoingInterceptor>>,
+    packet_codec: Arc<dyn PacketCodec>,
+    protocol: OnceLock<WsIoNegotiatedProtocol>,
+    recovered: bool,
+    recovery_secret: Option<String>,
+    recovery_session: ArcSwapOption<WsIoServerRecoverySession>,
     request_uri: Uri,
     state: AtomicEnumCell<ConnectionState>,
 }
@@ -138,13 +214,24 @@ impl FmtDebug for WsIoServerConnection {
             .field("state", &self.state)
             .field("request_uri", &self.request_uri)
             .field("headers", &self.headers)
-            .field("joined_rooms_len", &self.joined_rooms.len())
+            .field(
+                "joined_rooms_len",
+                &self.joined_rooms.lock().as_ref().map_or(0, FxHashSet::len),
+            )
+            .field("latency", &self.latency())
             .field("message_tx", &self.message_tx)
+            .field("packet_codec", &self.packet_codec)
+            .field("protocol", &self.protocol.get())
+            .field("recovered", &self.recovered)
+            .field("recovery_session", &self.recovery_session.load().as_deref())
+            .field("ack_registry", &self.ack_registry)
             .field("cancel_token", &"<cancel_token>")
             .field("namespace", &"<namespace>")
+            .field("event_middleware_executor", &self.event_middleware_executor)
             .field("event_registry", &self.event_registry)
             .field("init_timeout_task", &init_timeout_task)
-            .field("on_close_handler", &on_close_handler);
+            .field("on_close_handler", &on_close_handler)
+            .field("outgoing_interceptors_len", &self.outgoing_interceptors.load().len());
 
         #[cfg(feature = "connection-extensions")]
         debug.field("extensions", &self.extensions);
@@ -153,6 +240,27 @@ impl FmtDebug for WsIoServerConnection {
     }
 }
 
+impl AckSender for WsIoServerConnection {
+    #[inline]
+    async fn send_ack(&self, ack_id: u64, data: Option<Vec<u8>>) -> Result<()> {
+        self.send_packet(&WsIoPacket::new_ack(ack_id, data)).await
+    }
+
+    #[inline]
+    async fn send_ack_error(&self, ack_id: u64, code: &str) -> Result<()> {
+        self.send_packet(&WsIoPacket::new_ack_error(ack_id, code)).await
+    }
+}
+
+impl ErrorReporter for WsIoServerConnection {
+    #[inline]
+    fn report_error(&self, error: WsIoError) {
+        if let Some(error_handler) = &self.namespace.config.error_handler {
+            error_handler(error.with_connection_id(self.id));
+        }
+    }
+}
+
 impl TaskSpawner for WsIoServerConnection {
     #[inline]
     fn cancel_token(&self) -> Arc<CancellationToken> {
@@ -161,27 +269,54 @@ impl TaskSpawner for WsIoServerConnection {
 }
 
 impl WsIoServerConnection {
+    /// Creates a connection, reusing the id of `recovery_session` when the
+    /// client claimed one.
     #[inline]
     pub(crate) fn new(
         headers: HeaderMap,
         namespace: Arc<WsIoServerNamespace>,
+        packet_codec: Arc<dyn PacketCodec>,
+        recovery_session: Option<Arc<WsIoServerRecoverySession>>,
         request_uri: Uri,
     ) -> (Arc<Self>, Receiver<Arc<Message>>) {
         let channel_capacity = channel_capacity_from_websocket_config(&namespace.config.websocket_config);
         let (message_tx, message_rx) = channel(channel_capacity);
+        let id = match &recovery_session {
+            Some(recovery_session) => recovery_session.id(),
+            None => NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+        };
+
+        let recovery_secret = namespace
+            .config
+            .recovery_window
+            .and_then(|_| generate_recovery_secret());
+
         (
             Arc::new(Self {
+                ack_registry: WsIoEventAckRegistry::new(),
                 cancel_token: ArcSwap::new(Arc::new(CancellationToken::new())),
-                event_registry: WsIoEventRegistry::new(),
+                event_middleware_executor: WsIoHandlerExecutor::new(WsIoHandlerExecutionMode::Sequential),
+                event_registry: WsIoEventRegistry::with_shared(
+                    namespace.config.handler_execution_mode,
+                    namespace.config.event_registry.clone(),
+                )
+                .with_default_handler_timeout(namespace.config.event_handler_timeout),
                 #[cfg(feature = "connection-extensions")]
                 extensions: ConnectionExtensions::new(),
                 headers,
-                id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+                id,
                 init_timeout_task: Mutex::new(None),
-                joined_rooms: FxDashSet::default(),
+                joined_rooms: SyncMutex::new(Some(FxHashSet::default())),
+                latency_micros: AtomicU64::new(UNKNOWN_LATENCY),
                 message_tx,
                 namespace,
                 on_close_handler: Mutex::new(None),
+                outgoing_interceptors: ArcSwap::from_pointee(Vec::new()),
+                packet_codec,
+                protocol: OnceLock::new(),
+                recovered: recovery_session.is_some(),
+                recovery_secret,
+                recovery_session: ArcSwapOption::new(recovery_session),
                 request_uri,
                 state: AtomicEnumCell::new(ConnectionState::Created),
             }),
@@ -190,50 +325,29 @@ impl WsIoServerConnection {
     }
 
     // Private methods
-    #[inline]
-    fn handle_event_packet(self: &Arc<Self>, event: &str, packet_data: Option<Vec<u8>>) -> Result<()> {
-        self.event_registry.dispatch_event_packet(
-            self.clone(),
-            event,
-            &self.namespace.config.packet_codec,
-            packet_data,
-            self,
-        );
-
-        Ok(())
-    }
-
-    async fn handle_init_packet(self: &Arc<Self>, packet_data: Option<&[u8]>) -> Result<()> {
-        // Verify current state; only valid from AwaitingInit → Initiating
-        let state = self.state.get();
-        match state {
-            ConnectionState::AwaitingInit => self.state.try_transition(state, ConnectionState::Initiating)?,
-            _ => bail!("Received init packet in invalid state: {state:?}"),
-        }
-
-        // Abort init-timeout task
-        abort_locked_task(&self.init_timeout_task).await;
-
+    /// Runs the init-response handler, middleware and on-connect handler,
+    /// moving the connection from Initiating to Activating.
+    async fn activate(self: &Arc<Self>, packet_data: Option<&[u8]>) -> Result<()> {
         // Invoke init_response_handler with timeout protection if configured
         if let Some(init_response_handler) = &self.namespace.config.init_response_handler {
-            timeout(
+            self.run_lifecycle_handler(
+                "init_response",
                 self.namespace.config.init_response_handler_timeout,
-                init_response_handler(self.clone(), packet_data, &self.namespace.config.packet_codec),
+                || init_response_handler(self.clone(), packet_data, &*self.packet_codec),
             )
-            .await??
+            .await?;
         }
 
         // Activate connection
         self.state
             .try_transition(ConnectionState::Initiating, ConnectionState::Activating)?;
 
-        // Invoke middleware with timeout protection if configured
-        if let Some(middleware) = &self.namespace.config.middleware {
-            timeout(
-                self.namespace.config.middleware_execution_timeout,
-                middleware(self.clone()),
-            )
-            .await??;
+        // Invoke middlewares in order with timeout protection
+        for middleware in &self.namespace.config.middlewares {
+            self.run_lifecycle_handler("middleware", self.namespace.config.middleware_execution_timeout, || {
+                middleware(self.clone())
+            })
+            .await?;
 
             // Ensure connection is still in Activating state
             self.state.ensure(ConnectionState::Activating, |state| {
@@ -243,66 +357,461 @@ impl WsIoServerConnection {
 
         // Invoke on_connect_handler with timeout protection if configured
         if let Some(on_connect_handler) = &self.namespace.config.on_connect_handler {
-            timeout(
-                self.namespace.config.on_connect_handler_timeout,
-                on_connect_handler(self.clone()),
-            )
-            .await??;
+            self.run_lifecycle_handler("on_connect", self.namespace.config.on_connect_handler_timeout, || {
+                on_connect_handler(self.clone())
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Size threshold from which packets are deflated, or `None` unless both
+    /// the namespace and the client enabled compression.
+    #[inline]
+    fn compression_threshold(&self) -> Option<usize> {
+        self.namespace.config.compression_threshold.filter(|_| {
+            self.protocol_capabilities()
+                .contains(WsIoProtocolCapabilities::COMPRESSION)
+        })
+    }
+
+    #[inline]
+    fn encode_event_data<D: Serialize>(&self, data: Option<&D>) -> Result<Option<Vec<u8>>> {
+        data.map(|data| self.packet_codec.encode_data(data)).transpose()
+    }
+
+    #[inline]
+    fn encode_packet_to_message(&self, packet: &WsIoPacket) -> Result<Arc<Message>> {
+        encode_packet_to_message(&*self.packet_codec, packet)
+    }
+
+    #[inline]
+    fn handle_ack_packet(
+        &self,
+        ack_id: Option<u64>,
+        error_code: Option<&str>,
+        packet_data: Option<&[u8]>,
+    ) -> Result<()> {
+        let Some(ack_id) = ack_id else {
+            bail!("Ack packet missing ack id");
+        };
+
+        match error_code {
+            Some(error_code) => self.ack_registry.reject(ack_id, error_code.into()),
+            None => self.ack_registry.resolve(ack_id, packet_data.map(<[u8]>::to_vec)),
+        };
+
+        Ok(())
+    }
+
+    async fn handle_event_packet(
+        self: &Arc<Self>,
+        event: &str,
+        packet_data: Option<Bytes>,
+        ack_id: Option<u64>,
+    ) -> Result<()> {
+        if self.namespace.config.event_middlewares.is_empty() {
+            self.dispatch_event(event, packet_data, ack_id).await;
+            return Ok(());
+        }
+
+        // Every event goes through the middleware queue, even one no middleware
+        // matches, so events reach their handlers in arrival order; the queue is
+        // bounded, so a peer outpacing the stack holds up this read loop
+        let connection = self.clone();
+        let incoming_event = WsIoIncomingEvent::new(event.into(), packet_data, ack_id);
+        self.spawn_handler_task(&self.event_middleware_executor, async move {
+            connection.run_event_middlewares(incoming_event).await;
+            Ok(())
+        })
+        .await;
+
+        Ok(())
+    }
+
+    async fn handle_init_packet(self: &Arc<Self>, packet_key: Option<&str>, packet_data: Option<&[u8]>) -> Result<()> {
+        // Verify current state; only valid from AwaitingInit → Initiating
+        let state = self.state.get();
+        match state {
+            ConnectionState::AwaitingInit => self.state.try_transition(state, ConnectionState::Initiating)?,
+            _ => {
+                self.handle_protocol_violation(Error::msg(format!("Received init packet in invalid state: {state:?}")));
+
+                return Ok(());
+            },
+        }
+
+        // Abort init-timeout task
+        abort_locked_task(&self.init_timeout_task).await;
+
+        // Negotiate protocol with the client handshake, rejecting incompatible clients with a close reason
+        let negotiate_result = packet_key
+            .map(str::parse::<WsIoProtocolHandshake>)
+            .transpose()
+            .and_then(|handshake| self.local_handshake().negotiate(handshake.as_ref()));
+
+        match negotiate_result {
+            Ok(protocol) => {
+                let _ = self.protocol.set(protocol);
+            },
+            Err(err) => {
+                let reason = err.to_string();
+                self.report_error(WsIoError::new(WsIoErrorKind::ProtocolViolation(err)));
+                self.close_with_frame(Some(CloseFrame {
+                    code: CloseCode::Protocol,
+                    reason: truncate_close_reason(&reason).into(),
+                }));
+
+                return Ok(());
+            },
+        }
+
+        // Only clients that negotiated state recovery can present a session token
+        if self.recovered
+            && !self
+                .protocol_capabilities()
+                .contains(WsIoProtocolCapabilities::STATE_RECOVERY)
+        {
+            let err = Error::msg("Session recovery requested without negotiating state recovery");
+            let reason = err.to_string();
+            self.report_error(WsIoError::new(WsIoErrorKind::ProtocolViolation(err)));
+            self.close_with_frame(Some(CloseFrame {
+                code: CloseCode::Protocol,
+                reason: truncate_close_reason(&reason).into(),
+            }));
+
+            return Ok(());
+        }
+
+        // Run setup handlers, telling the client why it was rejected if one of them fails
+        if let Err(err) = self.activate(packet_data).await {
+            self.reject(err).await;
+            return Ok(());
         }
 
         // Transition state to Ready
         self.state
             .try_transition(ConnectionState::Activating, ConnectionState::Ready)?;
 
-        // Insert connection into namespace
-        self.namespace.insert_connection(self.clone());
+        match self.recovery_session.load_full() {
+            Some(recovery_session) => {
+                // Send ready packet, then replay missed messages before taking the place of the dropped connection
+                self.send_ready_packet().await?;
+                if !self.restore(&recovery_session).await? {
+                    self.close();
+                    return Ok(());
+                }
+            },
+            None => {
+                // Insert connection into namespace
+                self.namespace.insert_connection(self.clone());
 
-        // Send ready packet
-        self.send_packet(&WsIoPacket::new_ready()).await?;
+                // Send ready packet
+                self.send_ready_packet().await?;
+            },
+        }
 
         // Invoke on_ready_handler if configured
         if let Some(on_ready_handler) = self.namespace.config.on_ready_handler.clone() {
             // Run handler asynchronously in a detached task
-            self.spawn_task(on_ready_handler(self.clone()));
+            let connection = self.clone();
+            self.spawn_task(async move {
+                if let Err(kind) = catch_handler(|| on_ready_handler(connection.clone())).await {
+                    connection.report_error(WsIoError::new(kind).with_event("on_ready"));
+                }
+
+                Ok(())
+            });
         }
 
         Ok(())
     }
 
+    /// Answers a ping with the server time and records the latency the client
+    /// reported measuring with its previous one.
+    async fn handle_ping_packet(&self, ping_id: Option<u64>, packet_data: Option<&[u8]>) -> Result<()> {
+        let Some(ping_id) = ping_id else {
+            bail!("Ping packet missing ping id");
+        };
+
+        if let Some(packet_data) = packet_data {
+            let latency_micros = self.packet_codec.decode_data_as::<u64>(packet_data)?;
+            self.latency_micros
+                .store(latency_micros.min(UNKNOWN_LATENCY - 1), Ordering::Relaxed);
+        }
+
+        self.send_packet(&WsIoPacket::new_pong(
+            ping_id,
+            self.packet_codec.encode_data(&unix_time_micros())?,
+        ))
+        .await
+    }
+
+    /// Reports a packet that breaks the ws.io protocol and, under the
+    /// disconnect policy, closes the connection with a close frame naming it.
+    fn handle_protocol_violation(&self, err: Error) {
+        if matches!(self.state.get(), ConnectionState::Closed | ConnectionState::Closing) {
+            return;
+        }
+
+        let reason = err.to_string();
+        self.report_error(WsIoError::new(WsIoErrorKind::ProtocolViolation(err)));
+        if self.namespace.config.protocol_violation_policy == WsIoProtocolViolationPolicy::Disconnect {
+            self.close_with_frame(Some(CloseFrame {
+                code: CloseCode::Protocol,
+                reason: truncate_close_reason(&reason).into(),
+            }));
+        }
+    }
+
+    /// Runs the outgoing interceptors over an event message, decoding it back
+    /// into a packet only when any are registered.
+    fn intercept_event_message(&self, message: Arc<Message>) -> Result<Arc<Message>> {
+        let outgoing_interceptors = self.outgoing_interceptors.load();
+        if outgoing_interceptors.is_empty() {
+            return Ok(message);
+        }
+
+        let mut packet = match &*message {
+            Message::Binary(bytes) => self.packet_codec.decode(bytes)?,
+            Message::Text(text) => self.packet_codec.decode(text.as_bytes())?,
+            _ => return Ok(message),
+        };
+
+        for outgoing_interceptor in outgoing_interceptors.iter() {
+            outgoing_interceptor(self, &mut packet)?;
+        }
+
+        self.encode_packet_to_message(&packet)
+    }
+
+    /// Handshake advertised to the client, leaving out compression when the
+    /// namespace does not enable it.
+    #[inline]
+    fn local_handshake(&self) -> WsIoProtocolHandshake {
+        match self.namespace.config.compression_threshold {
+            Some(_) => WsIoProtocolHandshake::LOCAL,
+            None => WsIoProtocolHandshake::LOCAL.without_capabilities(WsIoProtocolCapabilities::COMPRESSION),
+        }
+    }
+
+    /// Tells the client why its connection was rejected, when it understands
+    /// error packets, and closes the connection.
+    async fn reject(&self, err: Error) {
+        let connect_error = err
+            .downcast::<WsIoConnectError>()
+            .unwrap_or_else(|_| WsIoConnectError::new(WsIoConnectError::INTERNAL, "Connection rejected"));
+
+        if self
+            .protocol_capabilities()
+            .contains(WsIoProtocolCapabilities::CONNECT_ERRORS)
+            && let Ok(data) = self.packet_codec.encode_data(&connect_error)
+        {
+            let _ = self.send_packet(&WsIoPacket::new_error(data)).await;
+        }
+
+        self.close_with_frame(Some(CloseFrame {
+            code: CloseCode::Policy,
+            reason: truncate_close_reason(connect_error.message()).into(),
+        }));
+    }
+
+    /// Replays the messages buffered for `recovery_session`, then inserts the
+    /// connection into the namespace in place of the one it recovers, rejoining
+    /// its rooms.
+    ///
+    /// Returns `false` when the session expired before it could be restored.
+    async fn restore(self: &Arc<Self>, recovery_session: &Arc<WsIoServerRecoverySession>) -> Result<bool> {
+        loop {
+            // Restoring under the session lock lets no broadcast slip between the replay and the live messages
+            let drain = recovery_session.drain(|| {
+                self.namespace.remove_recovery_session(recovery_session);
+                self.namespace.insert_connection(self.clone());
+                self.join(recovery_session.rooms().iter().cloned());
+            });
+
+            match drain {
+                RecoveryDrain::Expired => return Ok(false),
+                RecoveryDrain::Messages(messages) => {
+                    for message in messages {
+                        self.emit_event_message(message).await?;
+                    }
+                },
+                RecoveryDrain::Restored => {
+                    self.recovery_session.store(None);
+                    return Ok(true);
+                },
+            }
+        }
+    }
+
+    /// Passes `incoming_event` through the event middleware stack and
+    /// dispatches the event it lets through, reporting a rejection and failing
+    /// the ack the client waits on.
+    ///
+    /// Only the middleware calls run under `event_middleware_timeout`; the
+    /// handlers are bounded by their own timeout.
+    async fn run_event_middlewares(self: &Arc<Self>, incoming_event: WsIoIncomingEvent) {
+        let duration = self.namespace.config.event_middleware_timeout;
+        let ack_id = incoming_event.ack_id();
+        let event = incoming_event.name().to_owned();
+        let passed_event = Arc::new(SyncMutex::new(None));
+        let next = WsIoEventNext::new(self.clone(), passed_event.clone());
+        let (kind, error_code) = match timeout(duration, catch_handler(|| next.run(incoming_event))).await {
+            Ok(Ok(())) => {
+                let passed_event = passed_event.lock().take();
+                if let Some(event) = passed_event {
+                    self.dispatch_event(event.name(), event.data().cloned(), event.ack_id())
+                        .await;
+                }
+
+                return;
+            },
+            Ok(Err(WsIoErrorKind::Handler(err))) => (WsIoErrorKind::Middleware(err), WsIoConnectError::INTERNAL),
+            Ok(Err(kind)) => (kind, WsIoConnectError::INTERNAL),
+            Err(_) => (WsIoErrorKind::Timeout(duration), WsIoConnectError::TIMEOUT),
+        };
+
+        self.report_error(WsIoError::new(kind).with_event(&event));
+        if let Some(ack_id) = ack_id {
+            let _ = self.send_ack_error(ack_id, error_code).await;
+        }
+    }
+
+    /// Runs a lifecycle handler within `duration`, reporting its error, panic
+    /// or timeout under the handler `name` before failing.
+    ///
+    /// A [`WsIoConnectError`] returned by the handler is passed through and a
+    /// timeout becomes one, so `reject` can forward them to the client.
+    async fn run_lifecycle_handler<T, F, Fut>(&self, name: &'static str, duration: Duration, handler: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let kind = match timeout(duration, catch_handler(handler)).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(kind)) => kind,
+            Err(_) => WsIoErrorKind::Timeout(duration),
+        };
+
+        let connect_error = match &kind {
+            WsIoErrorKind::Handler(err) => err.downcast_ref::<WsIoConnectError>().cloned(),
+            WsIoErrorKind::Timeout(_) => Some(WsIoConnectError::new(
+                WsIoConnectError::TIMEOUT,
+                format!("{name} timed out"),
+            )),
+            _ => None,
+        };
+
+        let message = kind.to_string();
+        self.report_error(WsIoError::new(kind).with_event(name));
+        match connect_error {
+            Some(connect_error) => Err(connect_error.into()),
+            None => bail!("{name} {message}"),
+        }
+    }
+
     async fn send_packet(&self, packet: &WsIoPacket) -> Result<()> {
-        self.send_message(self.namespace.encode_packet_to_message(packet)?)
-            .await
+        self.send_message(self.encode_packet_to_message(packet)?).await
+    }
+
+    /// Sends the ready packet, with the session token the client presents to
+    /// recover the connection when state recovery is enabled and negotiated.
+    async fn send_ready_packet(&self) -> Result<()> {
+        match &self.recovery_secret {
+            Some(recovery_secret)
+                if self
+                    .protocol_capabilities()
+                    .contains(WsIoProtocolCapabilities::STATE_RECOVERY) =>
+            {
+                self.send_packet(&WsIoPacket::new_ready_with_recovery(&WsIoSessionRecovery {
+                    recovered: self.recovered,
+                    token: format!("{}.{recovery_secret}", self.id),
+                }))
+                .await
+            },
+            _ => self.send_packet(&WsIoPacket::new_ready()).await,
+        }
     }
 
     // Protected methods
     pub(crate) async fn cleanup(self: &Arc<Self>) {
+        // A ready connection that dropped without being closed may be recovered by its client
+        let is_recoverable = self.state.is(ConnectionState::Ready)
+            && self
+                .protocol_capabilities()
+                .contains(WsIoProtocolCapabilities::STATE_RECOVERY);
+
         // Set connection state to Closing
         self.state.store(ConnectionState::Closing);
 
-        // Remove connection from namespace
-        self.namespace.remove_connection(self.id);
+        // Let the client retry a session this connection claimed but did not restore, keeping its rooms
+        let unrestored_session = self.recovery_session.swap(None);
+        if let Some(recovery_session) = &unrestored_session {
+            recovery_session.release();
+        }
+
+        // Stop joining rooms, so a concurrent broadcast operator join either lands in these rooms or is undone
+        let joined_rooms = self
+            .joined_rooms
+            .lock()
+            .take()
+            .map_or_else(Vec::new, |joined_rooms| joined_rooms.into_iter().collect());
+
+        // Remove connection from namespace, which only holds ready connections, or keep it for recovery
+        let is_detached = match &self.recovery_secret {
+            Some(recovery_secret) if is_recoverable => self.namespace.detach_connection(
+                self.id,
+                self.packet_codec.clone(),
+                joined_rooms.clone(),
+                recovery_secret.clone(),
+            ),
+            _ => false,
+        };
+
+        let was_ready = is_detached || self.namespace.remove_connection(self.id);
 
         // Leave all joined rooms
-        let joined_rooms = self.joined_rooms.iter().map(|entry| entry.clone()).collect::<Vec<_>>();
-        for room_name in &joined_rooms {
-            self.namespace.remove_connection_id_from_room(room_name, self.id);
+        if !is_detached {
+            for room_name in &joined_rooms {
+                let is_kept = unrestored_session
+                    .as_ref()
+                    .is_some_and(|recovery_session| recovery_session.rooms().contains(room_name));
+
+                if !is_kept {
+                    self.namespace.remove_connection_id_from_room(room_name, self.id);
+                }
+            }
         }
 
-        self.joined_rooms.clear();
-
         // Abort init-timeout task
         abort_locked_task(&self.init_timeout_task).await;
 
         // Cancel all ongoing operations via cancel token
         self.cancel_token.load().cancel();
 
+        // Fail all pending acks
+        self.ack_registry.clear();
+
         // Invoke on_close_handler with timeout protection if configured
         if let Some(on_close_handler) = self.on_close_handler.lock().await.take() {
-            let _ = timeout(
-                self.namespace.config.on_close_handler_timeout,
-                on_close_handler(self.clone()),
-            )
-            .await;
+            let _ = self
+                .run_lifecycle_handler("on_close", self.namespace.config.on_close_handler_timeout, || {
+                    on_close_handler(self.clone())
+                })
+                .await;
+        }
+
+        // Invoke on_disconnect_handler with timeout protection if configured and the connection was ready
+        if was_ready && let Some(on_disconnect_handler) = &self.namespace.config.on_disconnect_handler {
+            let _ = self
+                .run_lifecycle_handler(
+                    "on_disconnect",
+                    self.namespace.config.on_disconnect_handler_timeout,
+                    || on_disconnect_handler(self.clone()),
+                )
+                .await;
         }
 
         // Set connection state to Closed
@@ -311,6 +820,10 @@ impl WsIoServerConnection {
 
     #[inline]
     pub(crate) fn close(&self) {
+        self.close_with_frame(None);
+    }
+
+    pub(crate) fn close_with_frame(&self, close_frame: Option<CloseFrame>) {
         // Skip if connection is already Closing or Closed, otherwise set connection state to Closing
         match self.state.get() {
             ConnectionState::Closed | ConnectionState::Closing => return,
@@ -318,7 +831,24 @@ impl WsIoServerConnection {
         }
 
         // Send websocket close frame to initiate graceful shutdown
-        let _ = self.message_tx.try_send(Arc::new(Message::Close(None)));
+        let _ = self.message_tx.try_send(Arc::new(Message::Close(close_frame)));
+    }
+
+    /// Prepares a message for the socket, deflating packets above the threshold
+    /// once compression has been negotiated with the client.
+    #[inline]
+    pub(crate) fn compress_outgoing_message(&self, message: Message) -> Message {
+        match self.compression_threshold() {
+            Some(threshold) => compress_packet_message(message, threshold),
+            None => message,
+        }
+    }
+
+    #[inline]
+    pub(crate) async fn dispatch_event(self: &Arc<Self>, event: &str, packet_data: Option<Bytes>, ack_id: Option<u64>) {
+        self.event_registry
+            .dispatch_event_packet(self.clone(), event, &self.packet_codec, packet_data, ack_id, self)
+            .await;
     }
 
     pub(crate) async fn emit_event_message(&self, message: Arc<Message>) -> Result<()> {
@@ -326,25 +856,98 @@ impl WsIoServerConnection {
             format!("Cannot emit in invalid state: {state:?}")
         })?;
 
-        self.send_message(message).await
+        self.send_message(self.intercept_event_message(message)?).await
+    }
+
+    pub(crate) async fn emit_event_with_ack<R: DeserializeOwned>(
+        &self,
+        event: &str,
+        packet_data: Option<Vec<u8>>,
+        ack_timeout: Duration,
+    ) -> Result<R> {
+        let (ack_id, ack_rx) = self.send_event_with_ack(event, packet_data).await?;
+        self.wait_for_ack(ack_id, ack_rx, ack_timeout).await
+    }
+
+    /// Records rooms the connection joined through a broadcast operator, which
+    /// already added it to the room bitmaps.
+    ///
+    /// Returns `false` once the connection was cleaned up, leaving the caller to
+    /// take it back out of the room bitmaps.
+    #[inline]
+    pub(crate) fn extend_joined_rooms(&self, room_names: &[String]) -> bool {
+        let mut joined_rooms = self.joined_rooms.lock();
+        let Some(joined_rooms) = joined_rooms.as_mut() else {
+            return false;
+        };
+
+        joined_rooms.extend(room_names.iter().cloned());
+        true
+    }
+
+    /// Handles a binary frame, unwrapping it first when compression has been
+    /// negotiated with the client.
+    pub(crate) async fn handle_incoming_binary_packet(self: &Arc<Self>, frame: Bytes) -> Result<()> {
+        if self.compression_threshold().is_none() {
+            return self.handle_incoming_packet(frame).await;
+        }
+
+        let max_size = self.namespace.config.websocket_config.max_message_size;
+        match decompress_packet_frame(frame, max_size) {
+            Ok(encoded_packet) => self.handle_incoming_packet(encoded_packet).await,
+            Err(err) => {
+                self.handle_protocol_violation(err.context("Failed to decompress packet"));
+                Ok(())
+            },
+        }
     }
 
-    pub(crate) async fn handle_incoming_packet(self: &Arc<Self>, encoded_packet: &[u8]) -> Result<()> {
-        // TODO: lazy load
-        let packet = self.namespace.config.packet_codec.decode(encoded_packet)?;
+    pub(crate) async fn handle_incoming_packet(self: &Arc<Self>, encoded_packet: Bytes) -> Result<()> {
+        // Key and data stay borrowed from the frame until an event handler needs them
+        let mut packet = match self.packet_codec.decode_view(&encoded_packet) {
+            Ok(packet) => packet,
+            Err(err) => {
+                self.handle_protocol_violation(err.context("Failed to decode packet"));
+                return Ok(());
+            },
+        };
+
         match packet.r#type {
+            WsIoPacketType::Ack => {
+                if self.is_ready()
+                    && let Err(err) =
+                        self.handle_ack_packet(packet.ack_id, packet.key.as_deref(), packet.data.as_deref())
+                {
+                    self.handle_protocol_violation(err);
+                }
+
+                Ok(())
+            },
             WsIoPacketType::Event => {
                 if self.is_ready() {
+                    let packet_data = packet.take_data_bytes(&encoded_packet);
                     if let Some(event) = packet.key.as_deref() {
-                        return self.handle_event_packet(event, packet.data);
+                        return self.handle_event_packet(event, packet_data, packet.ack_id).await;
                     } else {
-                        bail!("Event packet missing key");
+                        self.handle_protocol_violation(Error::msg("Event packet missing key"));
                     }
                 }
 
                 Ok(())
             },
-            WsIoPacketType::Init => self.handle_init_packet(packet.data.as_deref()).await,
+            WsIoPacketType::Init => {
+                self.handle_init_packet(packet.key.as_deref(), packet.data.as_deref())
+                    .await
+            },
+            WsIoPacketType::Ping => {
+                if self.is_ready()
+                    && let Err(err) = self.handle_ping_packet(packet.ack_id, packet.data.as_deref()).await
+                {
+                    self.handle_protocol_violation(err);
+                }
+
+                Ok(())
+            },
             _ => Ok(()),
         }
     }
@@ -357,11 +960,12 @@ impl WsIoServerConnection {
 
         // Generate init request data if init request handler is configured
         let init_request_data = if let Some(init_request_handler) = &self.namespace.config.init_request_handler {
-            timeout(
+            self.run_lifecycle_handler(
+                "init_request",
                 self.namespace.config.init_request_handler_timeout,
-                init_request_handler(self.clone(), &self.namespace.config.packet_codec),
+                || init_request_handler(self.clone(), &*self.packet_codec),
             )
-            .await??
+            .await?
         } else {
             None
         };
@@ -380,13 +984,69 @@ impl WsIoServerConnection {
         }));
 
         // Send init packet
-        self.send_packet(&WsIoPacket::new_init(init_request_data)).await
+        self.send_packet(&WsIoPacket::new_init_with_handshake(
+            init_request_data,
+            &self.local_handshake(),
+        ))
+        .await
+    }
+
+    /// Forgets rooms the connection left through a broadcast operator, which
+    /// already removed it from the room bitmaps.
+    #[inline]
+    pub(crate) fn remove_joined_rooms(&self, room_names: &[String]) {
+        if let Some(joined_rooms) = self.joined_rooms.lock().as_mut() {
+            for room_name in room_names {
+                joined_rooms.remove(room_name);
+            }
+        }
+    }
+
+    /// Sends an event expecting an ack, returning the ack id and the receiver
+    /// its reply is delivered to, so callers can send to many connections
+    /// before waiting on any.
+    pub(crate) async fn send_event_with_ack(
+        &self,
+        event: &str,
+        packet_data: Option<Vec<u8>>,
+    ) -> Result<(u64, OneshotReceiver<WsIoEventAckReply>)> {
+        if !self.protocol_capabilities().contains(WsIoProtocolCapabilities::ACKS) {
+            bail!("Connection {} does not support acks", self.id);
+        }
+
+        let (ack_id, ack_rx) = self.ack_registry.register();
+        let emit_result =
+            match self.encode_packet_to_message(&WsIoPacket::new_event_with_ack(event, packet_data, ack_id)) {
+                Ok(message) => self.emit_event_message(message).await,
+                Err(err) => Err(err),
+            };
+
+        if let Err(err) = emit_result {
+            self.ack_registry.remove(ack_id);
+            return Err(err);
+        }
+
+        Ok((ack_id, ack_rx))
     }
 
     pub(crate) async fn send_message(&self, message: Arc<Message>) -> Result<()> {
         Ok(self.message_tx.send(message).await?)
     }
 
+    /// Waits up to `ack_timeout` for the reply to an event sent with
+    /// [`send_event_with_ack`](Self::send_event_with_ack) and decodes it as `R`.
+    #[inline]
+    pub(crate) async fn wait_for_ack<R: DeserializeOwned>(
+        &self,
+        ack_id: u64,
+        ack_rx: OneshotReceiver<WsIoEventAckReply>,
+        ack_timeout: Duration,
+    ) -> Result<R> {
+        self.ack_registry
+            .wait(ack_id, ack_rx, ack_timeout, &*self.packet_codec)
+            .await
+    }
+
     // Public methods
     pub async fn disconnect(&self) {
         let _ = self.send_packet(&WsIoPacket::new_disconnect()).await;
@@ -395,11 +1055,31 @@ impl WsIoServerConnection {
 
     pub async fn emit<D: Serialize>(&self, event: impl AsRef<str>, data: Option<&D>) -> Result<()> {
         self.emit_event_message(
-            self.namespace.encode_packet_to_message(&WsIoPacket::new_event(
-                event.as_ref(),
-                data.map(|data| self.namespace.config.packet_codec.encode_data(data))
-                    .transpose()?,
-            ))?,
+            self.encode_packet_to_message(&WsIoPacket::new_event(event.as_ref(), self.encode_event_data(data)?))?,
+        )
+        .await
+    }
+
+    /// Emits `E` with a payload whose type is checked against its definition.
+    #[inline]
+    pub async fn emit_typed<E: WsIoEvent>(&self, data: &E::Data) -> Result<()> {
+        self.emit(E::NAME, Some(data)).await
+    }
+
+    /// Emits an event and waits for the client to acknowledge it.
+    ///
+    /// The client's ack handler reply is decoded as `R`. Fails when the ack
+    /// handler fails, the namespace `ack_timeout` elapses or the connection
+    /// closes first.
+    pub async fn emit_with_ack<D: Serialize, R: DeserializeOwned>(
+        &self,
+        event: impl AsRef<str>,
+        data: Option<&D>,
+    ) -> Result<R> {
+        self.emit_event_with_ack(
+            event.as_ref(),
+            self.encode_event_data(data)?,
+            self.namespace.config.ack_timeout,
         )
         .await
     }
@@ -428,27 +1108,80 @@ impl WsIoServerConnection {
         self.id
     }
 
+    /// Adds an interceptor that sees every event packet sent to this client,
+    /// broadcasts included, right before it is queued.
+    ///
+    /// Interceptors run in the order they were added, each seeing the packet
+    /// the previous one left, and may rewrite it, for example to redact fields
+    /// of its payload. Returning an error drops the packet and fails the emit.
+    /// Broadcast packets are shared between connections, so they are decoded
+    /// and re-encoded for connections that have interceptors.
+    pub fn intercept_outgoing<F>(&self, interceptor: F)
+    where
+        F: Fn(&WsIoServerConnection, &mut WsIoPacket) -> Result<()> + Send + Sync + 'static,
+    {
+        let interceptor: OutgoingInterceptor = Arc::new(interceptor);
+        self.outgoing_interceptors.rcu(|outgoing_interceptors| {
+            let mut outgoing_interceptors = (**outgoing_interceptors).clone();
+            outgoing_interceptors.push(interceptor.clone());
+            outgoing_interceptors
+        });
+    }
+
     #[inline]
     pub fn is_ready(&self) -> bool {
         self.state.is(ConnectionState::Ready)
     }
 
+    /// Whether this connection recovers the session of a connection that
+    /// dropped, taking over its id and rooms and receiving the broadcasts it
+    /// missed once ready.
+    #[inline]
+    pub fn is_recovered(&self) -> bool {
+        self.recovered
+    }
+
+    /// Joins `room_names`; does nothing once the connection was cleaned up.
     #[inline]
     pub fn join(self: &Arc<Self>, room_names: impl IntoIterator<Item = impl Into<String>>) {
+        let mut joined_rooms = self.joined_rooms.lock();
+        let Some(joined_rooms) = joined_rooms.as_mut() else {
+            return;
+        };
+
         for room_name in room_names {
             let room_name = room_name.into();
             self.namespace.add_connection_id_to_room(&room_name, self.id);
-            self.joined_rooms.insert(room_name);
+            joined_rooms.insert(room_name);
+        }
+    }
+
+    /// Round-trip time the client measured with its last answered ping, or
+    /// `None` until it reports one or when it cannot send pings.
+    ///
+    /// The value is reported by the client and not verified by the server, so
+    /// a client can claim any latency; do not rely on it for anything security
+    /// or fairness related.
+    #[inline]
+    pub fn latency(&self) -> Option<Duration> {
+        match self.latency_micros.load(Ordering::Relaxed) {
+            UNKNOWN_LATENCY => None,
+            latency_micros => Some(Duration::from_micros(latency_micros)),
         }
     }
 
     #[inline]
     pub fn leave(self: &Arc<Self>, room_names: impl IntoIterator<Item = impl Into<String>>) {
+        let mut joined_rooms = self.joined_rooms.lock();
+        let Some(joined_rooms) = joined_rooms.as_mut() else {
+            return;
+        };
+
         for room_name in room_names {
             let room_name = &room_name.into();
             self.namespace.remove_connection_id_from_room(room_name, self.id);
 
-            self.joined_rooms.remove(room_name);
+            joined_rooms.remove(room_name);
         }
     }
 
@@ -457,6 +1190,13 @@ impl WsIoServerConnection {
         self.namespace.clone()
     }
 
+    /// Value of the parameter `name` captured from the namespace path when
+    /// the connection joined the child of a dynamic namespace.
+    #[inline]
+    pub fn namespace_param(&self, name: &str) -> Option<&str> {
+        self.namespace.param(name)
+    }
+
     #[inline]
     pub fn off(&self, event: impl AsRef<str>) {
         self.event_registry.off(event.as_ref());
@@ -467,6 +1207,13 @@ impl WsIoServerConnection {
         self.event_registry.off_by_handler_id(event.as_ref(), handler_id);
     }
 
+    /// Removes every pattern handler registered with exactly `pattern`,
+    /// including `on_any` handlers when `pattern` is `*`.
+    #[inline]
+    pub fn off_pattern(&self, pattern: impl AsRef<str>) {
+        self.event_registry.off_pattern(pattern.as_ref());
+    }
+
     #[inline]
     pub fn on<H, Fut, D>(&self, event: impl AsRef<str>, handler: H) -> u32
     where
@@ -477,6 +1224,98 @@ impl WsIoServerConnection {
         self.event_registry.on(event.as_ref(), handler)
     }
 
+    /// Registers a handler that receives every event with its raw payload.
+    ///
+    /// Same as `on_pattern("*", handler)`.
+    #[inline]
+    pub fn on_any<H, Fut>(&self, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.event_registry.on_pattern("*", handler)
+    }
+
+    /// Registers a handler whose arguments are extracted from the event, in
+    /// any combination and order.
+    ///
+    /// Arguments implement
+    /// [`FromEventContext`](crate::core::traits::event::extract::FromEventContext):
+    /// the connection as `Arc<WsIoServerConnection>`, the payload as
+    /// [`WsIoData`](crate::core::event::extract::WsIoData), the event name as
+    /// [`WsIoEventName`](crate::core::event::extract::WsIoEventName), an ack
+    /// responder as [`WsIoAck`](crate::core::event::extract::WsIoAck), shared
+    /// state as [`WsIoState`](crate::extract::WsIoState) and, with the
+    /// `connection-extensions` feature, a connection extension as
+    /// [`WsIoExtension`](crate::extract::WsIoExtension). A failed extraction
+    /// skips the handler and is reported to the `on_error` hook.
+    #[inline]
+    pub fn on_extract<H, T>(&self, event: impl AsRef<str>, handler: H) -> u32
+    where
+        H: EventHandler<WsIoServerConnection, T>,
+    {
+        self.event_registry.on_extract(event.as_ref(), handler)
+    }
+
+    /// Registers a handler for every event whose name matches `pattern`, such
+    /// as `chat:*`.
+    ///
+    /// `*` matches any run of characters. The handler receives the event name and
+    /// its raw payload, runs alongside handlers registered with `on` and never
+    /// replies to acks.
+    #[inline]
+    pub fn on_pattern<H, Fut>(&self, pattern: impl AsRef<str>, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.event_registry.on_pattern(pattern.as_ref(), handler)
+    }
+
+    /// Registers a handler that receives `event` with its payload undecoded,
+    /// together with the packet codec it was encoded with.
+    ///
+    /// Unlike `on`, raw handlers can share an event name with handlers of any
+    /// payload type. Decode the payload into
+    /// [`WsIoValue`](crate::core::value::WsIoValue) to inspect it without a
+    /// concrete type. Raw handlers never reply to acks.
+    #[inline]
+    pub fn on_raw<H, Fut>(&self, event: impl AsRef<str>, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.event_registry.on_raw(event.as_ref(), handler)
+    }
+
+    /// Registers a handler for `E`, decoding its payload into the type of its
+    /// definition.
+    #[inline]
+    pub fn on_typed<E, H, Fut>(&self, handler: H) -> u32
+    where
+        E: WsIoEvent,
+        H: Fn(Arc<WsIoServerConnection>, Arc<E::Data>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.event_registry.on(E::NAME, handler)
+    }
+
+    /// Registers an event handler whose return value is sent back to the client
+    /// when the event was emitted with an ack.
+    ///
+    /// A handler that fails answers the ack with an error instead. Each event
+    /// takes a single ack handler; registering a second one panics.
+    #[inline]
+    pub fn on_with_ack<H, Fut, D, R>(&self, event: impl AsRef<str>, handler: H) -> u32
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+        D: DeserializeOwned + Send + Sync + 'static,
+        R: Serialize + Send + 'static,
+    {
+        self.event_registry.on_with_ack(event.as_ref(), handler)
+    }
+
     pub async fn on_close<H, Fut>(&self, handler: H)
     where
         H: Fn(Arc<WsIoServerConnection>) -> Fut + Send + Sync + 'static,
@@ -485,16 +1324,67 @@ impl WsIoServerConnection {
         *self.on_close_handler.lock().await = Some(Box::new(move |connection| Box::pin(handler(connection))));
     }
 
+    /// Packet codec selected by the client for this connection.
+    #[inline]
+    pub fn packet_codec(&self) -> &dyn PacketCodec {
+        &*self.packet_codec
+    }
+
+    /// Capabilities negotiated with the client, empty until the init handshake
+    /// has completed.
+    #[inline]
+    pub fn protocol_capabilities(&self) -> WsIoProtocolCapabilities {
+        self.protocol
+            .get()
+            .map_or(WsIoProtocolCapabilities::empty(), |protocol| protocol.capabilities)
+    }
+
+    /// Protocol version negotiated with the client, or `None` until the init
+    /// handshake has completed.
+    #[inline]
+    pub fn protocol_version(&self) -> Option<u16> {
+        self.protocol.get().map(|protocol| protocol.version)
+    }
+
     #[inline]
     pub fn request_uri(&self) -> &Uri {
         &self.request_uri
     }
 
+    /// Names of the rooms this connection joined, in no particular order.
+    #[inline]
+    pub fn rooms(&self) -> Vec<String> {
+        self.joined_rooms
+            .lock()
+            .as_ref()
+            .map_or_else(Vec::new, |joined_rooms| joined_rooms.iter().cloned().collect())
+    }
+
     #[inline]
     pub fn server(&self) -> WsIoServer {
         self.namespace.server()
     }
 
+    /// Runs the handlers of `event` in `mode` instead of the mode the namespace
+    /// sets for it or its handler execution mode.
+    #[inline]
+    pub fn set_execution_mode(&self, event: impl AsRef<str>, mode: WsIoHandlerExecutionMode) {
+        self.event_registry.set_execution_mode(event.as_ref(), mode);
+    }
+
+    /// Cuts off the handlers of `event` on this connection after `duration`
+    /// instead of the timeout the namespace sets for it or its default.
+    #[inline]
+    pub fn set_handler_timeout(&self, event: impl AsRef<str>, duration: Duration) {
+        self.event_registry.set_handler_timeout(event.as_ref(), duration);
+    }
+
+    /// State of type `S` added on the namespace or server builder.
+    #[inline]
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        self.namespace.state()
+    }
+
     #[inline]
     pub fn to(
         self: &Arc<Self>,
@@ -505,6 +1395,8 @@ impl WsIoServerConnection {
 }
 
 // Constants/Statics
+const UNKNOWN_LATENCY: u64 = u64::MAX;
+
 static NEXT_CONNECTION_ID: LazyLock<AtomicU64> = LazyLock::new(|| AtomicU64::new(0));
 
 #[cfg(test)]
@@ -516,46 +1408,101 @@ mod tests {
 
     use super::*;
 
+    type ReportedErrors = Arc<SyncMutex<Vec<String>>>;
+
     fn create_test_connection() -> Arc<WsIoServerConnection> {
+        create_test_connection_with_rx().0
+    }
+
+    fn create_test_connection_with_rx() -> (Arc<WsIoServerConnection>, Receiver<Arc<Message>>) {
+        let (connection, rx, _) = create_test_connection_with_policy(WsIoProtocolViolationPolicy::Disconnect);
+        (connection, rx)
+    }
+
+    fn create_test_connection_with_policy(
+        policy: WsIoProtocolViolationPolicy,
+    ) -> (Arc<WsIoServerConnection>, Receiver<Arc<Message>>, ReportedErrors) {
+        let reported_errors = ReportedErrors::default();
+        let reported_errors_clone = reported_errors.clone();
         let server = Arc::new(WsIoServer::builder().build());
-        let namespace = server.new_namespace_builder("/socket").register().unwrap();
-        let (connection, _rx) =
-            WsIoServerConnection::new(HeaderMap::new(), namespace, Uri::from_static("http://localhost"));
+        let namespace = server
+            .new_namespace_builder("/socket")
+            .on_error(move |error| reported_errors_clone.lock().push(error.to_string()))
+            .protocol_violation_policy(policy)
+            .register()
+            .unwrap();
+
+        let packet_codec = namespace.packet_codec(None).unwrap().clone();
+        let (connection, rx) = WsIoServerConnection::new(
+            HeaderMap::new(),
+            namespace,
+            packet_codec,
+            None,
+            Uri::from_static("http://localhost"),
+        );
 
-        connection
+        (connection, rx, reported_errors)
+    }
+
+    fn expect_protocol_close_frame(rx: &mut Receiver<Arc<Message>>) -> String {
+        let message = rx.try_recv().unwrap();
+        let Message::Close(Some(close_frame)) = &*message else {
+            panic!("Expected a close frame, got {message:?}");
+        };
+
+        assert_eq!(close_frame.code, CloseCode::Protocol);
+        close_frame.reason.to_string()
     }
 
     #[tokio::test]
     async fn test_handle_incoming_packet_decode_error() {
-        let connection = create_test_connection();
+        let (connection, mut rx, reported_errors) =
+            create_test_connection_with_policy(WsIoProtocolViolationPolicy::Disconnect);
+
         let garbage_data = b"obviously not valid json or messagepack";
-        // Should seamlessly return a Result::Err, not panic
-        let result = connection.handle_incoming_packet(garbage_data).await;
-        assert!(result.is_err(), "Decoding garbage payload should trigger an error");
+        // Should report the violation and close with a reason, not panic
+        connection
+            .handle_incoming_packet(Bytes::from_static(garbage_data))
+            .await
+            .unwrap();
+
+        assert_eq!(connection.state.get(), ConnectionState::Closing);
+        assert!(expect_protocol_close_frame(&mut rx).starts_with("Failed to decode packet"));
+
+        let reported_errors = reported_errors.lock();
+        assert_eq!(reported_errors.len(), 1);
+        assert!(reported_errors[0].starts_with(&format!(
+            "connection {}: protocol violation: Failed to decode packet",
+            connection.id()
+        )));
     }
 
     #[tokio::test]
     async fn test_handle_init_packet_in_invalid_state() {
-        let connection = create_test_connection();
+        let (connection, mut rx, reported_errors) =
+            create_test_connection_with_policy(WsIoProtocolViolationPolicy::Disconnect);
+
         assert_eq!(connection.state.get(), ConnectionState::Created);
 
-        // Sending an init packet when the connection is merely `Created` (not yet `AwaitingInit`) should throw an error
+        // Sending an init packet when the connection is merely `Created` (not yet `AwaitingInit`) is a violation
         // Init packet JSON encoded (type: 2 = Init) -> serialized as tuple array
         let encoded = b"[2,null,null]";
 
         // This simulates a manual client Init push before server starts the handshake buffer
-        let result = connection.handle_incoming_packet(encoded).await;
-        assert!(
-            result.is_err(),
-            "Should error because state is Created, not AwaitingInit"
-        );
+        connection
+            .handle_incoming_packet(Bytes::from_static(encoded))
+            .await
+            .unwrap();
 
-        assert!(result.unwrap_err().to_string().contains("invalid state"));
+        assert_eq!(connection.state.get(), ConnectionState::Closing);
+        assert!(expect_protocol_close_frame(&mut rx).contains("invalid state"));
+        assert!(reported_errors.lock()[0].contains("invalid state"));
     }
 
     #[tokio::test]
     async fn test_handle_event_packet_missing_key() {
-        let connection = create_test_connection();
+        let (connection, mut rx, reported_errors) =
+            create_test_connection_with_policy(WsIoProtocolViolationPolicy::Disconnect);
 
         // Force the connection into the Ready state so it accepts Event packets
         connection.state.store(ConnectionState::Ready);
@@ -563,9 +1510,74 @@ mod tests {
         // Manufacture an Event packet manually without a key (type: 1 = Event) -> serialized as tuple array
         let encoded = b"[1,null,null]";
 
-        let result = connection.handle_incoming_packet(encoded).await;
-        assert!(result.is_err(), "Should bail on missing event key");
-        assert_eq!(result.unwrap_err().to_string(), "Event packet missing key");
+        connection
+            .handle_incoming_packet(Bytes::from_static(encoded))
+            .await
+            .unwrap();
+
+        assert_eq!(expect_protocol_close_frame(&mut rx), "Event packet missing key");
+        assert_eq!(
+            *reported_errors.lock(),
+            [format!(
+                "connection {}: protocol violation: Event packet missing key",
+                connection.id()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_protocol_violation_report_policy_keeps_connection_open() {
+        let (connection, mut rx, reported_errors) =
+            create_test_connection_with_policy(WsIoProtocolViolationPolicy::Report);
+
+        connection.state.store(ConnectionState::Ready);
+        connection
+            .handle_incoming_packet(Bytes::from_static(b"[1,null,null]"))
+            .await
+            .unwrap();
+
+        connection
+            .handle_incoming_packet(Bytes::from_static(b"[4,null,null]"))
+            .await
+            .unwrap();
+
+        assert_eq!(connection.state.get(), ConnectionState::Ready);
+        assert!(rx.try_recv().is_err());
+        assert_eq!(reported_errors.lock().len(), 2);
+        assert!(reported_errors.lock()[1].ends_with("Ack packet missing ack id"));
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_handler_failures_are_reported() {
+        let (connection, _rx, reported_errors) =
+            create_test_connection_with_policy(WsIoProtocolViolationPolicy::Disconnect);
+
+        let result = connection
+            .run_lifecycle_handler("on_connect", Duration::from_secs(1), || async {
+                bail!("rejected") as Result<()>
+            })
+            .await;
+
+        assert!(result.is_err());
+
+        let result = connection
+            .run_lifecycle_handler("middleware", Duration::from_millis(10), || async {
+                sleep(Duration::from_secs(1)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            *reported_errors.lock(),
+            [
+                format!("[on_connect] connection {}: handler failed: rejected", connection.id()),
+                format!(
+                    "[middleware] connection {}: handler timed out after 10ms",
+                    connection.id()
+                ),
+            ]
+        );
     }
 
     #[tokio::test]
@@ -591,12 +1603,91 @@ mod tests {
         assert_eq!(namespace.connection_count(), 1);
 
         connection.join(["room_a", "room_b"]);
-        assert!(connection.joined_rooms.contains("room_a"));
+        assert!(connection.rooms().contains(&"room_a".to_owned()));
 
         connection.cleanup().await;
 
         assert_eq!(connection.state.get(), ConnectionState::Closed);
-        assert!(connection.joined_rooms.is_empty());
+        assert!(connection.rooms().is_empty());
         assert_eq!(namespace.connection_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_broadcast_join_skips_cleaned_up_connection() {
+        let connection = create_test_connection();
+        let namespace = connection.namespace();
+        namespace.insert_connection(connection.clone());
+        connection.join(["room_a"]);
+
+        // Cleanup took the joined rooms while the connection is still listed as ready
+        connection.joined_rooms.lock().take();
+        namespace.to(["room_a"]).join(["room_a", "room_b"]);
+
+        assert_eq!(namespace.room_size("room_a"), 1);
+        assert_eq!(namespace.room_size("room_b"), 0);
+        assert_eq!(namespace.rooms(), ["room_a"]);
+    }
+
+    #[tokio::test]
+    async fn test_handle_init_packet_negotiates_protocol() {
+        let (connection, mut rx) = create_test_connection_with_rx();
+        connection.state.store(ConnectionState::AwaitingInit);
+
+        let encoded = format!(r#"[2,"{}",null]"#, WsIoProtocolHandshake::LOCAL);
+        connection.handle_incoming_packet(encoded.into()).await.unwrap();
+
+        assert_eq!(connection.state.get(), ConnectionState::Ready);
+        assert_eq!(
+            connection.protocol_version(),
+            Some(WsIoProtocolHandshake::LOCAL.version)
+        );
+        assert!(
+            connection
+                .protocol_capabilities()
+                .contains(WsIoProtocolCapabilities::ACKS)
+        );
+
+        // Ready packet
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_handle_init_packet_without_handshake_uses_legacy_protocol() {
+        let (connection, _rx) = create_test_connection_with_rx();
+        connection.state.store(ConnectionState::AwaitingInit);
+
+        connection
+            .handle_incoming_packet(Bytes::from_static(b"[2,null,null]"))
+            .await
+            .unwrap();
+
+        assert_eq!(connection.state.get(), ConnectionState::Ready);
+        assert_eq!(connection.protocol_version(), Some(0));
+        assert!(connection.protocol_capabilities().is_empty());
+
+        let result = connection.emit_with_ack::<(), ()>("event", None).await;
+        assert!(result.unwrap_err().to_string().contains("does not support acks"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_init_packet_rejects_incompatible_protocol() {
+        let (connection, mut rx) = create_test_connection_with_rx();
+        connection.state.store(ConnectionState::AwaitingInit);
+
+        connection
+            .handle_incoming_packet(Bytes::from_static(br#"[2,"99:99:0",null]"#))
+            .await
+            .unwrap();
+
+        assert_eq!(connection.state.get(), ConnectionState::Closing);
+        assert_eq!(connection.protocol_version(), None);
+
+        let message = rx.try_recv().unwrap();
+        let Message::Close(Some(close_frame)) = &*message else {
+            panic!("Expected a close frame, got {message:?}");
+        };
+
+        assert_eq!(close_frame.code, CloseCode::Protocol);
+        assert!(close_frame.reason.contains("Incompatible protocol version"));
+    }
 }
@@ -0,0 +1,58 @@
+use std::{
+    any::type_name,
+    sync::Arc,
+};
+
+use anyhow::{
+    Result,
+    anyhow,
+};
+
+use crate::{
+    connection::WsIoServerConnection,
+    core::{
+        event::extract::WsIoEventContext,
+        traits::event::extract::FromEventContext,
+    },
+};
+
+// Structs
+
+/// A value of type `T` from the extensions of the connection.
+///
+/// Fails to extract when no `T` was inserted; take `Option<WsIoExtension<T>>`
+/// for values that may be missing.
+#[cfg(feature = "connection-extensions")]
+#[derive(Debug)]
+pub struct WsIoExtension<T>(pub Arc<T>);
+
+#[cfg(feature = "connection-extensions")]
+impl<T: Send + Sync + 'static> FromEventContext<WsIoServerConnection> for WsIoExtension<T> {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<WsIoServerConnection>) -> Result<Self> {
+        event_ctx
+            .ctx()
+            .extensions()
+            .get()
+            .map(Self)
+            .ok_or_else(|| anyhow!("Missing connection extension {}", type_name::<T>()))
+    }
+}
+
+/// Shared state of type `S` added with `with_state` on the namespace or server
+/// builder.
+///
+/// Fails to extract when no state of that type was added.
+#[derive(Debug)]
+pub struct WsIoState<S>(pub Arc<S>);
+
+impl<S: Send + Sync + 'static> FromEventContext<WsIoServerConnection> for WsIoState<S> {
+    #[inline]
+    fn from_event_context(event_ctx: &WsIoEventContext<WsIoServerConnection>) -> Result<Self> {
+        event_ctx
+            .ctx()
+            .state()
+            .map(Self)
+            .ok_or_else(|| anyhow!("Missing namespace state {}", type_name::<S>()))
+    }
+}
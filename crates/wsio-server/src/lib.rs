@@ -9,6 +9,8 @@ pub use wsio_core as core;
 pub mod builder;
 mod config;
 pub mod connection;
+pub mod extract;
+pub mod middleware;
 pub mod namespace;
 mod request;
 pub mod request_adapters;
@@ -18,9 +20,12 @@ mod runtime;
 use crate::request_adapters::tower::layer::WsIoServerLayer;
 use crate::{
     builder::WsIoServerBuilder,
+    config::get_state,
+    connection::WsIoServerConnection,
     namespace::{
         WsIoServerNamespace,
         builder::WsIoServerNamespaceBuilder,
+        matcher::WsIoServerNamespaceMatcher,
     },
     runtime::WsIoServerRuntime,
 };
@@ -39,6 +44,12 @@ impl WsIoServer {
         self.0.close_all().await
     }
 
+    /// Ready connection with the id `id`, looked up across all namespaces.
+    #[inline]
+    pub fn connection(&self, id: u64) -> Option<Arc<WsIoServerConnection>> {
+        self.0.connection(id)
+    }
+
     #[inline]
     pub fn connection_count(&self) -> usize {
         self.0.connection_count()
@@ -57,6 +68,9 @@ impl WsIoServer {
         WsIoServerLayer::new(self.0.clone())
     }
 
+    /// Namespace registered under `path`, dynamic namespace whose matcher
+    /// source is `path`, or child a dynamic namespace already created for
+    /// `path`.
     #[inline]
     pub fn of(&self, path: impl AsRef<str>) -> Option<Arc<WsIoServerNamespace>> {
         self.0.get_namespace(path.as_ref())
@@ -67,6 +81,20 @@ impl WsIoServer {
         self.0.namespace_count()
     }
 
+    /// Creates a builder for a dynamic namespace, which creates a child
+    /// namespace for each path `matcher` accepts when a client first connects
+    /// to it.
+    ///
+    /// Children share the configuration and handlers set on the builder, keep
+    /// their own connections and rooms, and expose the parameters `matcher`
+    /// captured through [`WsIoServerNamespace::params`]. Registered namespaces
+    /// take precedence, then dynamic namespaces in registration order. Children
+    /// stay until they are removed with [`Self::remove_namespace`].
+    #[inline]
+    pub fn new_dynamic_namespace_builder(&self, matcher: WsIoServerNamespaceMatcher) -> WsIoServerNamespaceBuilder {
+        self.0.new_dynamic_namespace_builder(matcher)
+    }
+
     #[inline]
     pub fn new_namespace_builder(&self, path: impl AsRef<str>) -> WsIoServerNamespaceBuilder {
         self.0.new_namespace_builder(path.as_ref())
@@ -79,4 +107,11 @@ impl WsIoServer {
     pub async fn shutdown(&self) {
         self.0.shutdown().await
     }
+
+    /// Server-level state of type `S` added with
+    /// [`WsIoServerBuilder::with_state`].
+    #[inline]
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        get_state(&self.0.config.states)
+    }
 }
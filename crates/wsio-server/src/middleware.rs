@@ -0,0 +1,158 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use tokio_tungstenite::tungstenite::Bytes;
+
+use crate::{
+    connection::WsIoServerConnection,
+    core::{
+        __private::matches_event_pattern,
+        packet::WsIoPacket,
+    },
+};
+
+// Types
+pub(crate) type EventMiddleware = Box<
+    dyn Fn(
+            Arc<WsIoServerConnection>,
+            WsIoIncomingEvent,
+            WsIoEventNext,
+        ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+pub(crate) type OutgoingInterceptor =
+    Arc<dyn Fn(&WsIoServerConnection, &mut WsIoPacket) -> Result<()> + Send + Sync + 'static>;
+
+// Structs
+pub(crate) struct EventMiddlewareEntry {
+    pub(crate) middleware: EventMiddleware,
+
+    /// Event name pattern the middleware applies to, or `None` for every event.
+    pub(crate) pattern: Option<String>,
+}
+
+impl EventMiddlewareEntry {
+    // Protected methods
+    #[inline]
+    pub(crate) fn matches(&self, event: &str) -> bool {
+        self.pattern
+            .as_deref()
+            .is_none_or(|pattern| matches_event_pattern(pattern, event))
+    }
+}
+
+/// The rest of the event middleware stack, ending with the event handlers.
+///
+/// A middleware that never calls [`Self::run`] short-circuits the event: no
+/// later middleware and no handler sees it.
+#[derive(Debug)]
+pub struct WsIoEventNext {
+    connection: Arc<WsIoServerConnection>,
+    index: usize,
+
+    /// Where the last middleware leaves the event for the connection to
+    /// dispatch once the stack returns.
+    passed_event: Arc<Mutex<Option<WsIoIncomingEvent>>>,
+}
+
+impl WsIoEventNext {
+    #[inline]
+    pub(crate) fn new(
+        connection: Arc<WsIoServerConnection>,
+        passed_event: Arc<Mutex<Option<WsIoIncomingEvent>>>,
+    ) -> Self {
+        Self {
+            connection,
+            index: 0,
+            passed_event,
+        }
+    }
+
+    // Public methods
+
+    /// Passes `event` to the next middleware whose pattern matches its current
+    /// name, or hands it to the event handlers after the last one.
+    ///
+    /// The handlers run once the whole stack has returned, so `run` does not
+    /// wait for them.
+    pub async fn run(self, event: WsIoIncomingEvent) -> Result<()> {
+        let namespace = self.connection.namespace();
+        let next_entry = namespace.config.event_middlewares[self.index..]
+            .iter()
+            .enumerate()
+            .find(|(_, entry)| entry.matches(&event.name));
+
+        match next_entry {
+            Some((offset, entry)) => {
+                let next = Self {
+                    connection: self.connection.clone(),
+                    index: self.index + offset + 1,
+                    passed_event: self.passed_event,
+                };
+
+                (entry.middleware)(self.connection, event, next).await
+            },
+            None => {
+                *self.passed_event.lock() = Some(event);
+                Ok(())
+            },
+        }
+    }
+}
+
+/// An incoming event on its way through the namespace event middleware.
+///
+/// Middleware may rename it or replace its payload before passing it on with
+/// [`WsIoEventNext::run`]; the handlers then see the changed event.
+#[derive(Clone, Debug)]
+pub struct WsIoIncomingEvent {
+    ack_id: Option<u64>,
+    data: Option<Bytes>,
+    name: String,
+}
+
+impl WsIoIncomingEvent {
+    #[inline]
+    pub(crate) fn new(name: String, data: Option<Bytes>, ack_id: Option<u64>) -> Self {
+        Self { ack_id, data, name }
+    }
+
+    // Public methods
+
+    /// Id the client is waiting on a reply for, if the event was emitted with
+    /// an ack.
+    #[inline]
+    pub fn ack_id(&self) -> Option<u64> {
+        self.ack_id
+    }
+
+    /// Payload encoded with the packet codec of the connection.
+    #[inline]
+    pub fn data(&self) -> Option<&Bytes> {
+        self.data.as_ref()
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Replaces the payload, which must be encoded with the packet codec of
+    /// the connection.
+    #[inline]
+    pub fn set_data(&mut self, data: Option<Bytes>) {
+        self.data = data;
+    }
+
+    #[inline]
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+}
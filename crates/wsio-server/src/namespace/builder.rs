@@ -1,4 +1,5 @@
 use std::{
+    any::TypeId,
     sync::Arc,
     time::Duration,
 };
@@ -13,10 +14,33 @@ use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 use super::{
     WsIoServerNamespace,
     config::WsIoServerNamespaceConfig,
+    matcher::WsIoServerNamespaceMatcher,
 };
 use crate::{
     connection::WsIoServerConnection,
-    core::packet::codecs::WsIoPacketCodec,
+    core::{
+        error::{
+            WsIoError,
+            WsIoProtocolViolationPolicy,
+        },
+        event::{
+            execution::WsIoHandlerExecutionMode,
+            raw::WsIoRawEvent,
+            registry::WsIoEventRegistry,
+        },
+        traits::{
+            event::{
+                definition::WsIoEvent,
+                extract::EventHandler,
+            },
+            packet::codec::PacketCodec,
+        },
+    },
+    middleware::{
+        EventMiddlewareEntry,
+        WsIoEventNext,
+        WsIoIncomingEvent,
+    },
     runtime::WsIoServerRuntime,
 };
 
@@ -36,28 +60,85 @@ impl WsIoServerNamespaceBuilder {
     pub(crate) fn new(path: &str, runtime: Arc<WsIoServerRuntime>) -> Self {
         Self {
             config: WsIoServerNamespaceConfig {
+                ack_timeout: runtime.config.ack_timeout,
                 broadcast_concurrency_limit: runtime.config.broadcast_concurrency_limit,
+                compression_threshold: runtime.config.compression_threshold,
+                error_handler: None,
+                event_handler_timeout: runtime.config.event_handler_timeout,
+                event_middleware_timeout: runtime.config.event_middleware_timeout,
+                event_middlewares: Vec::new(),
+                event_registry: Arc::new(WsIoEventRegistry::new()),
+                handler_execution_mode: runtime.config.handler_execution_mode,
+                heartbeat_interval: runtime.config.heartbeat_interval,
+                heartbeat_max_missed: runtime.config.heartbeat_max_missed,
                 http_request_upgrade_timeout: runtime.config.http_request_upgrade_timeout,
                 init_request_handler: None,
                 init_request_handler_timeout: runtime.config.init_request_handler_timeout,
                 init_response_handler: None,
                 init_response_handler_timeout: runtime.config.init_response_handler_timeout,
                 init_response_timeout: runtime.config.init_response_timeout,
-                middleware: None,
+                matcher: None,
                 middleware_execution_timeout: runtime.config.middleware_execution_timeout,
+                middlewares: Vec::new(),
                 on_connect_handler: None,
                 on_close_handler_timeout: runtime.config.on_close_handler_timeout,
                 on_connect_handler_timeout: runtime.config.on_connect_handler_timeout,
+                on_disconnect_handler: None,
+                on_disconnect_handler_timeout: runtime.config.on_disconnect_handler_timeout,
                 on_ready_handler: None,
-                packet_codec: runtime.config.packet_codec,
+                packet_codecs: vec![runtime.config.packet_codec.clone()],
                 path: path.into(),
+                ping_interval: runtime.config.ping_interval,
+                protocol_violation_policy: runtime.config.protocol_violation_policy,
+                recovery_buffer_size: runtime.config.recovery_buffer_size,
+                recovery_window: runtime.config.recovery_window,
+                states: runtime.config.states.clone(),
                 websocket_config: runtime.config.websocket_config,
             },
             runtime,
         }
     }
 
+    pub(crate) fn new_dynamic(matcher: WsIoServerNamespaceMatcher, runtime: Arc<WsIoServerRuntime>) -> Self {
+        let mut builder = Self::new(matcher.source(), runtime);
+        builder.config.matcher = Some(matcher);
+        builder
+    }
+
+    // Private methods
+    fn push_event_middleware<H, Fut>(mut self, pattern: Option<String>, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>, WsIoIncomingEvent, WsIoEventNext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.config.event_middlewares.push(EventMiddlewareEntry {
+            middleware: Box::new(move |connection, event, next| Box::pin(handler(connection, event, next))),
+            pattern,
+        });
+
+        self
+    }
+
     // Public methods
+    /// Adds a packet codec clients may select for this namespace.
+    ///
+    /// Clients pick a codec by its [`PacketCodec::name`] during the upgrade; the
+    /// first codec stays the default for clients that do not ask for one. When
+    /// several codecs share a name, the first one wins.
+    pub fn add_packet_codec<C: PacketCodec>(mut self, packet_codec: C) -> Self {
+        self.config.packet_codecs.push(Arc::new(packet_codec));
+        self
+    }
+
+    /// Sets how long `emit_with_ack` waits for a client acknowledgement.
+    ///
+    /// When the timeout elapses, the pending ack is dropped and the emit returns
+    /// an error.
+    pub fn ack_timeout(mut self, duration: Duration) -> Self {
+        self.config.ack_timeout = duration;
+        self
+    }
+
     /// Sets the maximum number of broadcast send operations to run at once.
     ///
     /// This value is passed to `StreamExt::for_each_concurrent`; `0` is treated
@@ -67,6 +148,92 @@ impl WsIoServerNamespaceBuilder {
         self
     }
 
+    /// Deflates packets of at least `bytes` encoded bytes sent to or received
+    /// from connections of this namespace.
+    ///
+    /// The `COMPRESSION` capability is advertised during the handshake and
+    /// compression only applies to clients that advertise it as well; other
+    /// clients keep exchanging plain frames.
+    pub fn compression_threshold(mut self, bytes: usize) -> Self {
+        self.config.compression_threshold = Some(bytes);
+        self
+    }
+
+    /// Sets the maximum duration an event handler on a connection of this
+    /// namespace may run.
+    ///
+    /// A handler still running after `duration` is dropped, fails its ack with
+    /// a timeout error and is reported to the `on_error` hook as
+    /// [`WsIoErrorKind::Timeout`](crate::core::error::WsIoErrorKind::Timeout).
+    pub fn event_handler_timeout(mut self, duration: Duration) -> Self {
+        self.config.event_handler_timeout = Some(duration);
+        self
+    }
+
+    /// Sets the maximum duration an incoming event may spend in the stack
+    /// added with [`Self::with_event_middleware`].
+    ///
+    /// Only the middleware calls count. An event still in the stack after
+    /// `duration` is dropped, fails its ack with a timeout error and is
+    /// reported to the `on_error` hook; handlers of an event the stack let
+    /// through are bounded by [`Self::event_handler_timeout`] instead.
+    pub fn event_middleware_timeout(mut self, duration: Duration) -> Self {
+        self.config.event_middleware_timeout = duration;
+        self
+    }
+
+    /// Runs the handlers of `event` in `mode` instead of the namespace
+    /// `handler_execution_mode`.
+    ///
+    /// Every connection gets its own executor for `event`, so a bounded limit
+    /// or sequential order applies per connection. Connections override it with
+    /// `WsIoServerConnection::set_execution_mode`.
+    pub fn execution_mode(self, event: impl AsRef<str>, mode: WsIoHandlerExecutionMode) -> Self {
+        self.config.event_registry.set_execution_mode(event.as_ref(), mode);
+
+        self
+    }
+
+    /// Sets how the handlers of incoming events run on connections of this
+    /// namespace.
+    pub fn handler_execution_mode(mut self, mode: WsIoHandlerExecutionMode) -> Self {
+        self.config.handler_execution_mode = mode;
+        self
+    }
+
+    /// Cuts off the handlers of `event` after `duration` instead of the
+    /// namespace `event_handler_timeout`.
+    ///
+    /// Applies to connections that did not set their own timeout for `event`
+    /// with `WsIoServerConnection::set_handler_timeout`.
+    pub fn handler_timeout(self, event: impl AsRef<str>, duration: Duration) -> Self {
+        self.config.event_registry.set_handler_timeout(event.as_ref(), duration);
+
+        self
+    }
+
+    /// Sets the interval at which connections of this namespace are expected
+    /// to send a frame.
+    ///
+    /// A connection that stays silent for [`Self::heartbeat_max_missed`]
+    /// intervals in a row is closed and cleaned up, so half-open connections
+    /// leave the namespace and their rooms. Any frame counts, including client
+    /// heartbeats and pongs answering [`Self::ping_interval`] pings, so match
+    /// it to the client ping interval or the server ping interval.
+    pub fn heartbeat_interval(mut self, duration: Duration) -> Self {
+        self.config.heartbeat_interval = Some(duration);
+        self
+    }
+
+    /// Sets how many heartbeat intervals in a row a connection of this
+    /// namespace may miss before it is closed.
+    ///
+    /// `0` is treated as `1`.
+    pub fn heartbeat_max_missed(mut self, count: u32) -> Self {
+        self.config.heartbeat_max_missed = count;
+        self
+    }
+
     /// Sets how long a matched HTTP request may take to finish the WebSocket
     /// upgrade.
     ///
@@ -106,13 +273,28 @@ impl WsIoServerNamespaceBuilder {
 
     /// Sets the maximum duration allowed for namespace middleware to run.
     ///
-    /// Middleware is registered with [`Self::with_middleware`] and runs during
-    /// connection setup before the on-connect handler.
+    /// Applies to each middleware added with [`Self::with_middleware`]; the
+    /// event middleware stack has its own [`Self::event_middleware_timeout`].
     pub fn middleware_execution_timeout(mut self, duration: Duration) -> Self {
         self.config.middleware_execution_timeout = duration;
         self
     }
 
+    /// Registers an event handler shared by every connection of this namespace.
+    ///
+    /// Shared handlers are registered once instead of per connection in
+    /// `on_connect`. A connection that registers its own handlers for `event`
+    /// replaces the shared ones for that event.
+    pub fn on<H, Fut, D>(self, event: impl AsRef<str>, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+        D: DeserializeOwned + Send + Sync + 'static,
+    {
+        self.config.event_registry.on(event.as_ref(), handler);
+        self
+    }
+
     /// Sets the maximum duration allowed for per-connection close handlers.
     ///
     /// This applies to handlers registered through `WsIoServerConnection::on_close`.
@@ -140,6 +322,62 @@ impl WsIoServerNamespaceBuilder {
         self
     }
 
+    /// Registers a namespace on-disconnect handler.
+    ///
+    /// The handler runs when a connection that completed setup closes, after it
+    /// left the namespace and its rooms, so it can no longer emit.
+    pub fn on_disconnect<H, Fut>(mut self, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.config.on_disconnect_handler = Some(Box::new(move |connection| Box::pin(handler(connection))));
+        self
+    }
+
+    /// Sets the maximum duration allowed for the namespace on-disconnect
+    /// handler.
+    pub fn on_disconnect_handler_timeout(mut self, duration: Duration) -> Self {
+        self.config.on_disconnect_handler_timeout = duration;
+        self
+    }
+
+    /// Registers a hook receiving errors raised on connections of this
+    /// namespace.
+    ///
+    /// The hook is called synchronously from the task the error happened on, so
+    /// it should hand off any slow work.
+    pub fn on_error<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(WsIoError) + Send + Sync + 'static,
+    {
+        self.config.error_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Registers an extractor handler shared by every connection of this
+    /// namespace.
+    ///
+    /// See `WsIoServerConnection::on_extract` for the available extractors.
+    pub fn on_extract<H, T>(self, event: impl AsRef<str>, handler: H) -> Self
+    where
+        H: EventHandler<WsIoServerConnection, T>,
+    {
+        self.config.event_registry.on_extract(event.as_ref(), handler);
+        self
+    }
+
+    /// Registers a raw event handler shared by every connection of this
+    /// namespace.
+    pub fn on_raw<H, Fut>(self, event: impl AsRef<str>, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<WsIoRawEvent>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.config.event_registry.on_raw(event.as_ref(), handler);
+        self
+    }
+
     /// Registers a namespace on-ready handler.
     ///
     /// The handler is spawned asynchronously after the connection is inserted,
@@ -153,22 +391,93 @@ impl WsIoServerNamespaceBuilder {
         self
     }
 
-    /// Sets the packet codec used by this namespace.
+    /// Registers a handler for `E` shared by every connection of this
+    /// namespace.
+    pub fn on_typed<E, H, Fut>(self, handler: H) -> Self
+    where
+        E: WsIoEvent,
+        H: Fn(Arc<WsIoServerConnection>, Arc<E::Data>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.on(E::NAME, handler)
+    }
+
+    /// Registers an ack-replying event handler shared by every connection of
+    /// this namespace.
+    ///
+    /// A handler that fails answers the ack with an error instead. Each event
+    /// takes a single ack handler; registering a second one panics.
+    pub fn on_with_ack<H, Fut, D, R>(self, event: impl AsRef<str>, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>, Arc<D>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+        D: DeserializeOwned + Send + Sync + 'static,
+        R: Serialize + Send + 'static,
+    {
+        self.config.event_registry.on_with_ack(event.as_ref(), handler);
+        self
+    }
+
+    /// Sets the packet codec used by this namespace, replacing any codecs set
+    /// before.
     ///
     /// The codec is used for ws.io protocol packets and for init payload
     /// serialization/deserialization. It must match the clients that connect to
-    /// this namespace.
-    pub fn packet_codec(mut self, packet_codec: WsIoPacketCodec) -> Self {
-        self.config.packet_codec = packet_codec;
+    /// this namespace. Use [`add_packet_codec`](Self::add_packet_codec) to accept
+    /// more than one.
+    pub fn packet_codec<C: PacketCodec>(mut self, packet_codec: C) -> Self {
+        self.config.packet_codecs = vec![Arc::new(packet_codec)];
+        self
+    }
+
+    /// Sends a WebSocket Ping frame to each connection of this namespace every
+    /// `duration`.
+    ///
+    /// The Pong replies count as activity for [`Self::heartbeat_interval`], so
+    /// clients that send no heartbeats of their own stay connected.
+    pub fn ping_interval(mut self, duration: Duration) -> Self {
+        self.config.ping_interval = Some(duration);
+        self
+    }
+
+    /// Sets the policy for clients of this namespace that break the ws.io
+    /// protocol.
+    pub fn protocol_violation_policy(mut self, policy: WsIoProtocolViolationPolicy) -> Self {
+        self.config.protocol_violation_policy = policy;
+        self
+    }
+
+    /// Sets the maximum number of broadcast packets kept for a connection of
+    /// this namespace waiting to be recovered.
+    ///
+    /// A connection that misses more packets than this cannot be recovered,
+    /// so its client reconnects as a new connection.
+    pub fn recovery_buffer_size(mut self, recovery_buffer_size: usize) -> Self {
+        self.config.recovery_buffer_size = recovery_buffer_size;
+        self
+    }
+
+    /// Enables connection state recovery for this namespace.
+    ///
+    /// When a connection drops without being closed or disconnected on
+    /// purpose, its id, rooms and the broadcasts it misses are kept for
+    /// `duration`. A client reconnecting with its session token in time gets
+    /// the same connection back, with its rooms restored and the missed
+    /// broadcasts replayed; see `WsIoServerConnection::is_recovered`. Setup
+    /// handlers run again for the recovered connection, while direct emits and
+    /// pending acks are not recovered.
+    pub fn recovery_window(mut self, duration: Duration) -> Self {
+        self.config.recovery_window = Some(duration);
         self
     }
 
     /// Registers the namespace with the owning server runtime.
     ///
-    /// Returns an error if another namespace with the same path is already
-    /// registered.
+    /// Returns an error if another namespace with the same path, or another
+    /// dynamic namespace with the same matcher source, is already registered.
     pub fn register(self) -> Result<Arc<WsIoServerNamespace>> {
-        let namespace = WsIoServerNamespace::new(self.config, self.runtime.clone());
+        let namespace = WsIoServerNamespace::new(Arc::new(self.config), self.runtime.clone());
+
         self.runtime.insert_namespace(namespace.clone())?;
         Ok(namespace)
     }
@@ -192,16 +501,75 @@ impl WsIoServerNamespaceBuilder {
         self
     }
 
-    /// Registers namespace middleware for connection setup.
+    /// Adds a middleware every incoming event of this namespace passes through
+    /// before its handlers run.
+    ///
+    /// Middlewares run in the order they were added, each wrapping the ones
+    /// after it like tower layers. A middleware hands the event on with
+    /// [`WsIoEventNext::run`] and may rename it or replace its payload first;
+    /// returning an error rejects the event, fails its ack and reports it to
+    /// the `on_error` hook as [`WsIoErrorKind::Middleware`], while returning
+    /// without calling `run` silently drops it. A middleware that drops an
+    /// event carrying an ack id answers that ack itself, since nothing else
+    /// will.
+    ///
+    /// The stack runs outside the read loop of the connection, so slow
+    /// middleware does not hold up acks, pongs or heartbeats. Events of one
+    /// connection still pass through it one at a time in arrival order, and
+    /// events arriving meanwhile wait in a bounded queue; once it is full the
+    /// connection stops reading until there is room. An event still in the
+    /// stack after [`Self::event_middleware_timeout`] is dropped, while the
+    /// handlers of an event the stack let through run afterwards under their
+    /// own execution mode and timeout.
+    ///
+    /// [`WsIoErrorKind::Middleware`]: crate::core::error::WsIoErrorKind::Middleware
+    pub fn with_event_middleware<H, Fut>(self, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>, WsIoIncomingEvent, WsIoEventNext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.push_event_middleware(None, handler)
+    }
+
+    /// Adds an event middleware like [`Self::with_event_middleware`] that only
+    /// sees events whose name matches `pattern`.
+    ///
+    /// In patterns `*` matches any run of characters, so `chat:*` matches
+    /// `chat:message`; a pattern without `*` matches that event name alone.
+    /// The name is checked when the event reaches the middleware, so it sees
+    /// events renamed by earlier middleware under their new name.
+    pub fn with_event_middleware_for<H, Fut>(self, pattern: impl AsRef<str>, handler: H) -> Self
+    where
+        H: Fn(Arc<WsIoServerConnection>, WsIoIncomingEvent, WsIoEventNext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.push_event_middleware(Some(pattern.as_ref().into()), handler)
+    }
+
+    /// Adds a namespace middleware for connection setup.
     ///
-    /// Middleware runs after init-response handling and before the on-connect
-    /// handler. Returning an error aborts setup for that connection.
+    /// Middlewares run in the order they were added, after init-response
+    /// handling and before the on-connect handler. The first one returning an
+    /// error aborts setup for that connection.
     pub fn with_middleware<H, Fut>(mut self, handler: H) -> Self
     where
         H: Fn(Arc<WsIoServerConnection>) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Result<()>> + Send + 'static,
     {
-        self.config.middleware = Some(Box::new(move |connection| Box::pin(handler(connection))));
+        self.config
+            .middlewares
+            .push(Box::new(move |connection| Box::pin(handler(connection))));
+
+        self
+    }
+
+    /// Adds shared application state for this namespace, replacing any
+    /// server-level or earlier value of the same type.
+    ///
+    /// Handlers read it with `WsIoServerConnection::state` or the
+    /// [`WsIoState`](crate::extract::WsIoState) extractor.
+    pub fn with_state<S: Send + Sync + 'static>(mut self, state: S) -> Self {
+        self.config.states.insert(TypeId::of::<S>(), Arc::new(state));
         self
     }
 
@@ -245,7 +613,7 @@ impl WsIoServerNamespaceBuilder {
             Box::pin(async move {
                 handler(
                     connection,
-                    bytes.map(|bytes| packet_codec.decode_data(bytes)).transpose()?,
+                    bytes.map(|bytes| packet_codec.decode_data_as(bytes)).transpose()?,
                 )
                 .await
             })
@@ -258,13 +626,23 @@ impl WsIoServerNamespaceBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::WsIoServer;
+    use crate::{
+        WsIoServer,
+        core::packet::codecs::WsIoPacketCodec,
+    };
 
     #[tokio::test]
     async fn test_namespace_builder_configuration() {
         let server = Arc::new(WsIoServer::builder().build());
         let builder = WsIoServerNamespaceBuilder::new("/custom", server.0.clone())
+            .ack_timeout(Duration::from_millis(250))
             .broadcast_concurrency_limit(42)
+            .compression_threshold(256)
+            .event_handler_timeout(Duration::from_secs(8))
+            .event_middleware_timeout(Duration::from_secs(12))
+            .handler_execution_mode(WsIoHandlerExecutionMode::Bounded(8))
+            .heartbeat_interval(Duration::from_secs(9))
+            .heartbeat_max_missed(5)
             .http_request_upgrade_timeout(Duration::from_millis(750))
             .init_request_handler_timeout(Duration::from_secs(1))
             .init_response_handler_timeout(Duration::from_secs(2))
@@ -272,7 +650,12 @@ mod tests {
             .middleware_execution_timeout(Duration::from_secs(4))
             .on_close_handler_timeout(Duration::from_secs(5))
             .on_connect_handler_timeout(Duration::from_secs(6))
+            .on_disconnect_handler_timeout(Duration::from_secs(7))
             .packet_codec(WsIoPacketCodec::Msgpack)
+            .ping_interval(Duration::from_secs(10))
+            .protocol_violation_policy(WsIoProtocolViolationPolicy::Report)
+            .recovery_buffer_size(64)
+            .recovery_window(Duration::from_secs(11))
             .websocket_config(WebSocketConfig::default().max_frame_size(Some(777)))
             .websocket_config_mut(|config| {
                 *config = config.max_frame_size(Some(888));
@@ -280,7 +663,14 @@ mod tests {
 
         let config = &builder.config;
         assert_eq!(config.path, "/custom");
+        assert_eq!(config.ack_timeout, Duration::from_millis(250));
         assert_eq!(config.broadcast_concurrency_limit, 42);
+        assert_eq!(config.compression_threshold, Some(256));
+        assert_eq!(config.event_handler_timeout, Some(Duration::from_secs(8)));
+        assert_eq!(config.event_middleware_timeout, Duration::from_secs(12));
+        assert_eq!(config.handler_execution_mode, WsIoHandlerExecutionMode::Bounded(8));
+        assert_eq!(config.heartbeat_interval, Some(Duration::from_secs(9)));
+        assert_eq!(config.heartbeat_max_missed, 5);
         assert_eq!(config.http_request_upgrade_timeout, Duration::from_millis(750));
         assert_eq!(config.init_request_handler_timeout, Duration::from_secs(1));
         assert_eq!(config.init_response_handler_timeout, Duration::from_secs(2));
@@ -288,23 +678,70 @@ mod tests {
         assert_eq!(config.middleware_execution_timeout, Duration::from_secs(4));
         assert_eq!(config.on_close_handler_timeout, Duration::from_secs(5));
         assert_eq!(config.on_connect_handler_timeout, Duration::from_secs(6));
-        assert!(matches!(config.packet_codec, WsIoPacketCodec::Msgpack));
+        assert_eq!(config.on_disconnect_handler_timeout, Duration::from_secs(7));
+        assert_eq!(config.packet_codecs.len(), 1);
+        assert_eq!(config.packet_codecs[0].name(), "msgpack");
+        assert_eq!(config.ping_interval, Some(Duration::from_secs(10)));
+        assert_eq!(config.protocol_violation_policy, WsIoProtocolViolationPolicy::Report);
+        assert_eq!(config.recovery_buffer_size, 64);
+        assert_eq!(config.recovery_window, Some(Duration::from_secs(11)));
         assert_eq!(config.websocket_config.max_frame_size, Some(888));
     }
 
+    #[tokio::test]
+    async fn test_namespace_builder_add_packet_codec_keeps_default_first() {
+        let server = Arc::new(WsIoServer::builder().build());
+        let builder = WsIoServerNamespaceBuilder::new("/custom", server.0.clone())
+            .add_packet_codec(WsIoPacketCodec::Msgpack)
+            .add_packet_codec(WsIoPacketCodec::Postcard);
+
+        let names = builder
+            .config
+            .packet_codecs
+            .iter()
+            .map(|packet_codec| packet_codec.name())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["json", "msgpack", "postcard"]);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_builder_states_override_server_states() {
+        let server = WsIoServer::builder().with_state(1u32).with_state("server").build();
+        let namespace = server
+            .new_namespace_builder("/custom")
+            .with_state("namespace")
+            .register()
+            .unwrap();
+
+        assert_eq!(namespace.state::<u32>().as_deref(), Some(&1));
+        assert_eq!(namespace.state::<&str>().as_deref(), Some(&"namespace"));
+        assert_eq!(server.state::<&str>().as_deref(), Some(&"server"));
+        assert!(namespace.state::<String>().is_none());
+    }
+
     #[tokio::test]
     async fn test_namespace_builder_registers_lifecycle_handlers() {
         let server = Arc::new(WsIoServer::builder().build());
         let builder = WsIoServerNamespaceBuilder::new("/custom", server.0.clone())
             .on_connect(|_connection| async { Ok(()) })
+            .on_error(|_error| {})
             .on_ready(|_connection| async { Ok(()) })
             .with_middleware(|_connection| async { Ok(()) })
+            .with_middleware(|_connection| async { Ok(()) })
+            .with_event_middleware(|_connection, event, next| next.run(event))
+            .with_event_middleware_for("chat:*", |_connection, event, next| next.run(event))
             .with_init_request(|_connection| async { Ok(Some("request".to_string())) })
             .with_init_response(|_connection, _data: Option<String>| async { Ok(()) });
 
         assert!(builder.config.on_connect_handler.is_some());
+        assert!(builder.config.error_handler.is_some());
         assert!(builder.config.on_ready_handler.is_some());
-        assert!(builder.config.middleware.is_some());
+        assert_eq!(builder.config.middlewares.len(), 2);
+        assert_eq!(builder.config.event_middlewares.len(), 2);
+        assert!(builder.config.event_middlewares[0].matches("ping"));
+        assert!(builder.config.event_middlewares[1].matches("chat:message"));
+        assert!(!builder.config.event_middlewares[1].matches("ping"));
         assert!(builder.config.init_request_handler.is_some());
         assert!(builder.config.init_response_handler.is_some());
     }
@@ -13,21 +13,30 @@ use anyhow::Result;
 use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 
 use crate::{
+    config::StateMap,
     connection::WsIoServerConnection,
     core::{
-        packet::codecs::WsIoPacketCodec,
+        error::WsIoProtocolViolationPolicy,
+        event::{
+            execution::WsIoHandlerExecutionMode,
+            registry::WsIoEventRegistry,
+        },
+        traits::packet::codec::PacketCodec,
         types::{
             ArcAsyncUnaryResultHandler,
             BoxAsyncUnaryResultHandler,
+            BoxErrorHandler,
         },
     },
+    middleware::EventMiddlewareEntry,
+    namespace::matcher::WsIoServerNamespaceMatcher,
 };
 
 // Types
 type InitRequestHandler = Box<
     dyn for<'a> Fn(
             Arc<WsIoServerConnection>,
-            &'a WsIoPacketCodec,
+            &'a dyn PacketCodec,
         ) -> Pin<Box<dyn Future<Output = Result<Option<Vec<u8>>>> + Send + 'a>>
         + Send
         + Sync
@@ -38,7 +47,7 @@ type InitResponseHandler = Box<
     dyn for<'a> Fn(
             Arc<WsIoServerConnection>,
             Option<&'a [u8]>,
-            &'a WsIoPacketCodec,
+            &'a dyn PacketCodec,
         ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>
         + Send
         + Sync
@@ -47,6 +56,10 @@ type InitResponseHandler = Box<
 
 // Structs
 pub(crate) struct WsIoServerNamespaceConfig {
+    /// Maximum duration to wait for a client to acknowledge an event emitted with
+    /// `emit_with_ack` on a connection of this namespace.
+    pub(crate) ack_timeout: Duration,
+
     /// Maximum number of broadcast send operations this namespace runs at once.
     ///
     /// Inherited from `WsIoServerConfig` when the namespace builder is created and
@@ -55,6 +68,62 @@ pub(crate) struct WsIoServerNamespaceConfig {
     /// limit.
     pub(crate) broadcast_concurrency_limit: usize,
 
+    /// Minimum encoded size in bytes from which packets sent to connections of
+    /// this namespace are deflated, or `None` to leave compression off.
+    ///
+    /// Inherited from `WsIoServerConfig` when the namespace builder is created and
+    /// overridable per namespace. Compression only takes effect on connections
+    /// whose client negotiated the `COMPRESSION` capability.
+    pub(crate) compression_threshold: Option<usize>,
+
+    /// Optional hook receiving errors raised on connections of this namespace.
+    ///
+    /// It is called with decode failures, handler errors and panics, lifecycle
+    /// handler timeouts and protocol violations, tagged with the connection id
+    /// and the event name when known. Without it these errors are dropped.
+    pub(crate) error_handler: Option<BoxErrorHandler>,
+
+    /// Maximum duration an event handler on a connection of this namespace may
+    /// run before it is cut off and reported as timed out.
+    ///
+    /// `None` lets handlers run until they finish or their connection closes.
+    /// Per-event timeouts set on the namespace builder or a connection take
+    /// precedence.
+    pub(crate) event_handler_timeout: Option<Duration>,
+
+    /// Maximum duration an incoming event may spend in `event_middlewares`,
+    /// not counting its handlers.
+    pub(crate) event_middleware_timeout: Duration,
+
+    /// Event handlers registered on the namespace builder and shared by every
+    /// connection of this namespace.
+    ///
+    /// Each connection registry falls back to it; handlers a connection
+    /// registers for an event replace the shared ones for that event.
+    pub(crate) event_registry: Arc<WsIoEventRegistry<WsIoServerConnection, WsIoServerConnection>>,
+
+    /// Middleware stack incoming events pass through before their handlers, in
+    /// the order it was added.
+    ///
+    /// Each middleware whose pattern matches the event hands it on with
+    /// `WsIoEventNext::run`; an error rejects the event and not calling it
+    /// short-circuits the event.
+    pub(crate) event_middlewares: Vec<EventMiddlewareEntry>,
+
+    /// How the handlers of incoming events run on connections of this
+    /// namespace.
+    pub(crate) handler_execution_mode: WsIoHandlerExecutionMode,
+
+    /// Interval at which connections of this namespace are expected to send a
+    /// frame.
+    ///
+    /// `None` never times out idle connections.
+    pub(super) heartbeat_interval: Option<Duration>,
+
+    /// Number of `heartbeat_interval`s in a row a connection may stay silent
+    /// before it is closed and cleaned up.
+    pub(super) heartbeat_max_missed: u32,
+
     /// Maximum duration allowed for a matched HTTP request to finish the
     /// WebSocket upgrade for this namespace.
     pub(super) http_request_upgrade_timeout: Duration,
@@ -62,7 +131,7 @@ pub(crate) struct WsIoServerNamespaceConfig {
     /// Optional server-side init-request handler for this namespace.
     ///
     /// When present, it runs during connection setup and may return optional data
-    /// that is encoded with `packet_codec` and sent to the client as the init
+    /// that is encoded with the connection's packet codec and sent to the client as the init
     /// packet payload.
     pub(crate) init_request_handler: Option<InitRequestHandler>,
 
@@ -81,15 +150,20 @@ pub(crate) struct WsIoServerNamespaceConfig {
     /// Maximum duration to wait for the client to send its init-response packet.
     pub(crate) init_response_timeout: Duration,
 
-    /// Optional namespace middleware run during connection setup.
-    ///
-    /// Middleware runs after init-response handling and before the on-connect
-    /// handler. Returning an error rejects/aborts the connection setup.
-    pub(crate) middleware: Option<BoxAsyncUnaryResultHandler<WsIoServerConnection>>,
+    /// Decides which requested paths a dynamic namespace serves, or `None` for
+    /// a namespace registered under `path` alone.
+    pub(super) matcher: Option<WsIoServerNamespaceMatcher>,
 
-    /// Maximum duration allowed for `middleware` execution.
+    /// Maximum duration allowed for each entry of `middlewares`.
     pub(crate) middleware_execution_timeout: Duration,
 
+    /// Namespace middleware stack run during connection setup, in the order it
+    /// was added.
+    ///
+    /// Middleware runs after init-response handling and before the on-connect
+    /// handler. The first error rejects/aborts the connection setup.
+    pub(crate) middlewares: Vec<BoxAsyncUnaryResultHandler<WsIoServerConnection>>,
+
     /// Maximum duration allowed for a connection's on-close handler to execute.
     pub(crate) on_close_handler_timeout: Duration,
 
@@ -101,19 +175,57 @@ pub(crate) struct WsIoServerNamespaceConfig {
     /// Maximum duration allowed for `on_connect_handler` execution.
     pub(crate) on_connect_handler_timeout: Duration,
 
+    /// Optional namespace on-disconnect handler.
+    ///
+    /// Runs while a connection that completed setup is cleaned up, after it left
+    /// the namespace and its rooms and after its own on-close handler.
+    pub(crate) on_disconnect_handler: Option<BoxAsyncUnaryResultHandler<WsIoServerConnection>>,
+
+    /// Maximum duration allowed for `on_disconnect_handler` execution.
+    pub(crate) on_disconnect_handler_timeout: Duration,
+
     /// Optional namespace on-ready handler.
     ///
     /// Runs asynchronously after the connection has completed setup and has been
     /// marked ready. It is spawned instead of being awaited in the setup path.
     pub(crate) on_ready_handler: Option<ArcAsyncUnaryResultHandler<WsIoServerConnection>>,
 
-    /// Packet codec used by this namespace for protocol packets and init data.
-    pub(crate) packet_codec: WsIoPacketCodec,
+    /// Packet codecs clients of this namespace may choose from.
+    ///
+    /// A client selects one by name during the upgrade through the `codec` query
+    /// parameter or a `wsio.<name>` `Sec-WebSocket-Protocol` token. The first
+    /// codec is used when the client does not ask for one. The selected codec
+    /// encodes the connection's protocol packets and init data.
+    pub(crate) packet_codecs: Vec<Arc<dyn PacketCodec>>,
 
     /// Namespace path used for routing clients from the `namespace` query
     /// parameter after the server request path is matched.
+    ///
+    /// For a dynamic namespace it is the source of its matcher instead.
     pub(super) path: String,
 
+    /// Interval at which connections of this namespace are sent WebSocket Ping
+    /// frames, or `None` to send none.
+    pub(super) ping_interval: Option<Duration>,
+
+    /// What a connection of this namespace does when its client breaks the
+    /// ws.io protocol.
+    pub(crate) protocol_violation_policy: WsIoProtocolViolationPolicy,
+
+    /// Maximum number of broadcast packets kept for a connection of this
+    /// namespace waiting to be recovered.
+    pub(crate) recovery_buffer_size: usize,
+
+    /// How long the session of a connection of this namespace that dropped
+    /// without closing is kept for recovery, or `None` to disable recovery.
+    pub(crate) recovery_window: Option<Duration>,
+
+    /// Shared application state of this namespace, one value per type.
+    ///
+    /// Starts as a copy of the server-level states; values added on the
+    /// namespace builder replace server values of the same type.
+    pub(crate) states: StateMap,
+
     /// Tungstenite WebSocket transport limits and buffer sizes for this namespace.
     ///
     /// The namespace receives a copy of the server-level config when the builder is
@@ -127,7 +239,17 @@ impl FmtDebug for WsIoServerNamespaceConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("WsIoServerNamespaceConfig")
             .field("path", &self.path)
+            .field("ack_timeout", &self.ack_timeout)
             .field("broadcast_concurrency_limit", &self.broadcast_concurrency_limit)
+            .field("compression_threshold", &self.compression_threshold)
+            .field("error_handler", &self.error_handler.as_ref().map(|_| "<handler>"))
+            .field("event_handler_timeout", &self.event_handler_timeout)
+            .field("event_middleware_timeout", &self.event_middleware_timeout)
+            .field("event_middlewares_len", &self.event_middlewares.len())
+            .field("event_registry", &self.event_registry)
+            .field("handler_execution_mode", &self.handler_execution_mode)
+            .field("heartbeat_interval", &self.heartbeat_interval)
+            .field("heartbeat_max_missed", &self.heartbeat_max_missed)
             .field("http_request_upgrade_timeout", &self.http_request_upgrade_timeout)
             .field(
                 "init_request_handler",
@@ -140,16 +262,27 @@ impl FmtDebug for WsIoServerNamespaceConfig {
             )
             .field("init_response_handler_timeout", &self.init_response_handler_timeout)
             .field("init_response_timeout", &self.init_response_timeout)
-            .field("middleware", &self.middleware.as_ref().map(|_| "<handler>"))
+            .field("matcher", &self.matcher)
             .field("middleware_execution_timeout", &self.middleware_execution_timeout)
+            .field("middlewares_len", &self.middlewares.len())
             .field("on_close_handler_timeout", &self.on_close_handler_timeout)
             .field(
                 "on_connect_handler",
                 &self.on_connect_handler.as_ref().map(|_| "<handler>"),
             )
             .field("on_connect_handler_timeout", &self.on_connect_handler_timeout)
+            .field(
+                "on_disconnect_handler",
+                &self.on_disconnect_handler.as_ref().map(|_| "<handler>"),
+            )
+            .field("on_disconnect_handler_timeout", &self.on_disconnect_handler_timeout)
             .field("on_ready_handler", &self.on_ready_handler.as_ref().map(|_| "<handler>"))
-            .field("packet_codec", &self.packet_codec)
+            .field("packet_codecs", &self.packet_codecs)
+            .field("ping_interval", &self.ping_interval)
+            .field("protocol_violation_policy", &self.protocol_violation_policy)
+            .field("recovery_buffer_size", &self.recovery_buffer_size)
+            .field("recovery_window", &self.recovery_window)
+            .field("states", &self.states)
             .field("websocket_config", &self.websocket_config)
             .finish()
     }
@@ -0,0 +1,209 @@
+use std::fmt::{
+    Debug as FmtDebug,
+    Formatter,
+    Result as FmtResult,
+};
+
+use anyhow::{
+    Result,
+    bail,
+};
+#[cfg(feature = "namespace-regex")]
+use regex::Regex;
+
+// Types
+type NamespacePredicate = Box<dyn Fn(&str) -> bool + Send + Sync + 'static>;
+
+// Enums
+enum Matcher {
+    Pattern(Vec<PatternSegment>),
+    Predicate(NamespacePredicate),
+    #[cfg(feature = "namespace-regex")]
+    Regex(Regex),
+}
+
+enum PatternSegment {
+    Literal(String),
+    Param(String),
+}
+
+// Structs
+
+/// Decides which requested namespace paths a dynamic namespace serves and
+/// which parameters they carry.
+pub struct WsIoServerNamespaceMatcher {
+    matcher: Matcher,
+    source: String,
+}
+
+impl FmtDebug for WsIoServerNamespaceMatcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let kind = match &self.matcher {
+            Matcher::Pattern(_) => "pattern",
+            Matcher::Predicate(_) => "predicate",
+            #[cfg(feature = "namespace-regex")]
+            Matcher::Regex(_) => "regex",
+        };
+
+        f.debug_struct("WsIoServerNamespaceMatcher")
+            .field("kind", &kind)
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl WsIoServerNamespaceMatcher {
+    // Protected methods
+    /// Returns the parameters of `path`, or `None` when it does not match.
+    pub(crate) fn matches(&self, path: &str) -> Option<Vec<(String, String)>> {
+        match &self.matcher {
+            Matcher::Pattern(segments) => {
+                let mut path_segments = path.strip_prefix('/')?.split('/');
+                let mut params = Vec::new();
+                for segment in segments {
+                    let path_segment = path_segments.next()?;
+                    match segment {
+                        PatternSegment::Literal(literal) if literal == path_segment => {},
+                        PatternSegment::Param(name) if !path_segment.is_empty() => {
+                            params.push((name.clone(), path_segment.into()));
+                        },
+                        _ => return None,
+                    }
+                }
+
+                path_segments.next().is_none().then_some(params)
+            },
+            Matcher::Predicate(predicate) => predicate(path).then(Vec::new),
+            #[cfg(feature = "namespace-regex")]
+            Matcher::Regex(regex) => {
+                let captures = regex.captures(path)?;
+                Some(
+                    regex
+                        .capture_names()
+                        .flatten()
+                        .filter_map(|name| Some((name.into(), captures.name(name)?.as_str().into())))
+                        .collect(),
+                )
+            },
+        }
+    }
+
+    #[inline]
+    pub(crate) fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Public methods
+    /// Matches paths segment by segment against `pattern`, where a `{name}`
+    /// segment matches any non-empty segment and captures it as the parameter
+    /// `name`.
+    ///
+    /// `/workspace/{id}` matches `/workspace/123` with `id` set to `123`, but
+    /// neither `/workspace` nor `/workspace/123/chat`.
+    pub fn pattern(pattern: impl Into<String>) -> Result<Self> {
+        let source = pattern.into();
+        let Some(path) = source.strip_prefix('/') else {
+            bail!("Namespace pattern {source} must start with '/'");
+        };
+
+        let mut segments = Vec::new();
+        for segment in path.split('/') {
+            let segment = match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some("") => bail!("Namespace pattern {source} has an unnamed parameter"),
+                Some(name) => {
+                    let is_duplicate = segments
+                        .iter()
+                        .any(|segment| matches!(segment, PatternSegment::Param(other) if other == name));
+
+                    if is_duplicate {
+                        bail!("Namespace pattern {source} repeats the parameter {name}");
+                    }
+
+                    PatternSegment::Param(name.into())
+                },
+                None => PatternSegment::Literal(segment.into()),
+            };
+
+            segments.push(segment);
+        }
+
+        Ok(Self {
+            matcher: Matcher::Pattern(segments),
+            source,
+        })
+    }
+
+    /// Matches the paths `predicate` accepts, without parameters.
+    ///
+    /// `name` stands in for the path of the dynamic namespace, so it must be
+    /// unique among them.
+    pub fn predicate<F>(name: impl Into<String>, predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            matcher: Matcher::Predicate(Box::new(predicate)),
+            source: name.into(),
+        }
+    }
+
+    /// Matches the paths `regex` matches as a whole, with its named capture
+    /// groups as parameters.
+    #[cfg(feature = "namespace-regex")]
+    pub fn regex(regex: impl Into<String>) -> Result<Self> {
+        let source = regex.into();
+        Ok(Self {
+            matcher: Matcher::Regex(Regex::new(&format!("^(?:{source})$"))?),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| ((*name).into(), (*value).into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_pattern_matcher() {
+        let matcher = WsIoServerNamespaceMatcher::pattern("/workspace/{id}/doc/{doc}").unwrap();
+        assert_eq!(
+            matcher.matches("/workspace/12/doc/a"),
+            Some(params(&[("id", "12"), ("doc", "a")]))
+        );
+
+        assert_eq!(matcher.matches("/workspace/12/doc"), None);
+        assert_eq!(matcher.matches("/workspace//doc/a"), None);
+        assert_eq!(matcher.matches("/workspace/12/doc/a/b"), None);
+        assert_eq!(matcher.matches("/other/12/doc/a"), None);
+        assert_eq!(matcher.matches("workspace/12/doc/a"), None);
+
+        assert!(WsIoServerNamespaceMatcher::pattern("workspace/{id}").is_err());
+        assert!(WsIoServerNamespaceMatcher::pattern("/workspace/{}").is_err());
+        assert!(WsIoServerNamespaceMatcher::pattern("/{id}/{id}").is_err());
+    }
+
+    #[test]
+    fn test_predicate_matcher() {
+        let matcher = WsIoServerNamespaceMatcher::predicate("rooms", |path| path.starts_with("/room-"));
+        assert_eq!(matcher.matches("/room-1"), Some(Vec::new()));
+        assert_eq!(matcher.matches("/lobby"), None);
+        assert_eq!(matcher.source(), "rooms");
+    }
+
+    #[cfg(feature = "namespace-regex")]
+    #[test]
+    fn test_regex_matcher() {
+        let matcher = WsIoServerNamespaceMatcher::regex(r"/user-(?<id>\d+)|/guest").unwrap();
+        assert_eq!(matcher.matches("/user-42"), Some(params(&[("id", "42")])));
+        assert_eq!(matcher.matches("/guest"), Some(Vec::new()));
+        assert_eq!(matcher.matches("/user-42/x"), None);
+        assert!(WsIoServerNamespaceMatcher::regex("(").is_err());
+    }
+}
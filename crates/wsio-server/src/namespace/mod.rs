@@ -1,10 +1,22 @@
-use std::sync::Arc;
+use std::{
+    future::pending,
+    ptr,
+    sync::{
+        Arc,
+        Weak,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+};
 
 use anyhow::Result;
 use arc_swap::ArcSwap;
 use futures_util::{
     SinkExt,
     StreamExt,
+    future::join_all,
 };
 use http::{
     HeaderMap,
@@ -31,11 +43,18 @@ use tokio::{
     spawn,
     sync::Mutex,
     task::JoinSet,
-    time::timeout,
+    time::{
+        Instant,
+        Interval,
+        interval_at,
+        sleep,
+        timeout,
+    },
 };
 use tokio_tungstenite::{
     WebSocketStream,
     tungstenite::{
+        Bytes,
         Message,
         protocol::Role,
     },
@@ -43,22 +62,58 @@ use tokio_tungstenite::{
 
 pub(crate) mod builder;
 mod config;
+pub mod matcher;
 pub mod operators;
+pub(crate) mod recovery;
 
 use self::{
     config::WsIoServerNamespaceConfig,
     operators::broadcast::WsIoServerNamespaceBroadcastOperator,
+    recovery::{
+        WsIoServerRecoverySession,
+        parse_recovery_token,
+    },
 };
 use crate::{
     WsIoServer,
+    config::get_state,
     connection::WsIoServerConnection,
-    core::packet::WsIoPacket,
+    core::{
+        packet::WsIoPacket,
+        traits::{
+            event::definition::WsIoEvent,
+            packet::codec::PacketCodec,
+        },
+    },
     runtime::{
         WsIoServerRuntime,
         WsIoServerRuntimeStatus,
     },
 };
 
+// Functions
+#[inline]
+pub(crate) fn encode_packet_to_message(packet_codec: &dyn PacketCodec, packet: &WsIoPacket) -> Result<Arc<Message>> {
+    let bytes = packet_codec.encode(packet)?;
+    Ok(Arc::new(match packet_codec.is_text() {
+        // SAFETY: text packet codecs only produce valid UTF-8 payloads.
+        true => Message::Text(unsafe { String::from_utf8_unchecked(bytes).into() }),
+        false => Message::Binary(bytes.into()),
+    }))
+}
+
+/// Waits for the next tick of the server ping interval, or forever when
+/// pings are disabled.
+#[inline]
+async fn tick_ping_interval(ping_interval: &mut Option<Interval>) {
+    match ping_interval {
+        Some(ping_interval) => {
+            ping_interval.tick().await;
+        },
+        None => pending().await,
+    }
+}
+
 // Enums
 #[repr(u8)]
 #[derive(Debug, Eq, IntoPrimitive, PartialEq, TryFromPrimitive)]
@@ -69,34 +124,82 @@ enum NamespaceStatus {
 }
 
 // Structs
+
+/// A namespace clients connect to, or a dynamic namespace creating child
+/// namespaces for the paths its matcher accepts.
+///
+/// Children share the configuration and handlers of their dynamic namespace
+/// but keep their own connections and rooms. The dynamic namespace itself holds
+/// no connections. A child is created once a connection to its path upgrades,
+/// and dropped once it has no connection left and no session waiting to be
+/// recovered.
 #[derive(Debug)]
 pub struct WsIoServerNamespace {
-    pub(crate) config: WsIoServerNamespaceConfig,
+    children: FxDashMap<String, Arc<WsIoServerNamespace>>,
+    pub(crate) config: Arc<WsIoServerNamespaceConfig>,
     connection_ids: ArcSwap<RoaringTreemap>,
     connections: FxDashMap<u64, Arc<WsIoServerConnection>>,
     connection_task_set: Mutex<JoinSet<()>>,
+    params: Vec<(String, String)>,
+    parent: Option<Weak<WsIoServerNamespace>>,
+    path: String,
+    recovery_sessions: FxDashMap<u64, Arc<WsIoServerRecoverySession>>,
     rooms: FxDashMap<String, RoaringTreemap>,
     runtime: Arc<WsIoServerRuntime>,
     status: AtomicEnumCell<NamespaceStatus>,
+    upgraded_connection_count: AtomicUsize,
 }
 
 impl WsIoServerNamespace {
-    fn new(config: WsIoServerNamespaceConfig, runtime: Arc<WsIoServerRuntime>) -> Arc<Self> {
+    #[inline]
+    fn new(config: Arc<WsIoServerNamespaceConfig>, runtime: Arc<WsIoServerRuntime>) -> Arc<Self> {
+        let path = config.path.clone();
+        Self::with_path(config, path, Vec::new(), None, runtime)
+    }
+
+    fn with_path(
+        config: Arc<WsIoServerNamespaceConfig>,
+        path: String,
+        params: Vec<(String, String)>,
+        parent: Option<Weak<WsIoServerNamespace>>,
+        runtime: Arc<WsIoServerRuntime>,
+    ) -> Arc<Self> {
         Arc::new(Self {
+            children: FxDashMap::default(),
             config,
             connection_ids: ArcSwap::new(Arc::new(RoaringTreemap::new())),
             connections: FxDashMap::default(),
             connection_task_set: Mutex::new(JoinSet::new()),
+            params,
+            parent,
+            path,
+            recovery_sessions: FxDashMap::default(),
             rooms: FxDashMap::default(),
             runtime,
             status: AtomicEnumCell::new(NamespaceStatus::Running),
+            upgraded_connection_count: AtomicUsize::new(0),
         })
     }
 
     // Private methods
+    /// Claims the recovery session `token` refers to, if it is still waiting
+    /// for its client and was encoded with `packet_codec`.
+    fn claim_recovery_session(
+        &self,
+        token: &str,
+        packet_codec: &dyn PacketCodec,
+    ) -> Option<Arc<WsIoServerRecoverySession>> {
+        let (id, secret) = parse_recovery_token(token)?;
+        let recovery_session = self.recovery_sessions.get(&id)?.clone();
+        (recovery_session.packet_codec().name() == packet_codec.name() && recovery_session.claim(secret))
+            .then_some(recovery_session)
+    }
+
     async fn handle_upgraded_request(
         self: &Arc<Self>,
         headers: HeaderMap,
+        packet_codec: Arc<dyn PacketCodec>,
+        recovery_token: Option<String>,
         request_uri: Uri,
         upgraded: Upgraded,
     ) -> Result<()> {
@@ -108,21 +211,42 @@ impl WsIoServerNamespace {
         // Check runtime and namespace status
         if !self.runtime.status.is(WsIoServerRuntimeStatus::Running) || !self.status.is(NamespaceStatus::Running) {
             ws_stream
-                .send((*self.encode_packet_to_message(&WsIoPacket::new_disconnect())?).clone())
+                .send((*encode_packet_to_message(&*packet_codec, &WsIoPacket::new_disconnect())?).clone())
                 .await?;
 
             let _ = ws_stream.close(None).await;
             return Ok(());
         }
 
+        // Claim the session the client asks to recover, reusing its connection id
+        let recovery_session = recovery_token.and_then(|token| self.claim_recovery_session(&token, &*packet_codec));
+
         // Create connection
-        let (connection, mut message_rx) = WsIoServerConnection::new(headers, self.clone(), request_uri);
+        let (connection, mut message_rx) =
+            WsIoServerConnection::new(headers, self.clone(), packet_codec, recovery_session, request_uri);
 
         // Split ws stream and spawn read and write tasks
         let (mut ws_stream_writer, mut ws_stream_reader) = ws_stream.split();
         let connection_clone = connection.clone();
+        let heartbeat_timeout = self
+            .config
+            .heartbeat_interval
+            .map(|interval| interval * self.config.heartbeat_max_missed.max(1));
         let mut read_ws_stream_task = spawn(async move {
-            while let Some(message) = ws_stream_reader.next().await {
+            loop {
+                // Any frame counts as a heartbeat; a connection silent for too long is treated as dead
+                let message = match heartbeat_timeout {
+                    Some(duration) => match timeout(duration, ws_stream_reader.next()).await {
+                        Ok(message) => message,
+                        Err(_) => break,
+                    },
+                    None => ws_stream_reader.next().await,
+                };
+
+                let Some(message) = message else {
+                    break;
+                };
+
                 if match message {
                     Ok(Message::Binary(bytes)) => {
                         // Treat any single-byte binary frame as a client heartbeat and ignore it
@@ -130,10 +254,15 @@ impl WsIoServerNamespace {
                             continue;
                         }
 
-                        connection_clone.handle_incoming_packet(&bytes).await
+                        connection_clone.handle_incoming_binary_packet(bytes).await
+                    },
+                    Ok(Message::Close(_)) => {
+                        // A close frame means the client left on purpose, so the connection is not kept for recovery
+                        connection_clone.close();
+                        break;
                     },
-                    Ok(Message::Close(_)) | Err(_) => break,
-                    Ok(Message::Text(text)) => connection_clone.handle_incoming_packet(text.as_bytes()).await,
+                    Ok(Message::Text(text)) => connection_clone.handle_incoming_packet(text.into()).await,
+                    Err(_) => break,
                     _ => Ok(()),
                 }
                 .is_err()
@@ -143,9 +272,22 @@ impl WsIoServerNamespace {
             }
         });
 
+        let mut ping_interval = self
+            .config
+            .ping_interval
+            .map(|period| interval_at(Instant::now() + period, period));
+
+        let connection_clone = connection.clone();
         let mut write_ws_stream_task = spawn(async move {
-            while let Some(message) = message_rx.recv().await {
-                let message = (*message).clone();
+            loop {
+                let message = select! {
+                    message = message_rx.recv() => match message {
+                        Some(message) => connection_clone.compress_outgoing_message((*message).clone()),
+                        None => break,
+                    },
+                    _ = tick_ping_interval(&mut ping_interval) => Message::Ping(Bytes::new()),
+                };
+
                 let is_close = matches!(message, Message::Close(_));
                 if ws_stream_writer.send(message).await.is_err() {
                     break;
@@ -184,32 +326,171 @@ impl WsIoServerNamespace {
         Ok(())
     }
 
+    /// Removes this child from its dynamic namespace once it handles no
+    /// upgraded connection and keeps no session waiting to be recovered.
+    fn remove_if_idle(&self) {
+        let Some(parent) = self.parent.as_ref().and_then(Weak::upgrade) else {
+            return;
+        };
+
+        parent.children.remove_if(&self.path, |_, child| {
+            ptr::eq(&**child, self)
+                && child.upgraded_connection_count.load(Ordering::Relaxed) == 0
+                && child.recovery_sessions.is_empty()
+        });
+    }
+
     // Protected methods
+    /// Returns the child namespace for `path`, creating it if the matcher
+    /// accepts `path` and this dynamic namespace is still running, and counts
+    /// an upgraded connection on it until [`release`](Self::release).
+    pub(crate) fn acquire_child(self: &Arc<Self>, path: &str) -> Option<Arc<WsIoServerNamespace>> {
+        if !self.status.is(NamespaceStatus::Running) {
+            return None;
+        }
+
+        // Count the connection while holding the map guard, so an idle child is never dropped in between
+        if let Some(child) = self.children.get(path) {
+            child.upgraded_connection_count.fetch_add(1, Ordering::Relaxed);
+            return Some(child.clone());
+        }
+
+        let params = self.config.matcher.as_ref()?.matches(path)?;
+        let child = self.children.entry(path.into()).or_insert_with(|| {
+            Self::with_path(
+                self.config.clone(),
+                path.into(),
+                params,
+                Some(Arc::downgrade(self)),
+                self.runtime.clone(),
+            )
+        });
+
+        child.upgraded_connection_count.fetch_add(1, Ordering::Relaxed);
+        Some(child.clone())
+    }
+
     #[inline]
     pub(crate) fn add_connection_id_to_room(&self, room_name: &str, connection_id: u64) {
         self.rooms.entry(room_name.into()).or_default().insert(connection_id);
     }
 
+    /// Buffers a broadcast message for the connection `connection_id` while it
+    /// waits to be recovered, returning whether it was buffered.
+    ///
+    /// A session whose buffer is full cannot be recovered consistently, so it
+    /// is dropped.
+    pub(crate) fn buffer_recovery_message(&self, connection_id: u64, message: Arc<Message>) -> bool {
+        // Clone the session out of the map so its lock is never taken while holding a map guard
+        let Some(recovery_session) = self.recovery_sessions.get(&connection_id).map(|entry| entry.clone()) else {
+            return false;
+        };
+
+        if recovery_session.push(message, self.config.recovery_buffer_size) {
+            return true;
+        }
+
+        self.expire_recovery_session(&recovery_session);
+        false
+    }
+
     #[inline]
-    pub(crate) fn encode_packet_to_message(&self, packet: &WsIoPacket) -> Result<Arc<Message>> {
-        let bytes = self.config.packet_codec.encode(packet)?;
-        Ok(Arc::new(match self.config.packet_codec.is_text() {
-            // SAFETY: text packet codecs only produce valid UTF-8 payloads.
-            true => Message::Text(unsafe { String::from_utf8_unchecked(bytes).into() }),
-            false => Message::Binary(bytes.into()),
-        }))
+    pub(crate) fn clone_children(&self) -> Vec<Arc<WsIoServerNamespace>> {
+        self.children.iter().map(|child| child.value().clone()).collect()
     }
 
+    /// Removes a connection that dropped without closing, keeping its id in
+    /// its rooms and in the broadcast targets until its client recovers it or
+    /// the recovery window elapses.
+    ///
+    /// Returns whether the connection had been inserted.
+    pub(crate) fn detach_connection(
+        self: &Arc<Self>,
+        id: u64,
+        packet_codec: Arc<dyn PacketCodec>,
+        rooms: Vec<String>,
+        secret: String,
+    ) -> bool {
+        let Some(recovery_window) = self.config.recovery_window else {
+            return self.remove_connection(id);
+        };
+
+        if self.connections.remove(&id).is_none() {
+            return false;
+        }
+
+        self.runtime.remove_connection(id);
+        let recovery_session = Arc::new(WsIoServerRecoverySession::new(id, packet_codec, rooms, secret));
+        self.recovery_sessions.insert(id, recovery_session.clone());
+
+        // Drop the session once the window elapses, without keeping the namespace alive
+        let namespace = Arc::downgrade(self);
+        spawn(async move {
+            sleep(recovery_window).await;
+            if let Some(namespace) = namespace.upgrade() {
+                namespace.expire_recovery_session(&recovery_session);
+            }
+        });
+
+        true
+    }
+
+    /// Drops a recovery session that was not restored, removing its
+    /// connection id from its rooms and from the broadcast targets.
+    pub(crate) fn expire_recovery_session(&self, recovery_session: &Arc<WsIoServerRecoverySession>) {
+        if !recovery_session.expire() {
+            return;
+        }
+
+        let id = recovery_session.id();
+        self.recovery_sessions
+            .remove_if(&id, |_, entry| Arc::ptr_eq(entry, recovery_session));
+
+        for room_name in recovery_session.rooms() {
+            self.remove_connection_id_from_room(room_name, id);
+        }
+
+        self.connection_ids.rcu(|old_connection_ids| {
+            let mut new_connection_ids = (**old_connection_ids).clone();
+            new_connection_ids.remove(id);
+            new_connection_ids
+        });
+
+        self.remove_if_idle();
+    }
+
+    /// Handles the upgrade of a request to `path`, which a dynamic namespace
+    /// only turns into a child once the upgrade succeeded, so requests that
+    /// never upgrade leave no child behind.
     pub(crate) async fn handle_on_upgrade_request(
         self: &Arc<Self>,
         headers: HeaderMap,
         on_upgrade: OnUpgrade,
+        packet_codec: Arc<dyn PacketCodec>,
+        path: String,
+        recovery_token: Option<String>,
         request_uri: Uri,
     ) {
         let namespace = self.clone();
         self.connection_task_set.lock().await.spawn(async move {
-            if let Ok(Ok(upgraded)) = timeout(namespace.config.http_request_upgrade_timeout, on_upgrade).await {
-                let _ = namespace.handle_upgraded_request(headers, request_uri, upgraded).await;
+            let Ok(Ok(upgraded)) = timeout(namespace.config.http_request_upgrade_timeout, on_upgrade).await else {
+                return;
+            };
+
+            if !namespace.is_dynamic() {
+                let _ = namespace
+                    .handle_upgraded_request(headers, packet_codec, recovery_token, request_uri, upgraded)
+                    .await;
+
+                return;
+            }
+
+            if let Some(child) = namespace.acquire_child(&path) {
+                let _ = child
+                    .handle_upgraded_request(headers, packet_codec, recovery_token, request_uri, upgraded)
+                    .await;
+
+                child.release();
             }
         });
     }
@@ -217,7 +498,7 @@ impl WsIoServerNamespace {
     #[inline]
     pub(crate) fn insert_connection(&self, connection: Arc<WsIoServerConnection>) {
         self.connections.insert(connection.id(), connection.clone());
-        self.runtime.insert_connection_id(connection.id());
+        self.runtime.insert_connection(connection.clone());
         self.connection_ids.rcu(|old_connection_ids| {
             let mut new_connection_ids = (**old_connection_ids).clone();
             new_connection_ids.insert(connection.id());
@@ -225,15 +506,69 @@ impl WsIoServerNamespace {
         });
     }
 
+    /// Whether a client connecting to `path` would join a child of this
+    /// dynamic namespace, without creating it.
+    #[inline]
+    pub(crate) fn matches_child(&self, path: &str) -> bool {
+        self.children.contains_key(path)
+            || self
+                .config
+                .matcher
+                .as_ref()
+                .is_some_and(|matcher| matcher.matches(path).is_some())
+    }
+
+    /// Returns the packet codec registered under `name`, or the namespace
+    /// default when `name` is `None`.
+    #[inline]
+    pub(crate) fn packet_codec(&self, name: Option<&str>) -> Option<&Arc<dyn PacketCodec>> {
+        match name {
+            Some(name) => self
+                .config
+                .packet_codecs
+                .iter()
+                .find(|packet_codec| packet_codec.name() == name),
+            None => self.config.packet_codecs.first(),
+        }
+    }
+
+    /// Stops counting an upgraded connection acquired with
+    /// [`acquire_child`](Self::acquire_child), dropping the child if it is idle.
     #[inline]
-    pub(crate) fn remove_connection(&self, id: u64) {
-        self.connections.remove(&id);
-        self.runtime.remove_connection_id(id);
+    pub(crate) fn release(&self) {
+        self.upgraded_connection_count.fetch_sub(1, Ordering::Relaxed);
+        self.remove_if_idle();
+    }
+
+    #[inline]
+    pub(crate) fn remove_child(&self, path: &str) -> Option<Arc<WsIoServerNamespace>> {
+        self.children.remove(path).map(|(_, child)| child)
+    }
+
+    /// Removes a connection, returning whether it had been inserted.
+    ///
+    /// Ids of connections that were never inserted are left alone, since a
+    /// connection recovering a session shares its id with it.
+    pub(crate) fn remove_connection(&self, id: u64) -> bool {
+        if self.connections.remove(&id).is_none() {
+            return false;
+        }
+
+        self.runtime.remove_connection(id);
         self.connection_ids.rcu(|old_connection_ids| {
             let mut new_connection_ids = (**old_connection_ids).clone();
             new_connection_ids.remove(id);
             new_connection_ids
         });
+
+        true
+    }
+
+    /// Forgets a recovery session once its connection has been restored.
+    #[inline]
+    pub(crate) fn remove_recovery_session(&self, recovery_session: &Arc<WsIoServerRecoverySession>) {
+        self.recovery_sessions
+            .remove_if(&recovery_session.id(), |_, entry| Arc::ptr_eq(entry, recovery_session));
     }
 
     #[inline]
@@ -246,15 +581,52 @@ impl WsIoServerNamespace {
     }
 
     // Public methods
+    /// Child namespace created for `path` by this dynamic namespace, while a
+    /// client is connected to it or may still recover its session.
+    #[inline]
+    pub fn child(&self, path: impl AsRef<str>) -> Option<Arc<WsIoServerNamespace>> {
+        self.children.get(path.as_ref()).map(|child| child.clone())
+    }
+
+    /// Child namespaces of this dynamic namespace that are currently in use.
+    #[inline]
+    pub fn children(&self) -> Vec<Arc<WsIoServerNamespace>> {
+        self.clone_children()
+    }
+
     pub async fn close_all(self: &Arc<Self>) {
         WsIoServerNamespaceBroadcastOperator::new(self.clone()).close().await;
     }
 
+    /// Ready connection with the id `id`, if it belongs to this namespace.
+    #[inline]
+    pub fn connection(&self, id: u64) -> Option<Arc<WsIoServerConnection>> {
+        self.connections.get(&id).map(|entry| entry.value().clone())
+    }
+
     #[inline]
     pub fn connection_count(&self) -> usize {
         self.connections.len()
     }
 
+    /// Ready connections that joined any of `room_names`, each listed once.
+    pub fn connections_in(
+        &self,
+        room_names: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Vec<Arc<WsIoServerConnection>> {
+        let mut connection_ids = RoaringTreemap::new();
+        for room_name in room_names {
+            if let Some(room) = self.rooms.get(room_name.as_ref()) {
+                connection_ids |= room.value();
+            }
+        }
+
+        connection_ids
+            .into_iter()
+            .filter_map(|connection_id| self.connection(connection_id))
+            .collect()
+    }
+
     pub async fn disconnect_all(self: &Arc<Self>) -> Result<()> {
         WsIoServerNamespaceBroadcastOperator::new(self.clone())
             .disconnect()
@@ -267,6 +639,12 @@ impl WsIoServerNamespace {
             .await
     }
 
+    pub async fn emit_typed<E: WsIoEvent>(self: &Arc<Self>, data: &E::Data) -> Result<()> {
+        WsIoServerNamespaceBroadcastOperator::new(self.clone())
+            .emit_typed::<E>(data)
+            .await
+    }
+
     #[inline]
     pub fn except(
         self: &Arc<Self>,
@@ -275,9 +653,50 @@ impl WsIoServerNamespace {
         WsIoServerNamespaceBroadcastOperator::new(self.clone()).except(room_names)
     }
 
+    /// Whether this is a dynamic namespace, creating children instead of
+    /// accepting connections itself.
+    #[inline]
+    pub fn is_dynamic(&self) -> bool {
+        self.config.matcher.is_some()
+    }
+
+    /// Value of the parameter `name` the matcher of the dynamic namespace
+    /// captured from the path of this child.
+    #[inline]
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parameters the matcher of the dynamic namespace captured from the path
+    /// of this child, in the order they appear; empty for other namespaces.
+    #[inline]
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Path clients connect with; a dynamic namespace reports the source of
+    /// its matcher.
     #[inline]
     pub fn path(&self) -> &str {
-        &self.config.path
+        &self.path
+    }
+
+    /// Number of connections in the room `room_name`, counting connections
+    /// waiting to be recovered; `0` when the room does not exist.
+    #[inline]
+    pub fn room_size(&self, room_name: impl AsRef<str>) -> usize {
+        self.rooms.get(room_name.as_ref()).map_or(0, |room| room.len() as usize)
+    }
+
+    /// Names of the rooms that currently have members, in no particular order.
+    ///
+    /// Rooms are removed once their last member leaves.
+    #[inline]
+    pub fn rooms(&self) -> Vec<String> {
+        self.rooms.iter().map(|room| room.key().clone()).collect()
     }
 
     #[inline]
@@ -293,12 +712,35 @@ impl WsIoServerNamespace {
         }
 
         self.close_all().await;
+        let recovery_sessions = self
+            .recovery_sessions
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect::<Vec<_>>();
+
+        for recovery_session in &recovery_sessions {
+            self.expire_recovery_session(recovery_session);
+        }
+
+        join_all(
+            self.clone_children()
+                .into_iter()
+                .map(|child| Box::pin(async move { child.shutdown().await })),
+        )
+        .await;
+
         let mut connection_task_set = self.connection_task_set.lock().await;
         while connection_task_set.join_next().await.is_some() {}
 
         self.status.store(NamespaceStatus::Stopped);
     }
 
+    /// State of type `S` added on the namespace or server builder.
+    #[inline]
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        get_state(&self.config.states)
+    }
+
     #[inline]
     pub fn to(
         self: &Arc<Self>,
@@ -316,13 +758,27 @@ mod tests {
 
     use super::*;
     use crate::{
-        config::WsIoServerConfig,
-        core::packet::codecs::WsIoPacketCodec,
+        config::{
+            StateMap,
+            WsIoServerConfig,
+        },
+        core::{
+            error::WsIoProtocolViolationPolicy,
+            event::execution::WsIoHandlerExecutionMode,
+            packet::codecs::WsIoPacketCodec,
+        },
     };
 
     fn create_test_namespace() -> Arc<WsIoServerNamespace> {
         let runtime = WsIoServerRuntime::new(WsIoServerConfig {
+            ack_timeout: Duration::from_secs(3),
             broadcast_concurrency_limit: 16,
+            compression_threshold: None,
+            event_handler_timeout: None,
+            event_middleware_timeout: Duration::from_secs(3),
+            handler_execution_mode: WsIoHandlerExecutionMode::Concurrent,
+            heartbeat_interval: None,
+            heartbeat_max_missed: 3,
             http_request_upgrade_timeout: Duration::from_secs(3),
             init_request_handler_timeout: Duration::from_secs(3),
             init_response_handler_timeout: Duration::from_secs(3),
@@ -330,8 +786,14 @@ mod tests {
             middleware_execution_timeout: Duration::from_secs(3),
             on_close_handler_timeout: Duration::from_secs(3),
             on_connect_handler_timeout: Duration::from_secs(3),
-            packet_codec: WsIoPacketCodec::SerdeJson,
+            on_disconnect_handler_timeout: Duration::from_secs(3),
+            packet_codec: Arc::new(WsIoPacketCodec::SerdeJson),
+            ping_interval: None,
+            protocol_violation_policy: WsIoProtocolViolationPolicy::Disconnect,
+            recovery_buffer_size: 1024,
+            recovery_window: None,
             request_path: "/socket".into(),
+            states: StateMap::default(),
             websocket_config: WebSocketConfig::default(),
         });
         runtime.new_namespace_builder("/test").register().unwrap()
@@ -391,6 +853,27 @@ mod tests {
         assert!(!namespace.rooms.contains_key("room2"));
     }
 
+    #[tokio::test]
+    async fn test_namespace_rooms_and_room_size() {
+        let namespace = create_test_namespace();
+        namespace.add_connection_id_to_room("room1", 1);
+        namespace.add_connection_id_to_room("room1", 2);
+        namespace.add_connection_id_to_room("room2", 3);
+
+        let mut rooms = namespace.rooms();
+        rooms.sort();
+        assert_eq!(rooms, ["room1", "room2"]);
+        assert_eq!(namespace.room_size("room1"), 2);
+        assert_eq!(namespace.room_size("missing"), 0);
+
+        // Ids without a ready connection are not listed as connections
+        assert!(namespace.connections_in(["room1", "room2"]).is_empty());
+        assert!(namespace.connection(1).is_none());
+
+        namespace.remove_connection_id_from_room("room2", 3);
+        assert_eq!(namespace.rooms(), ["room1"]);
+    }
+
     #[tokio::test]
     async fn test_namespace_remove_connection_id_from_empty_room() {
         let namespace = create_test_namespace();
@@ -400,13 +883,21 @@ mod tests {
 
     #[tokio::test]
     async fn test_namespace_encode_packet_to_message() {
-        let namespace = create_test_namespace();
         let packet = WsIoPacket::new_disconnect();
-        let message = namespace.encode_packet_to_message(&packet).unwrap();
+        let message = encode_packet_to_message(&WsIoPacketCodec::SerdeJson, &packet).unwrap();
 
         assert!(matches!(&*message, Message::Text(_)));
     }
 
+    #[tokio::test]
+    async fn test_namespace_packet_codec_lookup() {
+        let namespace = create_test_namespace();
+
+        assert_eq!(namespace.packet_codec(None).unwrap().name(), "json");
+        assert_eq!(namespace.packet_codec(Some("json")).unwrap().name(), "json");
+        assert!(namespace.packet_codec(Some("msgpack")).is_none());
+    }
+
     #[tokio::test]
     async fn test_namespace_shutdown_idempotent() {
         let namespace = create_test_namespace();
@@ -435,4 +926,17 @@ mod tests {
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("invalid status"));
     }
+
+    #[tokio::test]
+    async fn test_broadcast_operator_emit_with_ack_with_no_connections() {
+        let namespace = create_test_namespace();
+        let replies = namespace
+            .to(["room1"])
+            .timeout(Duration::from_millis(10))
+            .emit_with_ack::<(), ()>("event", None)
+            .await
+            .unwrap();
+
+        assert!(replies.is_empty());
+    }
 }
@@ -1,29 +1,49 @@
 use std::{
     collections::HashSet,
     sync::Arc,
+    time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{
+    Result,
+    anyhow,
+};
 use futures_util::{
     StreamExt,
-    future::ready,
+    future::{
+        join_all,
+        ready,
+    },
     stream::iter,
 };
+use kikiutils::types::fx_collections::FxHashMap;
 use roaring::RoaringTreemap;
-use serde::Serialize;
+use serde::{
+    Serialize,
+    de::DeserializeOwned,
+};
+use tokio_tungstenite::tungstenite::Message;
 
 use super::super::{
     NamespaceStatus,
     WsIoServerNamespace,
+    encode_packet_to_message,
 };
 use crate::{
     connection::WsIoServerConnection,
-    core::packet::WsIoPacket,
+    core::{
+        packet::WsIoPacket,
+        traits::{
+            event::definition::WsIoEvent,
+            packet::codec::PacketCodec,
+        },
+    },
 };
 
 // Structs
 #[derive(Clone, Debug)]
 pub struct WsIoServerNamespaceBroadcastOperator {
+    ack_timeout: Option<Duration>,
     exclude_connection_ids: HashSet<u64>,
     exclude_rooms: HashSet<String>,
     include_rooms: HashSet<String>,
@@ -34,6 +54,7 @@ impl WsIoServerNamespaceBroadcastOperator {
     #[inline]
     pub(in super::super) fn new(namespace: Arc<WsIoServerNamespace>) -> Self {
         Self {
+            ack_timeout: None,
             exclude_connection_ids: HashSet::new(),
             exclude_rooms: HashSet::new(),
             include_rooms: HashSet::new(),
@@ -42,11 +63,135 @@ impl WsIoServerNamespaceBroadcastOperator {
     }
 
     // Private methods
-    async fn for_each_target_connections<F, Fut>(&self, f: F)
+
+    /// Buffers the messages for target connections waiting to be recovered,
+    /// removing them from `connection_ids` so they are not sent twice once
+    /// restored.
+    fn buffer_recovery_messages(
+        &self,
+        connection_ids: &mut RoaringTreemap,
+        messages: &FxHashMap<String, Arc<Message>>,
+    ) {
+        if self.namespace.recovery_sessions.is_empty() {
+            return;
+        }
+
+        let mut buffered_connection_ids = RoaringTreemap::new();
+        for connection_id in &*connection_ids {
+            if self.namespace.connections.contains_key(&connection_id) {
+                continue;
+            }
+
+            let message = self
+                .namespace
+                .recovery_sessions
+                .get(&connection_id)
+                .and_then(|recovery_session| messages.get(recovery_session.packet_codec().name()).cloned());
+
+            if let Some(message) = message
+                && self.namespace.buffer_recovery_message(connection_id, message)
+            {
+                buffered_connection_ids.insert(connection_id);
+            }
+        }
+
+        *connection_ids -= buffered_connection_ids;
+    }
+
+    /// Runs `encode` once for each distinct packet codec used by the
+    /// connections in `connection_ids`, including those waiting to be
+    /// recovered, keyed by codec name.
+    fn encode_per_packet_codec<T>(
+        &self,
+        connection_ids: &RoaringTreemap,
+        encode: impl Fn(&dyn PacketCodec) -> Result<T>,
+    ) -> Result<FxHashMap<String, T>> {
+        let mut encoded = FxHashMap::default();
+        let mut encode_once = |packet_codec: &dyn PacketCodec| -> Result<()> {
+            if !encoded.contains_key(packet_codec.name()) {
+                encoded.insert(packet_codec.name().to_owned(), encode(packet_codec)?);
+            }
+
+            Ok(())
+        };
+
+        for connection_id in connection_ids {
+            if let Some(connection) = self.namespace.connections.get(&connection_id) {
+                encode_once(connection.packet_codec())?;
+            } else if let Some(recovery_session) = self.namespace.recovery_sessions.get(&connection_id) {
+                encode_once(recovery_session.packet_codec())?;
+            }
+        }
+
+        Ok(encoded)
+    }
+
+    async fn for_each_connections<F, Fut>(&self, connection_ids: RoaringTreemap, f: F)
     where
         F: Fn(Arc<WsIoServerConnection>) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Result<()>> + Send + 'static,
     {
+        if connection_ids.is_empty() {
+            return;
+        }
+
+        iter(connection_ids)
+            .filter_map(|connection_id| {
+                ready(
+                    self.namespace
+                        .connections
+                        .get(&connection_id)
+                        .map(|entry| entry.value().clone()),
+                )
+            })
+            .for_each_concurrent(self.namespace.config.broadcast_concurrency_limit, |connection| async {
+                let _ = f(connection).await;
+            })
+            .await;
+    }
+
+    /// Sends the message encoded for each connection's packet codec to every
+    /// connection in `connection_ids`.
+    async fn send_messages<F, Fut>(
+        &self,
+        connection_ids: RoaringTreemap,
+        messages: FxHashMap<String, Arc<Message>>,
+        send: F,
+    ) where
+        F: Fn(Arc<WsIoServerConnection>, Arc<Message>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.for_each_connections(connection_ids, move |connection| {
+            let message = messages.get(connection.packet_codec().name()).cloned();
+            let send_future = message.map(|message| send(connection, message));
+            async move {
+                match send_future {
+                    Some(send_future) => send_future.await,
+                    None => Ok(()),
+                }
+            }
+        })
+        .await;
+    }
+
+    /// Sends the message encoded for each target's packet codec to every
+    /// target connection.
+    async fn send_per_packet_codec<F, Fut>(
+        &self,
+        encode: impl Fn(&dyn PacketCodec) -> Result<Arc<Message>>,
+        send: F,
+    ) -> Result<()>
+    where
+        F: Fn(Arc<WsIoServerConnection>, Arc<Message>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let target_connection_ids = self.target_connection_ids();
+        let messages = self.encode_per_packet_codec(&target_connection_ids, encode)?;
+        self.send_messages(target_connection_ids, messages, send).await;
+        Ok(())
+    }
+
+    fn target_connection_ids(&self) -> RoaringTreemap {
         let mut target_connection_ids = if self.include_rooms.is_empty() {
             (**self.namespace.connection_ids.load()).clone()
         } else {
@@ -73,28 +218,21 @@ impl WsIoServerNamespaceBroadcastOperator {
             target_connection_ids.remove(*exclude_connection_id);
         }
 
-        if target_connection_ids.is_empty() {
-            return;
-        }
+        target_connection_ids
+    }
 
-        iter(target_connection_ids)
-            .filter_map(|target_connection_id| {
-                ready(
-                    self.namespace
-                        .connections
-                        .get(&target_connection_id)
-                        .map(|entry| entry.value().clone()),
-                )
-            })
-            .for_each_concurrent(self.namespace.config.broadcast_concurrency_limit, |connection| async {
-                let _ = f(connection).await;
-            })
-            .await;
+    /// Target connection ids with a ready connection, leaving out connections
+    /// waiting to be recovered, whose rooms are restored as they were.
+    fn target_ready_connection_ids(&self) -> RoaringTreemap {
+        self.target_connection_ids()
+            .into_iter()
+            .filter(|connection_id| self.namespace.connections.contains_key(connection_id))
+            .collect()
     }
 
     // Public methods
     pub async fn close(self) {
-        self.for_each_target_connections(|connection| async move {
+        self.for_each_connections(self.target_connection_ids(), |connection| async move {
             connection.close();
             Ok(())
         })
@@ -102,36 +240,117 @@ impl WsIoServerNamespaceBroadcastOperator {
     }
 
     pub async fn disconnect(self) -> Result<()> {
-        let message = self.namespace.encode_packet_to_message(&WsIoPacket::new_disconnect())?;
-        self.for_each_target_connections(move |connection| {
-            let message = message.clone();
-            async move { connection.send_message(message).await }
-        })
-        .await;
-
-        Ok(())
+        self.send_per_packet_codec(
+            |packet_codec| encode_packet_to_message(packet_codec, &WsIoPacket::new_disconnect()),
+            |connection, message| async move { connection.send_message(message).await },
+        )
+        .await
     }
 
+    /// Emits an event to every target connection.
+    ///
+    /// Targets that dropped and wait to be recovered get the event buffered
+    /// and replayed once their client reconnects.
     pub async fn emit<D: Serialize>(self, event: impl AsRef<str>, data: Option<&D>) -> Result<()> {
         self.namespace.status.ensure(NamespaceStatus::Running, |status| {
             format!("Cannot emit in invalid status: {status:?}")
         })?;
 
-        let message = self.namespace.encode_packet_to_message(&WsIoPacket::new_event(
-            event.as_ref(),
-            data.map(|data| self.namespace.config.packet_codec.encode_data(data))
-                .transpose()?,
-        ))?;
+        let event = event.as_ref();
+        let mut target_connection_ids = self.target_connection_ids();
+        let messages = self.encode_per_packet_codec(&target_connection_ids, |packet_codec| {
+            encode_packet_to_message(
+                packet_codec,
+                &WsIoPacket::new_event(event, data.map(|data| packet_codec.encode_data(data)).transpose()?),
+            )
+        })?;
 
-        self.for_each_target_connections(move |connection| {
-            let message = message.clone();
-            async move { connection.emit_event_message(message).await }
+        self.buffer_recovery_messages(&mut target_connection_ids, &messages);
+        self.send_messages(target_connection_ids, messages, |connection, message| async move {
+            connection.emit_event_message(message).await
         })
         .await;
 
         Ok(())
     }
 
+    /// Emits `E` to every target connection with a payload whose type is checked
+    /// against its definition.
+    #[inline]
+    pub async fn emit_typed<E: WsIoEvent>(self, data: &E::Data) -> Result<()> {
+        self.emit(E::NAME, Some(data)).await
+    }
+
+    /// Emits an event to every target connection and collects their ack replies.
+    ///
+    /// The event is sent to every target before any reply is awaited, so each
+    /// connection gets its own ack id and the full duration set with
+    /// [`timeout`](Self::timeout), falling back to the namespace `ack_timeout`.
+    /// Returns one `(connection_id, reply)` pair per target once every target
+    /// has answered, failed or timed out. Targets waiting to be recovered cannot
+    /// answer, so they get an error reply.
+    pub async fn emit_with_ack<D: Serialize, R: DeserializeOwned + Send + 'static>(
+        self,
+        event: impl AsRef<str>,
+        data: Option<&D>,
+    ) -> Result<Vec<(u64, Result<R>)>> {
+        self.namespace.status.ensure(NamespaceStatus::Running, |status| {
+            format!("Cannot emit in invalid status: {status:?}")
+        })?;
+
+        let ack_timeout = self.ack_timeout.unwrap_or(self.namespace.config.ack_timeout);
+        let event = event.as_ref();
+        let target_connection_ids = self.target_connection_ids();
+        let packet_data_per_codec = self.encode_per_packet_codec(&target_connection_ids, |packet_codec| {
+            data.map(|data| packet_codec.encode_data(data)).transpose()
+        })?;
+
+        let mut replies = Vec::with_capacity(target_connection_ids.len() as usize);
+        let mut connections = Vec::with_capacity(replies.capacity());
+        for connection_id in &target_connection_ids {
+            match self.namespace.connection(connection_id) {
+                Some(connection) => connections.push(connection),
+                None => replies.push((
+                    connection_id,
+                    Err(anyhow!(
+                        "Connection {connection_id} is waiting to be recovered and cannot ack"
+                    )),
+                )),
+            }
+        }
+
+        // Send to every target first, so no target waits on the replies of others before getting the event
+        let sent_events = iter(connections)
+            .map(|connection| {
+                let packet_data = packet_data_per_codec.get(connection.packet_codec().name()).cloned();
+                async move {
+                    let sent_event = match packet_data {
+                        Some(packet_data) => connection.send_event_with_ack(event, packet_data).await,
+                        None => Err(anyhow!("No data encoded for connection {}", connection.id())),
+                    };
+
+                    (connection, sent_event)
+                }
+            })
+            .buffer_unordered(self.namespace.config.broadcast_concurrency_limit)
+            .collect::<Vec<_>>()
+            .await;
+
+        replies.extend(
+            join_all(sent_events.into_iter().map(|(connection, sent_event)| async move {
+                let reply = match sent_event {
+                    Ok((ack_id, ack_rx)) => connection.wait_for_ack(ack_id, ack_rx, ack_timeout).await,
+                    Err(err) => Err(err),
+                };
+
+                (connection.id(), reply)
+            }))
+            .await,
+        );
+
+        Ok(replies)
+    }
+
     #[inline]
     pub fn except(mut self, room_names: impl IntoIterator<Item = impl Into<String>>) -> Self {
         self.exclude_rooms.extend(room_names.into_iter().map(Into::into));
@@ -143,6 +362,98 @@ impl WsIoServerNamespaceBroadcastOperator {
         self
     }
 
+    /// Ready target connections, resolved like the targets of an emit.
+    pub fn fetch_connections(self) -> Vec<Arc<WsIoServerConnection>> {
+        self.target_connection_ids()
+            .into_iter()
+            .filter_map(|connection_id| self.namespace.connection(connection_id))
+            .collect()
+    }
+
+    /// Makes every ready target connection join `room_names`, merging the
+    /// targets into each room at once instead of joining them one by one.
+    ///
+    /// `namespace.to(["a"]).join(["b"])` adds every member of room `a` to room
+    /// `b`.
+    pub fn join(self, room_names: impl IntoIterator<Item = impl Into<String>>) {
+        let connection_ids = self.target_ready_connection_ids();
+        if connection_ids.is_empty() {
+            return;
+        }
+
+        // Keep the ids each room gains, so undoing them never drops an earlier membership
+        let room_names = room_names.into_iter().map(Into::into).collect::<Vec<String>>();
+        let added_connection_ids = room_names
+            .iter()
+            .map(|room_name| {
+                let mut room = self.namespace.rooms.entry(room_name.clone()).or_default();
+                let added_connection_ids = &connection_ids - &*room;
+                *room |= &added_connection_ids;
+                added_connection_ids
+            })
+            .collect::<Vec<_>>();
+
+        // Connections cleaned up since their ids were read already left their rooms, so take them out again
+        let mut stale_connection_ids = RoaringTreemap::new();
+        for connection_id in &connection_ids {
+            let is_joined = self
+                .namespace
+                .connections
+                .get(&connection_id)
+                .is_some_and(|connection| connection.extend_joined_rooms(&room_names));
+
+            if !is_joined {
+                stale_connection_ids.insert(connection_id);
+            }
+        }
+
+        if stale_connection_ids.is_empty() {
+            return;
+        }
+
+        for (room_name, added_connection_ids) in room_names.iter().zip(added_connection_ids) {
+            if let Some(mut room) = self.namespace.rooms.get_mut(room_name) {
+                *room -= added_connection_ids & &stale_connection_ids;
+            }
+
+            self.namespace.rooms.remove_if(room_name, |_, room| room.is_empty());
+        }
+    }
+
+    /// Makes every ready target connection leave `room_names`, removing the
+    /// targets from each room at once.
+    ///
+    /// `namespace.to(["a"]).leave(["a"])` empties room `a`.
+    pub fn leave(self, room_names: impl IntoIterator<Item = impl Into<String>>) {
+        let connection_ids = self.target_ready_connection_ids();
+        if connection_ids.is_empty() {
+            return;
+        }
+
+        let room_names = room_names.into_iter().map(Into::into).collect::<Vec<String>>();
+        for room_name in &room_names {
+            if let Some(mut room) = self.namespace.rooms.get_mut(room_name) {
+                *room -= &connection_ids;
+            }
+
+            self.namespace.rooms.remove_if(room_name, |_, room| room.is_empty());
+        }
+
+        for connection_id in &connection_ids {
+            if let Some(connection) = self.namespace.connections.get(&connection_id) {
+                connection.remove_joined_rooms(&room_names);
+            }
+        }
+    }
+
+    /// Sets how long [`emit_with_ack`](Self::emit_with_ack) waits for each
+    /// connection's ack.
+    #[inline]
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.ack_timeout = Some(duration);
+        self
+    }
+
     #[inline]
     pub fn to(mut self, room_names: impl IntoIterator<Item = impl Into<String>>) -> Self {
         self.include_rooms.extend(room_names.into_iter().map(Into::into));
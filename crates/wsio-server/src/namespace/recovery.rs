@@ -0,0 +1,230 @@
+use std::{
+    collections::VecDeque,
+    fmt::{
+        Debug as FmtDebug,
+        Formatter,
+        Result as FmtResult,
+    },
+    mem::take,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::core::traits::packet::codec::PacketCodec;
+
+// Functions
+/// Compares two secrets in time that only depends on their lengths, so a
+/// client probing tokens cannot learn how many leading bytes it got right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Generates the secret part of a session token from the OS random number
+/// generator, returning `None` when it is unavailable.
+pub(crate) fn generate_recovery_secret() -> Option<String> {
+    let mut bytes = [0; 16];
+    getrandom::fill(&mut bytes).ok()?;
+    Some(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Splits a session token into the connection id and the secret.
+#[inline]
+pub(crate) fn parse_recovery_token(token: &str) -> Option<(u64, &str)> {
+    let (id, secret) = token.split_once('.')?;
+    Some((id.parse().ok()?, secret))
+}
+
+// Enums
+pub(crate) enum RecoveryDrain {
+    Expired,
+    Messages(Vec<Arc<Message>>),
+    Restored,
+}
+
+// Structs
+#[derive(Default)]
+struct RecoverySessionState {
+    claimed: bool,
+    expired: bool,
+    messages: VecDeque<Arc<Message>>,
+    restored: bool,
+}
+
+/// What is kept of a connection that dropped without closing while its
+/// client may still recover it.
+pub(crate) struct WsIoServerRecoverySession {
+    id: u64,
+    packet_codec: Arc<dyn PacketCodec>,
+    rooms: Vec<String>,
+    secret: String,
+    state: Mutex<RecoverySessionState>,
+}
+
+impl FmtDebug for WsIoServerRecoverySession {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = self.state.lock();
+        f.debug_struct("WsIoServerRecoverySession")
+            .field("id", &self.id)
+            .field("packet_codec", &self.packet_codec)
+            .field("rooms", &self.rooms)
+            .field("secret", &"<secret>")
+            .field("claimed", &state.claimed)
+            .field("expired", &state.expired)
+            .field("messages_len", &state.messages.len())
+            .field("restored", &state.restored)
+            .finish()
+    }
+}
+
+impl WsIoServerRecoverySession {
+    #[inline]
+    pub(crate) fn new(id: u64, packet_codec: Arc<dyn PacketCodec>, rooms: Vec<String>, secret: String) -> Self {
+        Self {
+            id,
+            packet_codec,
+            rooms,
+            secret,
+            state: Mutex::new(RecoverySessionState::default()),
+        }
+    }
+
+    // Protected methods
+    /// Marks the session as claimed by a reconnecting client presenting
+    /// `secret`, failing when it is wrong or another client claimed it first.
+    pub(crate) fn claim(&self, secret: &str) -> bool {
+        let mut state = self.state.lock();
+        if state.claimed || state.expired || !constant_time_eq(secret.as_bytes(), self.secret.as_bytes()) {
+            return false;
+        }
+
+        state.claimed = true;
+        true
+    }
+
+    /// Takes the buffered messages, or, once none are left, marks the session
+    /// as restored and runs `on_restore` before any further message can be
+    /// buffered.
+    pub(crate) fn drain(&self, on_restore: impl FnOnce()) -> RecoveryDrain {
+        let mut state = self.state.lock();
+        if state.expired {
+            return RecoveryDrain::Expired;
+        }
+
+        if !state.messages.is_empty() {
+            return RecoveryDrain::Messages(take(&mut state.messages).into());
+        }
+
+        state.restored = true;
+        on_restore();
+        RecoveryDrain::Restored
+    }
+
+    /// Marks the session as expired, returning `false` when it was already
+    /// restored or expired.
+    pub(crate) fn expire(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.restored || state.expired {
+            return false;
+        }
+
+        state.expired = true;
+        state.messages.clear();
+        true
+    }
+
+    #[inline]
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    #[inline]
+    pub(crate) fn packet_codec(&self) -> &dyn PacketCodec {
+        &*self.packet_codec
+    }
+
+    /// Buffers `message`, returning `false` when the session was restored in
+    /// the meantime or the buffer already holds `max_messages` messages.
+    pub(crate) fn push(&self, message: Arc<Message>, max_messages: usize) -> bool {
+        let mut state = self.state.lock();
+        if state.restored || state.expired || state.messages.len() >= max_messages {
+            return false;
+        }
+
+        state.messages.push_back(message);
+        true
+    }
+
+    /// Lets another client claim the session after the one that claimed it
+    /// failed to restore it.
+    #[inline]
+    pub(crate) fn release(&self) {
+        self.state.lock().claimed = false;
+    }
+
+    #[inline]
+    pub(crate) fn rooms(&self) -> &[String] {
+        &self.rooms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::packet::codecs::WsIoPacketCodec;
+
+    fn create_test_session() -> WsIoServerRecoverySession {
+        WsIoServerRecoverySession::new(
+            7,
+            Arc::new(WsIoPacketCodec::SerdeJson),
+            vec!["room".into()],
+            "secret".into(),
+        )
+    }
+
+    #[test]
+    fn test_recovery_token() {
+        let secret = generate_recovery_secret().unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_ne!(secret, generate_recovery_secret().unwrap());
+        assert!(constant_time_eq(secret.as_bytes(), secret.as_bytes()));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secrets"));
+
+        assert_eq!(parse_recovery_token("7.abc"), Some((7, "abc")));
+        assert_eq!(parse_recovery_token("x.abc"), None);
+        assert_eq!(parse_recovery_token("7abc"), None);
+    }
+
+    #[test]
+    fn test_recovery_session_claim_and_drain() {
+        let session = create_test_session();
+        assert!(!session.claim("wrong"));
+        assert!(session.claim("secret"));
+        assert!(!session.claim("secret"));
+        session.release();
+        assert!(session.claim("secret"));
+
+        let message = Arc::new(Message::Text("event".into()));
+        assert!(session.push(message.clone(), 1));
+        assert!(!session.push(message, 1));
+        assert!(matches!(session.drain(|| {}), RecoveryDrain::Messages(messages) if messages.len() == 1));
+
+        let mut restored = false;
+        assert!(matches!(session.drain(|| restored = true), RecoveryDrain::Restored));
+        assert!(restored);
+        assert!(!session.push(Arc::new(Message::Text("late".into())), 1));
+        assert!(!session.expire());
+    }
+
+    #[test]
+    fn test_recovery_session_expire() {
+        let session = create_test_session();
+        assert!(session.push(Arc::new(Message::Text("event".into())), 1));
+        assert!(session.expire());
+        assert!(!session.expire());
+        assert!(!session.claim("secret"));
+        assert!(matches!(session.drain(|| {}), RecoveryDrain::Expired));
+    }
+}
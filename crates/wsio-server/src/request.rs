@@ -11,6 +11,7 @@ use http::{
         CONNECTION,
         SEC_WEBSOCKET_ACCEPT,
         SEC_WEBSOCKET_KEY,
+        SEC_WEBSOCKET_PROTOCOL,
         SEC_WEBSOCKET_VERSION,
         UPGRADE,
     },
@@ -19,7 +20,17 @@ use hyper::upgrade::OnUpgrade;
 use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
 use url::form_urlencoded;
 
-use crate::runtime::WsIoServerRuntime;
+use crate::{
+    core::{
+        protocol::WSIO_SESSION_HEADER,
+        traits::packet::codec::PacketCodec,
+    },
+    namespace::WsIoServerNamespace,
+    runtime::WsIoServerRuntime,
+};
+
+// Constants/Statics
+const PACKET_CODEC_PROTOCOL_PREFIX: &str = "wsio.";
 
 // Functions
 #[inline]
@@ -68,15 +79,36 @@ pub(super) async fn dispatch_request<ReqBody, ResBody: Default, E: Send>(
         .uri()
         .query()
         .and_then(|q| form_urlencoded::parse(q.as_bytes()).find(|(k, _)| k == "namespace"))
+        .map(|(k, v)| (k, v.into_owned()))
     else {
         return respond(StatusCode::BAD_REQUEST);
     };
 
-    // Get namespace
-    let Some(namespace) = runtime.get_namespace(&namespace_path) else {
+    // Get namespace, or the dynamic namespace creating the child for this path once the connection upgrades
+    let Some(namespace) = runtime.resolve_namespace(&namespace_path) else {
         return respond(StatusCode::NOT_FOUND);
     };
 
+    // Select packet codec
+    let requested_packet_codec = request
+        .uri()
+        .query()
+        .and_then(|q| form_urlencoded::parse(q.as_bytes()).find(|(k, _)| k == "codec"))
+        .map(|(_, name)| name);
+
+    let Some((packet_codec, ws_protocol_header)) =
+        select_packet_codec(&request, &namespace, requested_packet_codec.as_deref())
+    else {
+        return respond(StatusCode::BAD_REQUEST);
+    };
+
+    // Get the session token of a client recovering a dropped connection
+    let recovery_token = request
+        .headers()
+        .get(WSIO_SESSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+
     // Generate accept key
     let ws_accept_key = derive_accept_key(ws_sec_key.as_bytes());
 
@@ -86,7 +118,14 @@ pub(super) async fn dispatch_request<ReqBody, ResBody: Default, E: Send>(
     };
 
     namespace
-        .handle_on_upgrade_request(request.headers().clone(), on_upgrade, request.uri().clone())
+        .handle_on_upgrade_request(
+            request.headers().clone(),
+            on_upgrade,
+            packet_codec,
+            namespace_path,
+            recovery_token,
+            request.uri().clone(),
+        )
         .await;
 
     // Build websocket accept header
@@ -102,11 +141,59 @@ pub(super) async fn dispatch_request<ReqBody, ResBody: Default, E: Send>(
     let headers = response.headers_mut();
     headers.insert(CONNECTION, HeaderValue::from_static("Upgrade"));
     headers.insert(SEC_WEBSOCKET_ACCEPT, ws_accept_header);
+    if let Some(ws_protocol_header) = ws_protocol_header {
+        headers.insert(SEC_WEBSOCKET_PROTOCOL, ws_protocol_header);
+    }
+
     headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
 
     Ok(response)
 }
 
+/// Selects the connection's packet codec.
+///
+/// A `codec` query parameter wins over `wsio.<name>` `Sec-WebSocket-Protocol`
+/// tokens, and the namespace default is used when neither is present. Returns
+/// `None` when the client asked for codecs the namespace does not accept. The
+/// second value is the protocol token to echo back when the codec was picked
+/// from the header.
+fn select_packet_codec<ReqBody>(
+    request: &Request<ReqBody>,
+    namespace: &WsIoServerNamespace,
+    requested_packet_codec: Option<&str>,
+) -> Option<(Arc<dyn PacketCodec>, Option<HeaderValue>)> {
+    if requested_packet_codec.is_some() {
+        return namespace
+            .packet_codec(requested_packet_codec)
+            .map(|packet_codec| (packet_codec.clone(), None));
+    }
+
+    let mut has_requested_protocol = false;
+    for value in request.headers().get_all(SEC_WEBSOCKET_PROTOCOL) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+
+        for token in value.split(',').map(str::trim) {
+            let Some(name) = token.strip_prefix(PACKET_CODEC_PROTOCOL_PREFIX) else {
+                continue;
+            };
+
+            has_requested_protocol = true;
+            if let Some(packet_codec) = namespace.packet_codec(Some(name)) {
+                return Some((packet_codec.clone(), HeaderValue::from_str(token).ok()));
+            }
+        }
+    }
+
+    match has_requested_protocol {
+        true => None,
+        false => namespace
+            .packet_codec(None)
+            .map(|packet_codec| (packet_codec.clone(), None)),
+    }
+}
+
 #[inline]
 fn respond<ResBody: Default, E: Send>(status: StatusCode) -> Result<Response<ResBody>, E> {
     let mut response = Response::new(ResBody::default());
@@ -121,7 +208,10 @@ mod tests {
     use http::header::CONNECTION;
 
     use super::*;
-    use crate::WsIoServer;
+    use crate::{
+        WsIoServer,
+        core::packet::codecs::WsIoPacketCodec,
+    };
 
     fn valid_upgrade_request(uri: &str) -> Request<()> {
         Request::builder()
@@ -246,4 +336,79 @@ mod tests {
             StatusCode::INTERNAL_SERVER_ERROR
         );
     }
+
+    #[tokio::test]
+    async fn dispatch_request_rejects_unsupported_codec_query() {
+        let server = WsIoServer::builder().build();
+        server.new_namespace_builder("/socket").register().unwrap();
+
+        assert_eq!(
+            dispatch_status(valid_upgrade_request("/ws.io?namespace=/socket&codec=unknown"), &server).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn select_packet_codec_prefers_query_over_protocol_header() {
+        let server = WsIoServer::builder().build();
+        let namespace = server
+            .new_namespace_builder("/socket")
+            .add_packet_codec(WsIoPacketCodec::Msgpack)
+            .register()
+            .unwrap();
+
+        let request = Request::builder()
+            .header(SEC_WEBSOCKET_PROTOCOL, "wsio.json")
+            .body(())
+            .unwrap();
+
+        let (packet_codec, ws_protocol_header) = select_packet_codec(&request, &namespace, Some("msgpack")).unwrap();
+        assert_eq!(packet_codec.name(), "msgpack");
+        assert!(ws_protocol_header.is_none());
+    }
+
+    #[tokio::test]
+    async fn select_packet_codec_echoes_first_supported_protocol_token() {
+        let server = WsIoServer::builder().build();
+        let namespace = server
+            .new_namespace_builder("/socket")
+            .add_packet_codec(WsIoPacketCodec::Msgpack)
+            .register()
+            .unwrap();
+
+        let request = Request::builder()
+            .header(SEC_WEBSOCKET_PROTOCOL, "chat, wsio.unknown, wsio.msgpack, wsio.json")
+            .body(())
+            .unwrap();
+
+        let (packet_codec, ws_protocol_header) = select_packet_codec(&request, &namespace, None).unwrap();
+        assert_eq!(packet_codec.name(), "msgpack");
+        assert_eq!(ws_protocol_header.unwrap(), "wsio.msgpack");
+    }
+
+    #[tokio::test]
+    async fn select_packet_codec_falls_back_to_default_without_wsio_tokens() {
+        let server = WsIoServer::builder().build();
+        let namespace = server.new_namespace_builder("/socket").register().unwrap();
+        let request = Request::builder()
+            .header(SEC_WEBSOCKET_PROTOCOL, "chat")
+            .body(())
+            .unwrap();
+
+        let (packet_codec, ws_protocol_header) = select_packet_codec(&request, &namespace, None).unwrap();
+        assert_eq!(packet_codec.name(), "json");
+        assert!(ws_protocol_header.is_none());
+    }
+
+    #[tokio::test]
+    async fn select_packet_codec_rejects_unsupported_protocol_tokens() {
+        let server = WsIoServer::builder().build();
+        let namespace = server.new_namespace_builder("/socket").register().unwrap();
+        let request = Request::builder()
+            .header(SEC_WEBSOCKET_PROTOCOL, "wsio.msgpack")
+            .body(())
+            .unwrap();
+
+        assert!(select_packet_codec(&request, &namespace, None).is_none());
+    }
 }
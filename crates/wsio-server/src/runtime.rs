@@ -4,25 +4,28 @@ use anyhow::{
     Result,
     bail,
 };
-use arc_swap::ArcSwap;
 use futures_util::future::join_all;
 use kikiutils::{
     atomic::enum_cell::AtomicEnumCell,
-    types::fx_collections::FxHashMap,
+    types::fx_collections::{
+        FxDashMap,
+        FxHashMap,
+    },
 };
 use num_enum::{
     IntoPrimitive,
     TryFromPrimitive,
 };
 use parking_lot::RwLock;
-use roaring::RoaringTreemap;
 use serde::Serialize;
 
 use crate::{
     config::WsIoServerConfig,
+    connection::WsIoServerConnection,
     namespace::{
         WsIoServerNamespace,
         builder::WsIoServerNamespaceBuilder,
+        matcher::WsIoServerNamespaceMatcher,
     },
 };
 
@@ -39,7 +42,8 @@ pub(crate) enum WsIoServerRuntimeStatus {
 #[derive(Debug)]
 pub(crate) struct WsIoServerRuntime {
     pub(crate) config: WsIoServerConfig,
-    connection_ids: ArcSwap<RoaringTreemap>,
+    connections: FxDashMap<u64, Arc<WsIoServerConnection>>,
+    dynamic_namespaces: RwLock<Vec<Arc<WsIoServerNamespace>>>,
     namespaces: RwLock<FxHashMap<String, Arc<WsIoServerNamespace>>>,
     pub(crate) status: AtomicEnumCell<WsIoServerRuntimeStatus>,
 }
@@ -48,26 +52,51 @@ impl WsIoServerRuntime {
     pub(crate) fn new(config: WsIoServerConfig) -> Arc<Self> {
         Arc::new(Self {
             config,
-            connection_ids: ArcSwap::new(Arc::new(RoaringTreemap::new())),
+            connections: FxDashMap::default(),
+            dynamic_namespaces: RwLock::new(Vec::new()),
             namespaces: RwLock::new(FxHashMap::default()),
             status: AtomicEnumCell::new(WsIoServerRuntimeStatus::Running),
         })
     }
 
     // Private methods
+    /// Namespaces that hold connections: the registered ones and the children
+    /// of dynamic namespaces.
+    fn clone_connection_namespaces(&self) -> Vec<Arc<WsIoServerNamespace>> {
+        let mut namespaces = self.namespaces.read().values().cloned().collect::<Vec<_>>();
+        for dynamic_namespace in self.dynamic_namespaces.read().iter() {
+            namespaces.extend(dynamic_namespace.clone_children());
+        }
+
+        namespaces
+    }
+
+    /// Registered namespaces, dynamic ones included.
     #[inline]
     fn clone_namespaces(&self) -> Vec<Arc<WsIoServerNamespace>> {
-        self.namespaces.read().values().cloned().collect()
+        let mut namespaces = self.namespaces.read().values().cloned().collect::<Vec<_>>();
+        namespaces.extend(self.dynamic_namespaces.read().iter().cloned());
+        namespaces
     }
 
     // Protected methods
+    #[inline]
+    pub(crate) fn connection(&self, id: u64) -> Option<Arc<WsIoServerConnection>> {
+        self.connections.get(&id).map(|entry| entry.value().clone())
+    }
+
     #[inline]
     pub(crate) fn connection_count(&self) -> usize {
-        self.connection_ids.load().len() as usize
+        self.connections.len()
     }
 
     pub(crate) async fn close_all(&self) {
-        join_all(self.clone_namespaces().iter().map(|namespace| namespace.close_all())).await;
+        join_all(
+            self.clone_connection_namespaces()
+                .iter()
+                .map(|namespace| namespace.close_all()),
+        )
+        .await;
     }
 
     pub(crate) async fn emit<D: Serialize>(&self, event: &str, data: Option<&D>) -> Result<()> {
@@ -76,7 +105,7 @@ impl WsIoServerRuntime {
         })?;
 
         join_all(
-            self.clone_namespaces()
+            self.clone_connection_namespaces()
                 .iter()
                 .map(|namespace| namespace.emit(event, data)),
         )
@@ -87,29 +116,51 @@ impl WsIoServerRuntime {
 
     pub(crate) async fn disconnect_all(&self) {
         join_all(
-            self.clone_namespaces()
+            self.clone_connection_namespaces()
                 .iter()
                 .map(|namespace| namespace.disconnect_all()),
         )
         .await;
     }
 
-    #[inline]
+    /// Returns the namespace registered under `path`, the dynamic namespace
+    /// whose matcher source is `path`, or an existing child for `path`.
     pub(crate) fn get_namespace(&self, path: &str) -> Option<Arc<WsIoServerNamespace>> {
-        self.namespaces.read().get(path).cloned()
+        if let Some(namespace) = self.namespaces.read().get(path) {
+            return Some(namespace.clone());
+        }
+
+        let dynamic_namespaces = self.dynamic_namespaces.read();
+        dynamic_namespaces
+            .iter()
+            .find(|dynamic_namespace| dynamic_namespace.path() == path)
+            .cloned()
+            .or_else(|| {
+                dynamic_namespaces
+                    .iter()
+                    .find_map(|dynamic_namespace| dynamic_namespace.child(path))
+            })
     }
 
     #[inline]
-    pub(crate) fn insert_connection_id(&self, id: u64) {
-        self.connection_ids.rcu(|old_connection_ids| {
-            let mut new_connection_ids = (**old_connection_ids).clone();
-            new_connection_ids.insert(id);
-            new_connection_ids
-        });
+    pub(crate) fn insert_connection(&self, connection: Arc<WsIoServerConnection>) {
+        self.connections.insert(connection.id(), connection);
     }
 
-    #[inline]
     pub(crate) fn insert_namespace(&self, namespace: Arc<WsIoServerNamespace>) -> Result<()> {
+        if namespace.is_dynamic() {
+            let mut dynamic_namespaces = self.dynamic_namespaces.write();
+            if dynamic_namespaces
+                .iter()
+                .any(|dynamic_namespace| dynamic_namespace.path() == namespace.path())
+            {
+                bail!("Dynamic namespace {} already exists", namespace.path());
+            }
+
+            dynamic_namespaces.push(namespace);
+            return Ok(());
+        }
+
         if self.namespaces.read().contains_key(namespace.path()) {
             bail!("Namespace {} already exists", namespace.path());
         }
@@ -118,9 +169,19 @@ impl WsIoServerRuntime {
         Ok(())
     }
 
+    /// Number of registered namespaces, dynamic ones included and their
+    /// children not.
     #[inline]
     pub(crate) fn namespace_count(&self) -> usize {
-        self.namespaces.read().len()
+        self.namespaces.read().len() + self.dynamic_namespaces.read().len()
+    }
+
+    #[inline]
+    pub(crate) fn new_dynamic_namespace_builder(
+        self: &Arc<Self>,
+        matcher: WsIoServerNamespaceMatcher,
+    ) -> WsIoServerNamespaceBuilder {
+        WsIoServerNamespaceBuilder::new_dynamic(matcher, self.clone())
     }
 
     #[inline]
@@ -129,20 +190,45 @@ impl WsIoServerRuntime {
     }
 
     #[inline]
-    pub(crate) fn remove_connection_id(&self, id: u64) {
-        self.connection_ids.rcu(|old_connection_ids| {
-            let mut new_connection_ids = (**old_connection_ids).clone();
-            new_connection_ids.remove(id);
-            new_connection_ids
-        });
+    pub(crate) fn remove_connection(&self, id: u64) {
+        self.connections.remove(&id);
     }
 
+    /// Removes and shuts down the namespace registered under `path`, the
+    /// dynamic namespace whose matcher source is `path` along with its
+    /// children, or the child for `path`.
     pub(crate) async fn remove_namespace(&self, path: &str) {
-        let Some(namespace) = self.namespaces.write().remove(path) else {
-            return;
-        };
+        let namespace = self.namespaces.write().remove(path).or_else(|| {
+            let mut dynamic_namespaces = self.dynamic_namespaces.write();
+            match dynamic_namespaces
+                .iter()
+                .position(|dynamic_namespace| dynamic_namespace.path() == path)
+            {
+                Some(index) => Some(dynamic_namespaces.remove(index)),
+                None => dynamic_namespaces
+                    .iter()
+                    .find_map(|dynamic_namespace| dynamic_namespace.remove_child(path)),
+            }
+        });
+
+        if let Some(namespace) = namespace {
+            namespace.shutdown().await;
+        }
+    }
+
+    /// Returns the namespace handling a client connecting to `path`: the one
+    /// registered under `path`, or the first dynamic namespace whose matcher
+    /// accepts it, which creates the child once the connection upgrades.
+    pub(crate) fn resolve_namespace(&self, path: &str) -> Option<Arc<WsIoServerNamespace>> {
+        if let Some(namespace) = self.namespaces.read().get(path) {
+            return Some(namespace.clone());
+        }
 
-        namespace.shutdown().await;
+        self.dynamic_namespaces
+            .read()
+            .iter()
+            .find(|dynamic_namespace| dynamic_namespace.matches_child(path))
+            .cloned()
     }
 
     pub(crate) async fn shutdown(&self) {
@@ -161,14 +247,32 @@ impl WsIoServerRuntime {
 mod tests {
     use std::time::Duration;
 
+    use http::{
+        HeaderMap,
+        Uri,
+    };
     use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
 
     use super::*;
-    use crate::core::packet::codecs::WsIoPacketCodec;
+    use crate::{
+        config::StateMap,
+        core::{
+            error::WsIoProtocolViolationPolicy,
+            event::execution::WsIoHandlerExecutionMode,
+            packet::codecs::WsIoPacketCodec,
+        },
+    };
 
     fn create_test_config() -> WsIoServerConfig {
         WsIoServerConfig {
+            ack_timeout: Duration::from_secs(3),
             broadcast_concurrency_limit: 16,
+            compression_threshold: None,
+            event_handler_timeout: None,
+            event_middleware_timeout: Duration::from_secs(3),
+            handler_execution_mode: WsIoHandlerExecutionMode::Concurrent,
+            heartbeat_interval: None,
+            heartbeat_max_missed: 3,
             http_request_upgrade_timeout: Duration::from_secs(3),
             init_request_handler_timeout: Duration::from_secs(3),
             init_response_handler_timeout: Duration::from_secs(3),
@@ -176,8 +280,14 @@ mod tests {
             middleware_execution_timeout: Duration::from_secs(3),
             on_close_handler_timeout: Duration::from_secs(3),
             on_connect_handler_timeout: Duration::from_secs(3),
-            packet_codec: WsIoPacketCodec::SerdeJson,
+            on_disconnect_handler_timeout: Duration::from_secs(3),
+            packet_codec: Arc::new(WsIoPacketCodec::SerdeJson),
+            ping_interval: None,
+            protocol_violation_policy: WsIoProtocolViolationPolicy::Disconnect,
+            recovery_buffer_size: 1024,
+            recovery_window: None,
             request_path: "/socket".into(),
+            states: StateMap::default(),
             websocket_config: WebSocketConfig::default(),
         }
     }
@@ -213,14 +323,89 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_runtime_connection_id_tracking() {
+    async fn test_runtime_resolves_dynamic_namespace_children() {
         let runtime = WsIoServerRuntime::new(create_test_config());
-        runtime.insert_connection_id(1);
-        runtime.insert_connection_id(2);
+        runtime.new_namespace_builder("/workspace/static").register().unwrap();
+        let dynamic_namespace = runtime
+            .new_dynamic_namespace_builder(WsIoServerNamespaceMatcher::pattern("/workspace/{id}").unwrap())
+            .register()
+            .unwrap();
+
+        assert_eq!(runtime.namespace_count(), 2);
+        assert!(dynamic_namespace.is_dynamic());
+        assert!(runtime.get_namespace("/workspace/1").is_none());
+
+        // Registered namespaces win over dynamic ones, and resolving creates no child
+        assert!(!runtime.resolve_namespace("/workspace/static").unwrap().is_dynamic());
+        assert!(runtime.resolve_namespace("/other/1").is_none());
+        assert!(Arc::ptr_eq(
+            &runtime.resolve_namespace("/workspace/1").unwrap(),
+            &dynamic_namespace
+        ));
+        assert!(dynamic_namespace.children().is_empty());
+
+        let child = dynamic_namespace.acquire_child("/workspace/1").unwrap();
+        assert_eq!(child.path(), "/workspace/1");
+        assert_eq!(child.param("id"), Some("1"));
+        assert!(Arc::ptr_eq(
+            &child,
+            &dynamic_namespace.acquire_child("/workspace/1").unwrap()
+        ));
+        assert!(Arc::ptr_eq(&child, &runtime.get_namespace("/workspace/1").unwrap()));
+        assert!(Arc::ptr_eq(&child.config, &dynamic_namespace.config));
+        assert_eq!(runtime.namespace_count(), 2);
+
+        // The child is dropped once its last connection is released
+        child.release();
+        assert!(runtime.get_namespace("/workspace/1").is_some());
+        child.release();
+        assert!(runtime.get_namespace("/workspace/1").is_none());
+
+        let duplicate = runtime
+            .new_dynamic_namespace_builder(WsIoServerNamespaceMatcher::pattern("/workspace/{id}").unwrap())
+            .register();
+
+        assert!(duplicate.is_err());
+
+        dynamic_namespace.acquire_child("/workspace/1").unwrap();
+        runtime.remove_namespace("/workspace/1").await;
+        assert!(dynamic_namespace.children().is_empty());
+
+        dynamic_namespace.acquire_child("/workspace/2").unwrap();
+        runtime.remove_namespace("/workspace/{id}").await;
+        assert_eq!(runtime.namespace_count(), 1);
+        assert!(runtime.resolve_namespace("/workspace/2").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_runtime_connection_tracking() {
+        let runtime = WsIoServerRuntime::new(create_test_config());
+        let namespace = runtime.new_namespace_builder("/test").register().unwrap();
+        let packet_codec = namespace.packet_codec(None).unwrap().clone();
+        let connections = [(), ()].map(|_| {
+            WsIoServerConnection::new(
+                HeaderMap::new(),
+                namespace.clone(),
+                packet_codec.clone(),
+                None,
+                Uri::from_static("http://localhost"),
+            )
+            .0
+        });
+
+        for connection in &connections {
+            runtime.insert_connection(connection.clone());
+        }
+
         assert_eq!(runtime.connection_count(), 2);
+        assert!(Arc::ptr_eq(
+            &runtime.connection(connections[0].id()).unwrap(),
+            &connections[0]
+        ));
 
-        runtime.remove_connection_id(1);
+        runtime.remove_connection(connections[0].id());
         assert_eq!(runtime.connection_count(), 1);
+        assert!(runtime.connection(connections[0].id()).is_none());
     }
 
     #[tokio::test]
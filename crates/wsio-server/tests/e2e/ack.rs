@@ -0,0 +1,258 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::{
+    sync::{
+        Mutex,
+        oneshot::channel,
+    },
+    time::{
+        Instant,
+        sleep,
+        timeout,
+    },
+};
+use wsio_client::WsIoClient;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_client_ready,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_client_emit_with_ack() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(|ctx| async move {
+            ctx.on_with_ack("sum", |_ctx, data: Arc<(u32, u32)>| async move { Ok(data.0 + data.1) });
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+
+    let sum: u32 = client.emit_with_ack("sum", Some(&(2, 3))).await.unwrap();
+    assert_eq!(sum, 5);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_client_emit_with_ack_fails_when_server_handler_fails() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_with_ack::<_, _, (), u32>("sum", |_ctx, _data| async { anyhow::bail!("no sums today") })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+
+    // The error ack fails the emit long before the 10s ack timeout
+    let started_at = Instant::now();
+    let result = client.emit_with_ack::<(), u32>("sum", None).await;
+    assert!(started_at.elapsed() < Duration::from_secs(1));
+    assert!(result.unwrap_err().to_string().contains("failed on the peer: internal"));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_server_emit_with_ack() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let (tx, rx) = channel();
+    let tx = Arc::new(Mutex::new(Some(tx)));
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_ready(move |ctx| {
+            let tx = tx.clone();
+            async move {
+                let reply = ctx.emit_with_ack::<_, String>("greet", Some(&"server")).await;
+                if let Some(sender) = tx.lock().await.take() {
+                    let _ = sender.send(reply.map_err(|err| err.to_string()));
+                }
+
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let client = WsIoClient::builder(ws_url.as_str()).unwrap().build();
+    client.on_with_ack(
+        "greet",
+        |_ctx, name: Arc<String>| async move { Ok(format!("hello {name}")) },
+    );
+
+    client.connect().await;
+    wait_for_client_ready(&client).await;
+
+    let reply = timeout(Duration::from_secs(2), rx)
+        .await
+        .expect("Test timed out waiting for ack reply")
+        .expect("Channel closed");
+
+    assert_eq!(reply.unwrap(), "hello server");
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_emit_with_ack_fails_without_ack_handler() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on("plain", |_ctx, _data: Arc<()>| async { Ok(()) })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+
+    // Neither event has a handler that could answer, so the server fails the
+    // acks instead of leaving them to time out
+    let started_at = Instant::now();
+    for event in ["plain", "missing"] {
+        let result = client.emit_with_ack::<(), ()>(event, None).await;
+        assert!(result.unwrap_err().to_string().contains("no_ack_handler"));
+    }
+
+    assert!(started_at.elapsed() < Duration::from_secs(1));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_emit_with_ack_times_out_on_slow_handler() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_with_ack("slow", |_ctx, _data: Arc<()>| async {
+            sleep(Duration::from_secs(1)).await;
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .ack_timeout(Duration::from_millis(100))
+        .build();
+
+    client.connect().await;
+    wait_for_client_ready(&client).await;
+
+    let result = client.emit_with_ack::<(), ()>("slow", None).await;
+    assert!(result.unwrap_err().to_string().contains("Timed out"));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_broadcast_emit_with_ack() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(|ctx| async move {
+            ctx.join(["acks"]);
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let client_a = create_connected_client(&ws_url).await;
+    client_a.on_with_ack("whoami", |_ctx, _data: Arc<()>| async move { Ok("a") });
+
+    // Client b answers too late, so its ack times out
+    let client_b = create_connected_client(&ws_url).await;
+    client_b.on_with_ack("whoami", |_ctx, _data: Arc<()>| async move {
+        sleep(Duration::from_secs(1)).await;
+        Ok("b")
+    });
+
+    wait_for_condition(|| namespace.connection_count() == 2).await.unwrap();
+
+    let started_at = Instant::now();
+    let replies = namespace
+        .to(["acks"])
+        .timeout(Duration::from_millis(200))
+        .emit_with_ack::<(), String>("whoami", None)
+        .await
+        .unwrap();
+
+    assert!(started_at.elapsed() < Duration::from_secs(2));
+    assert_eq!(replies.len(), 2);
+
+    let answered: Vec<_> = replies.iter().filter_map(|(_, reply)| reply.as_ref().ok()).collect();
+    assert_eq!(answered, ["a"]);
+
+    let timed_out = replies
+        .iter()
+        .filter(|(_, reply)| reply.as_ref().is_err_and(|err| err.to_string().contains("Timed out")))
+        .count();
+
+    assert_eq!(timed_out, 1);
+
+    cleanup_e2e(vec![client_a, client_b], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_broadcast_emit_with_ack_sends_before_waiting() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    // Only one target is sent to at a time, yet no target waits for another's ack
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .broadcast_concurrency_limit(1)
+        .on_connect(|ctx| async move {
+            ctx.join(["acks"]);
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let mut clients = Vec::new();
+    for _ in 0..3 {
+        let client = create_connected_client(&ws_url).await;
+        client.on_with_ack("silent", |_ctx, _data: Arc<()>| async {
+            sleep(Duration::from_secs(1)).await;
+            Ok(())
+        });
+
+        clients.push(client);
+    }
+
+    wait_for_condition(|| namespace.connection_count() == 3).await.unwrap();
+
+    let started_at = Instant::now();
+    let replies = namespace
+        .to(["acks"])
+        .timeout(Duration::from_millis(300))
+        .emit_with_ack::<(), ()>("silent", None)
+        .await
+        .unwrap();
+
+    assert!(started_at.elapsed() < Duration::from_millis(600));
+    assert_eq!(replies.len(), 3);
+    assert!(
+        replies
+            .iter()
+            .all(|(_, reply)| reply.as_ref().is_err_and(|err| err.to_string().contains("Timed out")))
+    );
+
+    cleanup_e2e(clients, server_task).await;
+}
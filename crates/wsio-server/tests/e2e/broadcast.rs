@@ -426,3 +426,71 @@ async fn test_e2e_server_connection_count() {
 
     cleanup_e2e(vec![client1, client2], server_task).await;
 }
+
+#[tokio::test]
+async fn test_e2e_broadcast_operator_room_membership() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let server_namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(|ctx| async move {
+            ctx.on("join_room", |event_ctx, room: Arc<String>| async move {
+                event_ctx.join([room.as_str()]);
+                Ok(())
+            });
+
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let client_a = create_connected_client(&ws_url).await;
+    let client_b = create_connected_client(&ws_url).await;
+    let client_c = create_connected_client(&ws_url).await;
+
+    client_a.emit("join_room", Some(&"old")).await.unwrap();
+    client_b.emit("join_room", Some(&"old")).await.unwrap();
+    client_c.emit("join_room", Some(&"new")).await.unwrap();
+
+    wait_for_condition(|| server_namespace.room_size("old") == 2 && server_namespace.room_size("new") == 1)
+        .await
+        .expect("clients should join their rooms");
+
+    // Move every member of "old" into "new"
+    server_namespace.to(["old"]).join(["new"]);
+    server_namespace.to(["old"]).leave(["old"]);
+
+    assert_eq!(server_namespace.room_size("new"), 3);
+    assert_eq!(server_namespace.rooms(), ["new"]);
+
+    let moved_connections = server_namespace.to(["new"]).fetch_connections();
+    assert_eq!(moved_connections.len(), 3);
+    assert!(moved_connections.iter().all(|connection| connection.rooms() == ["new"]));
+
+    // Targets resolve like emits, exclusions included
+    let excluded_id = moved_connections[0].id();
+    let remaining_connections = server_namespace
+        .to(["new"])
+        .except_connection_ids([excluded_id])
+        .fetch_connections();
+
+    assert_eq!(remaining_connections.len(), 2);
+    assert!(
+        remaining_connections
+            .iter()
+            .all(|connection| connection.id() != excluded_id)
+    );
+
+    // Moved members receive broadcasts to their new room
+    let received = Arc::new(AtomicUsize::new(0));
+    for client in [&client_a, &client_b, &client_c] {
+        register_unit_counter(client, "new_msg", received.clone());
+    }
+
+    server_namespace.to(["new"]).emit::<()>("new_msg", None).await.unwrap();
+    wait_for_condition(|| received.load(Ordering::SeqCst) == 3)
+        .await
+        .expect("every moved client should receive the room broadcast");
+
+    cleanup_e2e(vec![client_a, client_b, client_c], server_task).await;
+}
@@ -0,0 +1,99 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::time::sleep;
+use wsio_client::WsIoClient;
+use wsio_server::core::packet::codecs::WsIoPacketCodec;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    setup_server,
+    wait_for_client_ready,
+    wait_for_condition,
+};
+
+async fn create_connected_client_with_codec(ws_url: &str, packet_codec: WsIoPacketCodec) -> WsIoClient {
+    let client = WsIoClient::builder(ws_url).unwrap().packet_codec(packet_codec).build();
+    client.connect().await;
+    wait_for_client_ready(&client).await;
+    client
+}
+
+#[tokio::test]
+async fn test_e2e_codec_negotiation_per_connection() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let connection_codecs = Arc::new(Mutex::new(Vec::new()));
+    let connection_codecs_clone = connection_codecs.clone();
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .add_packet_codec(WsIoPacketCodec::Msgpack)
+        .on_ready(move |ctx| {
+            let connection_codecs = connection_codecs_clone.clone();
+            async move {
+                connection_codecs.lock().push(ctx.packet_codec().name().to_owned());
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let json_client = create_connected_client_with_codec(&ws_url, WsIoPacketCodec::SerdeJson).await;
+    let msgpack_client = create_connected_client_with_codec(&ws_url, WsIoPacketCodec::Msgpack).await;
+
+    let received_count = Arc::new(AtomicUsize::new(0));
+    for client in [&json_client, &msgpack_client] {
+        let received_count = received_count.clone();
+        client.on("greet", move |_ctx, message: Arc<String>| {
+            let received_count = received_count.clone();
+            async move {
+                assert_eq!(message.as_str(), "hello");
+                received_count.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+    }
+
+    wait_for_condition(|| connection_codecs.lock().len() == 2)
+        .await
+        .expect("both connections should become ready");
+
+    let mut codec_names = connection_codecs.lock().clone();
+    codec_names.sort();
+    assert_eq!(codec_names, ["json", "msgpack"]);
+
+    namespace.emit("greet", Some(&"hello")).await.unwrap();
+
+    wait_for_condition(|| received_count.load(Ordering::SeqCst) == 2)
+        .await
+        .expect("clients with different codecs should both receive the broadcast");
+
+    cleanup_e2e(vec![json_client, msgpack_client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_codec_not_accepted_by_namespace() {
+    let (server_task, server, ws_url) = setup_server().await;
+    server.new_namespace_builder(TEST_NAMESPACE).register().unwrap();
+
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .packet_codec(WsIoPacketCodec::Msgpack)
+        .build();
+
+    client.connect().await;
+    sleep(Duration::from_millis(200)).await;
+    assert!(!client.is_session_ready());
+
+    cleanup_e2e(vec![client], server_task).await;
+}
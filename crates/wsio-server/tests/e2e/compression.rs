@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use wsio_client::WsIoClient;
+use wsio_server::{
+    WsIoServer,
+    core::{
+        packet::codecs::WsIoPacketCodec,
+        protocol::WsIoProtocolCapabilities,
+    },
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    setup_server,
+    wait_for_client_ready,
+};
+
+async fn create_client(
+    ws_url: &str,
+    packet_codec: WsIoPacketCodec,
+    compression_threshold: Option<usize>,
+) -> (WsIoClient, Arc<Mutex<WsIoProtocolCapabilities>>) {
+    let capabilities = Arc::new(Mutex::new(WsIoProtocolCapabilities::empty()));
+    let capabilities_clone = capabilities.clone();
+    let mut builder = WsIoClient::builder(ws_url)
+        .unwrap()
+        .packet_codec(packet_codec)
+        .on_session_ready(move |session| {
+            let capabilities = capabilities_clone.clone();
+            async move {
+                *capabilities.lock() = session.protocol_capabilities();
+                Ok(())
+            }
+        });
+
+    if let Some(compression_threshold) = compression_threshold {
+        builder = builder.compression_threshold(compression_threshold);
+    }
+
+    let client = builder.build();
+    client.connect().await;
+    wait_for_client_ready(&client).await;
+    (client, capabilities)
+}
+
+fn register_echo_namespace(server: &WsIoServer) {
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .add_packet_codec(WsIoPacketCodec::Msgpack)
+        .compression_threshold(64)
+        .on_connect(|ctx| async move {
+            ctx.on_with_ack("echo", |_ctx, data: Arc<String>| async move { Ok((*data).clone()) });
+            ctx.on_with_ack("capabilities", |ctx, _data: Arc<()>| async move {
+                Ok(ctx.protocol_capabilities().bits())
+            });
+
+            Ok(())
+        })
+        .register()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_e2e_compressed_round_trip() {
+    let (server_task, server, ws_url) = setup_server().await;
+    register_echo_namespace(&server);
+
+    let mut clients = Vec::new();
+    for packet_codec in [WsIoPacketCodec::SerdeJson, WsIoPacketCodec::Msgpack] {
+        let (client, capabilities) = create_client(&ws_url, packet_codec, Some(64)).await;
+        assert!(capabilities.lock().contains(WsIoProtocolCapabilities::COMPRESSION));
+
+        let server_capabilities: u32 = client.emit_with_ack("capabilities", Some(&())).await.unwrap();
+        assert!(
+            WsIoProtocolCapabilities::from_bits(server_capabilities).contains(WsIoProtocolCapabilities::COMPRESSION)
+        );
+
+        // Large packets are deflated both ways, small ones stay plain
+        for message in ["ws.io ".repeat(4096), "hi".to_owned()] {
+            let echoed: String = client.emit_with_ack("echo", Some(&message)).await.unwrap();
+            assert_eq!(echoed, message);
+        }
+
+        clients.push(client);
+    }
+
+    cleanup_e2e(clients, server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_compression_requires_both_sides() {
+    let (server_task, server, ws_url) = setup_server().await;
+    register_echo_namespace(&server);
+
+    let (client, capabilities) = create_client(&ws_url, WsIoPacketCodec::Msgpack, None).await;
+    assert!(!capabilities.lock().contains(WsIoProtocolCapabilities::COMPRESSION));
+
+    let message = "ws.io ".repeat(4096);
+    let echoed: String = client.emit_with_ack("echo", Some(&message)).await.unwrap();
+    assert_eq!(echoed, message);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
@@ -0,0 +1,151 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+    time::Duration,
+};
+
+use anyhow::bail;
+use parking_lot::Mutex;
+use tokio::time::sleep;
+use wsio_client::WsIoClient;
+use wsio_server::core::{
+    error::connect::{
+        WsIoConnectError,
+        WsIoConnectErrorAction,
+    },
+    value::WsIoValue,
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_middleware_rejection_reaches_client_and_stops_reconnecting() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let attempts_clone = attempts.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .with_middleware(move |_ctx| {
+            attempts_clone.fetch_add(1, Ordering::SeqCst);
+            async {
+                Err(WsIoConnectError::new("unauthorized", "Invalid token")
+                    .with_data(WsIoValue::String("renew".into()))
+                    .into())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let connect_errors = Arc::new(Mutex::new(Vec::new()));
+    let connect_errors_clone = connect_errors.clone();
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .reconnect_delay(Duration::from_millis(20))
+        .on_connect_error(move |error| {
+            connect_errors_clone.lock().push(error.clone());
+            WsIoConnectErrorAction::Stop
+        })
+        .build();
+
+    client.connect().await;
+
+    wait_for_condition(|| !connect_errors.lock().is_empty())
+        .await
+        .expect("client should receive the rejection");
+
+    let connect_error = connect_errors.lock()[0].clone();
+    assert_eq!(connect_error.code(), "unauthorized");
+    assert_eq!(connect_error.message(), "Invalid token");
+    assert_eq!(connect_error.data(), Some(&WsIoValue::String("renew".into())));
+
+    // Several reconnect delays pass without another attempt
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert!(!client.is_session_ready());
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_handler_failure_is_sent_as_internal_error_and_client_reconnects() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(|_ctx| async { bail!("database password is hunter2") })
+        .register()
+        .unwrap();
+
+    let connect_errors = Arc::new(Mutex::new(Vec::new()));
+    let connect_errors_clone = connect_errors.clone();
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .reconnect_delay(Duration::from_millis(20))
+        .on_connect_error(move |error| {
+            connect_errors_clone.lock().push(error.clone());
+            WsIoConnectErrorAction::Reconnect
+        })
+        .build();
+
+    client.connect().await;
+
+    wait_for_condition(|| connect_errors.lock().len() >= 2)
+        .await
+        .expect("client should keep reconnecting after rejections");
+
+    // The handler error message stays on the server
+    let connect_error = connect_errors.lock()[0].clone();
+    assert_eq!(connect_error.code(), WsIoConnectError::INTERNAL);
+    assert_eq!(connect_error.message(), "Connection rejected");
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_handler_timeout_is_sent_as_timeout_error() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .middleware_execution_timeout(Duration::from_millis(20))
+        .with_middleware(|_ctx| async {
+            sleep(Duration::from_secs(1)).await;
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let connect_errors = Arc::new(Mutex::new(Vec::new()));
+    let connect_errors_clone = connect_errors.clone();
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .on_connect_error(move |error| {
+            connect_errors_clone.lock().push(error.clone());
+            WsIoConnectErrorAction::Stop
+        })
+        .build();
+
+    client.connect().await;
+
+    wait_for_condition(|| !connect_errors.lock().is_empty())
+        .await
+        .expect("client should receive the timeout");
+
+    assert_eq!(
+        connect_errors.lock()[0],
+        WsIoConnectError::new(WsIoConnectError::TIMEOUT, "middleware timed out")
+    );
+
+    cleanup_e2e(vec![client], server_task).await;
+}
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use wsio_server::namespace::matcher::WsIoServerNamespaceMatcher;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_dynamic_namespace_creates_children_on_demand() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let connected_ids = Arc::new(Mutex::new(Vec::new()));
+    let connected_ids_clone = connected_ids.clone();
+    let dynamic_namespace = server
+        .new_dynamic_namespace_builder(WsIoServerNamespaceMatcher::pattern("/workspace/{id}").unwrap())
+        .on_connect(move |ctx| {
+            connected_ids_clone
+                .lock()
+                .push(ctx.namespace_param("id").unwrap().to_owned());
+
+            async { Ok(()) }
+        })
+        .on_with_ack("whoami", |ctx, _data: Arc<()>| async move {
+            Ok(format!(
+                "{}:{}",
+                ctx.namespace().path(),
+                ctx.namespace_param("id").unwrap()
+            ))
+        })
+        .register()
+        .unwrap();
+
+    let first_client = create_connected_client(&ws_url.replace(TEST_NAMESPACE, "/workspace/1")).await;
+    let second_client = create_connected_client(&ws_url.replace(TEST_NAMESPACE, "/workspace/2")).await;
+
+    // Children share the handlers registered on the dynamic namespace
+    let reply: String = first_client.emit_with_ack::<(), _>("whoami", None).await.unwrap();
+    assert_eq!(reply, "/workspace/1:1");
+    let reply: String = second_client.emit_with_ack::<(), _>("whoami", None).await.unwrap();
+    assert_eq!(reply, "/workspace/2:2");
+
+    let mut connected_ids = connected_ids.lock().clone();
+    connected_ids.sort();
+    assert_eq!(connected_ids, ["1", "2"]);
+    assert_eq!(dynamic_namespace.children().len(), 2);
+    assert_eq!(server.namespace_count(), 1);
+
+    // Each child keeps its own connections
+    let first_child = server.of("/workspace/1").unwrap();
+    assert_eq!(first_child.connection_count(), 1);
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    for (client, name) in [(&first_client, "first"), (&second_client, "second")] {
+        let received = received.clone();
+        client.on("notice", move |_ctx, _data: Arc<()>| {
+            received.lock().push(name);
+            async { Ok(()) }
+        });
+    }
+
+    first_child.emit::<()>("notice", None).await.unwrap();
+    wait_for_condition(|| !received.lock().is_empty())
+        .await
+        .expect("the first client should receive the child broadcast");
+
+    server.emit::<()>("notice", None).await.unwrap();
+    wait_for_condition(|| received.lock().len() == 3)
+        .await
+        .expect("server broadcasts should reach every child");
+
+    let mut received = received.lock().clone();
+    received.sort();
+    assert_eq!(received, ["first", "first", "second"]);
+
+    cleanup_e2e(vec![first_client, second_client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_predicate_dynamic_namespace() {
+    let (server_task, server, ws_url) = setup_server().await;
+    server
+        .new_dynamic_namespace_builder(WsIoServerNamespaceMatcher::predicate("even rooms", |path| {
+            path.strip_prefix("/room-")
+                .and_then(|id| id.parse::<u32>().ok())
+                .is_some_and(|id| id % 2 == 0)
+        }))
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url.replace(TEST_NAMESPACE, "/room-2")).await;
+    assert_eq!(server.of("/room-2").unwrap().connection_count(), 1);
+    assert!(server.of("even rooms").unwrap().is_dynamic());
+    assert!(server.of("/room-3").is_none());
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_dynamic_namespace_drops_child_after_last_connection() {
+    let (server_task, server, ws_url) = setup_server().await;
+    let dynamic_namespace = server
+        .new_dynamic_namespace_builder(WsIoServerNamespaceMatcher::pattern("/workspace/{id}").unwrap())
+        .register()
+        .unwrap();
+
+    let child_url = ws_url.replace(TEST_NAMESPACE, "/workspace/1");
+    let first_client = create_connected_client(&child_url).await;
+    let second_client = create_connected_client(&child_url).await;
+    assert_eq!(server.of("/workspace/1").unwrap().connection_count(), 2);
+
+    first_client.disconnect().await;
+    wait_for_condition(|| {
+        server
+            .of("/workspace/1")
+            .is_some_and(|child| child.connection_count() == 1)
+    })
+    .await
+    .expect("the child should keep its remaining connection");
+
+    second_client.disconnect().await;
+    wait_for_condition(|| dynamic_namespace.children().is_empty())
+        .await
+        .expect("the child should be dropped after its last connection closes");
+
+    assert!(server.of("/workspace/1").is_none());
+
+    cleanup_e2e(vec![first_client, second_client], server_task).await;
+}
@@ -0,0 +1,185 @@
+use std::{
+    future::pending,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::bail;
+use parking_lot::Mutex;
+use tokio::time::sleep;
+use wsio_client::WsIoClient;
+use wsio_server::core::error::WsIoErrorKind;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_server_on_error_receives_handler_and_decode_failures() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let connection_id = Arc::new(Mutex::new(None));
+    let connection_id_clone = connection_id.clone();
+    let reported_errors = Arc::new(Mutex::new(Vec::new()));
+    let reported_errors_clone = reported_errors.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            *connection_id_clone.lock() = Some(ctx.id());
+            ctx.on("fail", |_ctx, _data: Arc<()>| async { bail!("boom") });
+            ctx.on("panic", |_ctx, _data: Arc<()>| async { panic!("handler panicked") });
+            ctx.on("number", |_ctx, _data: Arc<u64>| async { Ok(()) });
+            async { Ok(()) }
+        })
+        .on_error(move |error| {
+            let kind = match error.kind() {
+                WsIoErrorKind::Decode(_) => "decode",
+                WsIoErrorKind::Handler(error) => {
+                    assert_eq!(error.to_string(), "boom");
+                    "handler"
+                },
+                WsIoErrorKind::HandlerPanic(message) => {
+                    assert_eq!(message, "handler panicked");
+                    "panic"
+                },
+                kind => panic!("Unexpected error kind: {kind}"),
+            };
+
+            reported_errors_clone
+                .lock()
+                .push((error.connection_id(), error.event().map(str::to_owned), kind));
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+
+    client.emit::<()>("fail", None).await.unwrap();
+    client.emit::<()>("panic", None).await.unwrap();
+    client.emit("number", Some(&"not a number")).await.unwrap();
+
+    wait_for_condition(|| reported_errors.lock().len() == 3)
+        .await
+        .expect("server should report every failure");
+
+    let connection_id = *connection_id.lock();
+    let mut reported_errors = reported_errors.lock().clone();
+    reported_errors.sort_by_key(|(_, _, kind)| *kind);
+    assert_eq!(
+        reported_errors,
+        [
+            (connection_id, Some("number".to_owned()), "decode"),
+            (connection_id, Some("fail".to_owned()), "handler"),
+            (connection_id, Some("panic".to_owned()), "panic"),
+        ]
+    );
+
+    // The connection survives failing handlers
+    assert!(client.is_session_ready());
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_client_on_error_receives_handler_failures() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_ready(|ctx| async move { ctx.emit::<()>("fail", None).await })
+        .register()
+        .unwrap();
+
+    let reported_errors = Arc::new(Mutex::new(Vec::new()));
+    let reported_errors_clone = reported_errors.clone();
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .on_error(move |error| reported_errors_clone.lock().push(error.to_string()))
+        .build();
+
+    client.on("fail", |_session, _data: Arc<()>| async { bail!("client boom") });
+    client.connect().await;
+
+    wait_for_condition(|| !reported_errors.lock().is_empty())
+        .await
+        .expect("client should report the failing handler");
+
+    assert_eq!(*reported_errors.lock(), ["[fail] handler failed: client boom"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_server_cuts_off_handlers_after_their_timeout() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let reported_errors = Arc::new(Mutex::new(Vec::new()));
+    let reported_errors_clone = reported_errors.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .event_handler_timeout(Duration::from_millis(50))
+        .handler_timeout("slow", Duration::from_secs(1))
+        .on_with_ack("stuck", |_ctx, _data: Arc<()>| async {
+            pending::<()>().await;
+            Ok("never")
+        })
+        .on_with_ack("slow", |_ctx, _data: Arc<()>| async {
+            sleep(Duration::from_millis(100)).await;
+            Ok("done")
+        })
+        .on_error(move |error| {
+            assert!(matches!(error.kind(), WsIoErrorKind::Timeout(_)));
+            reported_errors_clone.lock().push(error.event().map(str::to_owned));
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+
+    // The per-event timeout lets `slow` outlive the namespace default
+    let reply: String = client.emit_with_ack::<(), _>("slow", None).await.unwrap();
+    assert_eq!(reply, "done");
+
+    client.emit::<()>("stuck", None).await.unwrap();
+    wait_for_condition(|| !reported_errors.lock().is_empty())
+        .await
+        .expect("server should report the stuck handler");
+
+    assert_eq!(*reported_errors.lock(), [Some("stuck".to_owned())]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_client_cuts_off_handlers_after_their_timeout() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_ready(|ctx| async move { ctx.emit::<()>("stuck", None).await })
+        .register()
+        .unwrap();
+
+    let reported_errors = Arc::new(Mutex::new(Vec::new()));
+    let reported_errors_clone = reported_errors.clone();
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .event_handler_timeout(Duration::from_millis(50))
+        .on_error(move |error| reported_errors_clone.lock().push(error.to_string()))
+        .build();
+
+    client.on("stuck", |_session, _data: Arc<()>| pending());
+    client.connect().await;
+
+    wait_for_condition(|| !reported_errors.lock().is_empty())
+        .await
+        .expect("client should report the stuck handler");
+
+    assert_eq!(*reported_errors.lock(), ["[stuck] handler timed out after 50ms"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
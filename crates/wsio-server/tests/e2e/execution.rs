@@ -0,0 +1,93 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::time::sleep;
+use wsio_server::core::event::execution::WsIoHandlerExecutionMode;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_sequential_handlers_preserve_event_order() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let received_steps = Arc::new(Mutex::new(Vec::new()));
+    let received_steps_clone = received_steps.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .handler_execution_mode(WsIoHandlerExecutionMode::Sequential)
+        .on_connect(move |ctx| {
+            let received_steps = received_steps_clone.clone();
+            async move {
+                ctx.on("step", move |_ctx, step: Arc<u64>| {
+                    let received_steps = received_steps.clone();
+                    async move {
+                        // Earlier steps sleep longer, so concurrent handlers would reorder them
+                        sleep(Duration::from_millis(5 * (10 - *step))).await;
+                        received_steps.lock().push(*step);
+                        Ok(())
+                    }
+                });
+
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    for step in 0..10u64 {
+        client.emit("step", Some(&step)).await.unwrap();
+    }
+
+    wait_for_condition(|| received_steps.lock().len() == 10)
+        .await
+        .expect("server should handle every step");
+
+    assert_eq!(*received_steps.lock(), (0..10).collect::<Vec<_>>());
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_namespace_execution_mode_per_event() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let received_steps = Arc::new(Mutex::new(Vec::new()));
+    let received_steps_clone = received_steps.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .execution_mode("step", WsIoHandlerExecutionMode::Sequential)
+        .on("step", move |_ctx, step: Arc<u64>| {
+            let received_steps = received_steps_clone.clone();
+            async move {
+                // Earlier steps sleep longer, so concurrent handlers would reorder them
+                sleep(Duration::from_millis(5 * (10 - *step))).await;
+                received_steps.lock().push(*step);
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    for step in 0..10u64 {
+        client.emit("step", Some(&step)).await.unwrap();
+    }
+
+    wait_for_condition(|| received_steps.lock().len() == 10)
+        .await
+        .expect("server should handle every step");
+
+    assert_eq!(*received_steps.lock(), (0..10).collect::<Vec<_>>());
+
+    cleanup_e2e(vec![client], server_task).await;
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use wsio_server::{
+    connection::WsIoServerConnection,
+    core::{
+        error::WsIoErrorKind,
+        event::extract::{
+            WsIoAck,
+            WsIoData,
+            WsIoEventName,
+        },
+    },
+    extract::WsIoExtension,
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+struct UserId(u64);
+
+#[tokio::test]
+async fn test_e2e_extract_handler_resolves_arguments_and_replies_to_ack() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let received_events = Arc::new(Mutex::new(Vec::new()));
+    let received_events_clone = received_events.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            ctx.extensions().insert(UserId(42));
+            let received_events = received_events_clone.clone();
+            ctx.on_extract(
+                "rename",
+                move |ack: WsIoAck<WsIoServerConnection>,
+                      WsIoExtension(user_id): WsIoExtension<UserId>,
+                      WsIoData(name): WsIoData<String>,
+                      WsIoEventName(event): WsIoEventName| {
+                    received_events.lock().push((event, user_id.0, name.clone()));
+                    async move { ack.reply(&format!("{} is now {name}", user_id.0)).await }
+                },
+            );
+
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let reply: String = client.emit_with_ack("rename", Some(&"alice")).await.unwrap();
+
+    assert_eq!(reply, "42 is now alice");
+    assert_eq!(*received_events.lock(), [(Arc::from("rename"), 42, "alice".to_owned())]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_extract_failure_skips_handler_and_reports_error() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let handled_clone = handled.clone();
+    let reported_errors = Arc::new(Mutex::new(Vec::new()));
+    let reported_errors_clone = reported_errors.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            let handled = handled_clone.clone();
+            ctx.on_extract(
+                "profile",
+                move |ctx: Arc<WsIoServerConnection>, user_id: Option<WsIoExtension<UserId>>| {
+                    handled.lock().push((ctx.id(), user_id.is_some()));
+                    async { Ok(()) }
+                },
+            );
+
+            ctx.on_extract("profile", |_user_id: WsIoExtension<UserId>| async {
+                panic!("extension should be missing")
+            });
+
+            async { Ok(()) }
+        })
+        .on_error(move |error| {
+            assert!(matches!(error.kind(), WsIoErrorKind::Extract(_)));
+            reported_errors_clone.lock().push(error.to_string());
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    client.emit::<()>("profile", None).await.unwrap();
+
+    wait_for_condition(|| !handled.lock().is_empty() && !reported_errors.lock().is_empty())
+        .await
+        .expect("one handler should run and the other be reported");
+
+    assert!(!handled.lock()[0].1);
+    let reported_error = reported_errors.lock()[0].clone();
+    assert!(reported_error.contains("failed to extract handler arguments: Missing connection extension"));
+    assert!(reported_error.ends_with("::UserId"));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
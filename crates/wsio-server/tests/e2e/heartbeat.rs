@@ -0,0 +1,145 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use parking_lot::Mutex;
+use tokio::time::sleep;
+use wsio_client::WsIoClient;
+use wsio_server::WsIoServer;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    setup_server,
+    wait_for_client_ready,
+    wait_for_condition,
+};
+
+async fn connect_client(ws_url: &str, ping_interval: Duration) -> WsIoClient {
+    let client = WsIoClient::builder(ws_url)
+        .unwrap()
+        .ping_interval(ping_interval)
+        .build();
+
+    client.connect().await;
+    wait_for_client_ready(&client).await;
+    client
+}
+
+fn register_counting_namespace(server: &WsIoServer, ping_interval: Option<Duration>) -> Arc<AtomicUsize> {
+    let disconnect_count = Arc::new(AtomicUsize::new(0));
+    let disconnect_count_clone = disconnect_count.clone();
+    let mut builder = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .heartbeat_interval(Duration::from_millis(50))
+        .heartbeat_max_missed(3);
+
+    if let Some(ping_interval) = ping_interval {
+        builder = builder.ping_interval(ping_interval);
+    }
+
+    builder
+        .on_disconnect(move |_ctx| {
+            disconnect_count_clone.fetch_add(1, Ordering::SeqCst);
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    disconnect_count
+}
+
+#[tokio::test]
+async fn test_e2e_heartbeat_timeout_closes_silent_connections() {
+    let (server_task, server, ws_url) = setup_server().await;
+    let disconnect_count = register_counting_namespace(&server, None);
+
+    // The client heartbeat comes far too late to keep the connection alive
+    let client = connect_client(&ws_url, Duration::from_secs(10)).await;
+
+    wait_for_condition(|| disconnect_count.load(Ordering::SeqCst) > 0)
+        .await
+        .expect("server should drop the silent connection");
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_heartbeats_and_server_pings_keep_connections_alive() {
+    let (server_task, server, ws_url) = setup_server().await;
+    let disconnect_count = register_counting_namespace(&server, Some(Duration::from_millis(40)));
+
+    // One client sends its own heartbeats, the other only answers server pings
+    let heartbeat_client = connect_client(&ws_url, Duration::from_millis(40)).await;
+    let pong_client = connect_client(&ws_url, Duration::from_secs(10)).await;
+
+    sleep(Duration::from_millis(500)).await;
+
+    assert_eq!(disconnect_count.load(Ordering::SeqCst), 0);
+    assert_eq!(server.connection_count(), 2);
+    assert!(heartbeat_client.is_session_ready());
+    assert!(pong_client.is_session_ready());
+
+    cleanup_e2e(vec![heartbeat_client, pong_client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_pings_measure_latency_and_clock_offset() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let connection = Arc::new(Mutex::new(None));
+    let connection_clone = connection.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            *connection_clone.lock() = Some(ctx.clone());
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    let session = Arc::new(Mutex::new(None));
+    let session_clone = session.clone();
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .ping_interval(Duration::from_millis(30))
+        .on_session_ready(move |session| {
+            *session_clone.lock() = Some(session);
+            async { Ok(()) }
+        })
+        .build();
+
+    client.connect().await;
+    wait_for_client_ready(&client).await;
+
+    // The server learns the latency from the ping after the first answered one
+    let connection = connection.lock().clone().unwrap();
+    wait_for_condition(|| client.latency().is_some() && connection.latency().is_some())
+        .await
+        .expect("both sides should know the latency");
+
+    assert!(client.latency().unwrap() < Duration::from_secs(1));
+    assert!(connection.latency().unwrap() < Duration::from_secs(1));
+
+    // Both ends share a clock, so the offset only reflects the link asymmetry
+    let session = session.lock().clone().unwrap();
+    assert!(session.clock_offset_micros().unwrap().abs() < 1_000_000);
+
+    let now = SystemTime::now();
+    let local_time = session.to_local_time(now).unwrap();
+    let drift = local_time.duration_since(now).unwrap_or_else(|err| err.duration());
+
+    assert!(drift < Duration::from_secs(1));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
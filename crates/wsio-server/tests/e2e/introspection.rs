@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_room_and_connection_introspection() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(|ctx| async move {
+            ctx.on("join_room", |event_ctx, room: Arc<String>| async move {
+                event_ctx.join([room.as_str()]);
+                Ok(())
+            });
+
+            Ok(())
+        })
+        .register()
+        .unwrap();
+
+    let client_a = create_connected_client(&ws_url).await;
+    let client_b = create_connected_client(&ws_url).await;
+    client_a.emit("join_room", Some(&"lobby")).await.unwrap();
+    client_a.emit("join_room", Some(&"admins")).await.unwrap();
+    client_b.emit("join_room", Some(&"lobby")).await.unwrap();
+
+    wait_for_condition(|| namespace.room_size("lobby") == 2 && namespace.room_size("admins") == 1)
+        .await
+        .expect("clients should join their rooms");
+
+    let mut rooms = namespace.rooms();
+    rooms.sort();
+    assert_eq!(rooms, ["admins", "lobby"]);
+    assert_eq!(namespace.room_size("missing"), 0);
+
+    // Connections are listed once even when they joined several of the rooms
+    let connections = namespace.connections_in(["lobby", "admins"]);
+    assert_eq!(connections.len(), 2);
+
+    let admin = namespace.connections_in(["admins"]).pop().unwrap();
+    let mut admin_rooms = admin.rooms();
+    admin_rooms.sort();
+    assert_eq!(admin_rooms, ["admins", "lobby"]);
+
+    // Lookups by id work from the namespace and across the server
+    assert_eq!(namespace.connection(admin.id()).unwrap().id(), admin.id());
+    assert_eq!(server.connection(admin.id()).unwrap().id(), admin.id());
+    assert!(server.connection(u64::MAX).is_none());
+
+    // Disconnected connections leave their rooms and can no longer be looked up
+    client_a.disconnect().await;
+    wait_for_condition(|| namespace.room_size("admins") == 0)
+        .await
+        .expect("disconnected connection should leave its rooms");
+
+    assert!(server.connection(admin.id()).is_none());
+    assert_eq!(namespace.rooms(), ["lobby"]);
+
+    cleanup_e2e(vec![client_b], server_task).await;
+}
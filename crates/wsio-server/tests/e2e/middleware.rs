@@ -0,0 +1,325 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::bail;
+use parking_lot::Mutex;
+use tokio::time::{
+    Instant,
+    sleep,
+};
+use wsio_server::{
+    connection::WsIoServerConnection,
+    core::{
+        error::WsIoErrorKind,
+        event::execution::WsIoHandlerExecutionMode,
+        packet::WsIoPacket,
+        traits::ack::sender::AckSender,
+    },
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_setup_middlewares_run_in_order() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let first_calls = calls.clone();
+    let second_calls = calls.clone();
+    let connect_calls = calls.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .with_middleware(move |_ctx| {
+            first_calls.lock().push("first");
+            async { Ok(()) }
+        })
+        .with_middleware(move |_ctx| {
+            second_calls.lock().push("second");
+            async { Ok(()) }
+        })
+        .on_connect(move |_ctx| {
+            connect_calls.lock().push("on_connect");
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    assert_eq!(*calls.lock(), ["first", "second", "on_connect"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_event_middleware_rejects_mutates_and_short_circuits() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let seen_events = Arc::new(Mutex::new(Vec::new()));
+    let seen_events_clone = seen_events.clone();
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let handled_clone = handled.clone();
+    let reported_errors = Arc::new(Mutex::new(Vec::new()));
+    let reported_errors_clone = reported_errors.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on("chat", move |_ctx, text: Arc<String>| {
+            handled_clone.lock().push(text.to_string());
+            async { Ok(()) }
+        })
+        .on_with_ack("ping", |_ctx, _data: Arc<()>| async { Ok("handler") })
+        .with_event_middleware(move |_ctx, event, next| {
+            // The outer middleware sees every event, including rejected ones
+            seen_events_clone.lock().push(event.name().to_owned());
+            next.run(event)
+        })
+        .with_event_middleware(|ctx, mut event, next| async move {
+            match event.name() {
+                "forbidden" => bail!("not allowed"),
+                "ping" => {
+                    if let Some(ack_id) = event.ack_id() {
+                        ctx.send_ack(ack_id, Some(ctx.packet_codec().encode_data(&"middleware")?))
+                            .await?;
+                    }
+
+                    Ok(())
+                },
+                "shout" => {
+                    let text: String = match event.data() {
+                        Some(data) => ctx.packet_codec().decode_data_as(data)?,
+                        None => bail!("shout without text"),
+                    };
+
+                    event.set_name("chat");
+                    event.set_data(Some(ctx.packet_codec().encode_data(&text.to_uppercase())?.into()));
+                    next.run(event).await
+                },
+                _ => next.run(event).await,
+            }
+        })
+        .on_error(move |error| {
+            assert!(matches!(error.kind(), WsIoErrorKind::Middleware(_)));
+            reported_errors_clone.lock().push(error.to_string());
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    client.emit("forbidden", Some(&"x")).await.unwrap();
+    client.emit("shout", Some(&"hello")).await.unwrap();
+    client.emit("chat", Some(&"plain")).await.unwrap();
+    let reply: String = client.emit_with_ack::<(), _>("ping", None).await.unwrap();
+    assert_eq!(reply, "middleware");
+
+    wait_for_condition(|| handled.lock().len() == 2 && !reported_errors.lock().is_empty())
+        .await
+        .expect("events should pass through the middleware stack");
+
+    assert_eq!(*seen_events.lock(), ["forbidden", "shout", "chat", "ping"]);
+    assert_eq!(*handled.lock(), ["HELLO", "plain"]);
+    assert_eq!(reported_errors.lock().len(), 1);
+    assert!(reported_errors.lock()[0].ends_with("rejected by middleware: not allowed"));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_event_middleware_for_pattern_sees_matching_events_only() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let seen_events = Arc::new(Mutex::new(Vec::new()));
+    let seen_events_clone = seen_events.clone();
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let mut builder = server.new_namespace_builder(TEST_NAMESPACE);
+    for event in ["chat:message", "chat:typing", "status"] {
+        let handled = handled.clone();
+        builder = builder.on(event, move |_ctx, _data: Arc<()>| {
+            handled.lock().push(event);
+            async { Ok(()) }
+        });
+    }
+
+    builder
+        .with_event_middleware_for("chat:*", move |_ctx, event, next| {
+            seen_events_clone.lock().push(event.name().to_owned());
+            next.run(event)
+        })
+        .with_event_middleware_for("ping", |_ctx, _event, _next| async { bail!("no pings") })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    client.emit::<()>("chat:message", None).await.unwrap();
+    client.emit::<()>("status", None).await.unwrap();
+    client.emit::<()>("chat:typing", None).await.unwrap();
+
+    // The rejected ack fails right away instead of waiting out the ack timeout
+    let started_at = Instant::now();
+    let result = client.emit_with_ack::<(), ()>("ping", None).await;
+    assert!(started_at.elapsed() < Duration::from_secs(1));
+    assert!(result.unwrap_err().to_string().contains("failed on the peer: internal"));
+
+    wait_for_condition(|| handled.lock().len() == 3)
+        .await
+        .expect("every event but the rejected one should reach its handlers");
+
+    assert_eq!(*seen_events.lock(), ["chat:message", "chat:typing"]);
+    assert_eq!(*handled.lock(), ["chat:message", "status", "chat:typing"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_slow_event_middleware_does_not_block_the_read_loop() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let connection = Arc::new(Mutex::new(None));
+    let connection_clone = connection.clone();
+    let mut builder = server.new_namespace_builder(TEST_NAMESPACE);
+    for event in ["after", "slow"] {
+        let handled = handled.clone();
+        builder = builder.on(event, move |_ctx, _data: Arc<()>| {
+            handled.lock().push(event);
+            async { Ok(()) }
+        });
+    }
+
+    builder
+        .on_connect(move |ctx| {
+            *connection_clone.lock() = Some(ctx);
+            async { Ok(()) }
+        })
+        .with_event_middleware_for("slow", |_ctx, event, next| async move {
+            sleep(Duration::from_millis(500)).await;
+            next.run(event).await
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    client.on_with_ack("whoami", |_session, _data: Arc<()>| async { Ok("client") });
+    client.emit::<()>("slow", None).await.unwrap();
+    client.emit::<()>("after", None).await.unwrap();
+
+    // The client's ack is read while the middleware still holds up `slow`
+    let connection = connection.lock().clone().unwrap();
+    let started_at = Instant::now();
+    let reply: String = connection.emit_with_ack::<(), _>("whoami", None).await.unwrap();
+    assert_eq!(reply, "client");
+    assert!(started_at.elapsed() < Duration::from_millis(300));
+    assert!(handled.lock().is_empty());
+
+    wait_for_condition(|| handled.lock().len() == 2)
+        .await
+        .expect("queued events should reach their handlers");
+
+    assert_eq!(*handled.lock(), ["slow", "after"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_slow_handler_behind_passing_event_middleware_still_runs() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .event_middleware_timeout(Duration::from_millis(100))
+        .handler_execution_mode(WsIoHandlerExecutionMode::Bounded(1))
+        .on_with_ack("slow", |_ctx, data: Arc<u32>| async move {
+            sleep(Duration::from_millis(300)).await;
+            Ok(*data * 2)
+        })
+        .with_event_middleware(|_ctx, event, next| next.run(event))
+        .register()
+        .unwrap();
+
+    // The second event waits for the first handler to free its slot longer
+    // than the middleware timeout, which only covers the middleware call
+    let client = create_connected_client(&ws_url).await;
+    let (first, second) = tokio::join!(
+        client.emit_with_ack::<_, u32>("slow", Some(&1)),
+        client.emit_with_ack::<_, u32>("slow", Some(&2)),
+    );
+    assert_eq!(first.unwrap(), 2);
+    assert_eq!(second.unwrap(), 4);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_outgoing_interceptors_redact_and_drop_packets() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let audited = Arc::new(Mutex::new(Vec::new()));
+    let audited_clone = audited.clone();
+    let connection = Arc::new(Mutex::new(None));
+    let connection_clone = connection.clone();
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            ctx.intercept_outgoing(|ctx: &WsIoServerConnection, packet: &mut WsIoPacket| {
+                match packet.key.as_deref() {
+                    Some("blocked") => bail!("blocked event"),
+                    Some("secret") => packet.data = Some(ctx.packet_codec().encode_data(&"***")?),
+                    _ => {},
+                }
+
+                Ok(())
+            });
+
+            // Added second, so it audits the packet the redaction left
+            let audited = audited_clone.clone();
+            ctx.intercept_outgoing(move |ctx: &WsIoServerConnection, packet: &mut WsIoPacket| {
+                let data = packet.data.as_deref().unwrap_or_default();
+                audited.lock().push(ctx.packet_codec().decode_data_as::<String>(data)?);
+
+                Ok(())
+            });
+
+            *connection_clone.lock() = Some(ctx.clone());
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_clone = received.clone();
+    client.on_any(move |_session, event| {
+        received_clone
+            .lock()
+            .push((event.name().to_owned(), event.decode::<String>().unwrap().unwrap()));
+
+        async { Ok(()) }
+    });
+
+    let connection = connection.lock().clone().unwrap();
+    assert!(connection.emit("blocked", Some(&"dropped")).await.is_err());
+    connection.emit("secret", Some(&"hunter2")).await.unwrap();
+    namespace.emit("secret", Some(&"broadcast")).await.unwrap();
+    namespace.emit("notice", Some(&"visible")).await.unwrap();
+
+    wait_for_condition(|| received.lock().len() == 3)
+        .await
+        .expect("client should receive the intercepted events");
+
+    // Client handlers run concurrently, so events may be recorded out of order
+    let mut received = received.lock().clone();
+    received.sort();
+    let expected = [("notice", "visible"), ("secret", "***"), ("secret", "***")]
+        .map(|(event, data)| (event.to_owned(), data.to_owned()));
+
+    assert_eq!(received, expected);
+    assert_eq!(*audited.lock(), ["***", "***", "visible"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
@@ -22,9 +22,27 @@ use wsio_server::{
     namespace::WsIoServerNamespace,
 };
 
+mod ack;
 mod broadcast;
+mod codec;
+mod compression;
+mod connect_error;
+mod dynamic_namespace;
+mod error;
+mod execution;
+mod extract;
+mod heartbeat;
+mod introspection;
+mod middleware;
+mod namespace_handlers;
+mod pattern;
 mod ping_pong;
+mod protocol;
+mod raw;
 mod reconnect;
+mod recovery;
+mod state;
+mod typed;
 
 const CLIENT_STATE_TIMEOUT: Duration = Duration::from_secs(2);
 const POLL_INTERVAL: Duration = Duration::from_millis(10);
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use wsio_server::{
+    connection::WsIoServerConnection,
+    core::event::extract::WsIoData,
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    cleanup_server_task,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_namespace_handlers_are_shared_and_overridable() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let handled_clone = handled.clone();
+    let override_connection_id = Arc::new(Mutex::new(None));
+    let override_connection_id_clone = override_connection_id.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on("chat", move |ctx, text: Arc<String>| {
+            handled_clone.lock().push((ctx.id(), format!("namespace {text}")));
+            async { Ok(()) }
+        })
+        .on_extract(
+            "echo",
+            |ctx: Arc<WsIoServerConnection>, WsIoData(text): WsIoData<String>| async move {
+                ctx.emit("echoed", Some(&text)).await
+            },
+        )
+        .on_with_ack("add", |_ctx, numbers: Arc<(u32, u32)>| async move {
+            Ok(numbers.0 + numbers.1)
+        })
+        .on_connect({
+            let handled = handled.clone();
+            move |ctx| {
+                // The first connection replaces the namespace `chat` handler
+                let mut override_connection_id = override_connection_id_clone.lock();
+                if override_connection_id.is_none() {
+                    *override_connection_id = Some(ctx.id());
+                    let handled = handled.clone();
+                    ctx.on("chat", move |ctx, text: Arc<String>| {
+                        handled.lock().push((ctx.id(), format!("connection {text}")));
+                        async { Ok(()) }
+                    });
+                }
+
+                async { Ok(()) }
+            }
+        })
+        .register()
+        .unwrap();
+
+    let overriding_client = create_connected_client(&ws_url).await;
+    let client = create_connected_client(&ws_url).await;
+
+    let echoed = Arc::new(Mutex::new(Vec::new()));
+    let echoed_clone = echoed.clone();
+    client.on("echoed", move |_session, text: Arc<String>| {
+        echoed_clone.lock().push(text.to_string());
+        async { Ok(()) }
+    });
+
+    overriding_client.emit("chat", Some(&"hi")).await.unwrap();
+    client.emit("chat", Some(&"hi")).await.unwrap();
+    client.emit("echo", Some(&"ping")).await.unwrap();
+    let sum: u32 = client.emit_with_ack("add", Some(&(2, 3))).await.unwrap();
+    assert_eq!(sum, 5);
+
+    wait_for_condition(|| handled.lock().len() == 2 && !echoed.lock().is_empty())
+        .await
+        .expect("every event should be handled");
+
+    let override_connection_id = override_connection_id.lock().unwrap();
+    let mut handled = handled.lock().clone();
+    handled.sort_by_key(|(id, _)| *id != override_connection_id);
+    assert_eq!(handled[0], (override_connection_id, "connection hi".to_owned()));
+    assert_eq!(handled[1].1, "namespace hi");
+    assert_eq!(*echoed.lock(), ["ping"]);
+
+    cleanup_e2e(vec![overriding_client, client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_on_disconnect_runs_for_ready_connections() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let disconnected = Arc::new(Mutex::new(Vec::new()));
+    let disconnected_clone = disconnected.clone();
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_disconnect(move |ctx| {
+            disconnected_clone.lock().push((ctx.id(), ctx.is_ready()));
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    wait_for_condition(|| namespace.connection_count() == 1)
+        .await
+        .expect("connection should be registered");
+
+    client.disconnect().await;
+
+    wait_for_condition(|| !disconnected.lock().is_empty())
+        .await
+        .expect("on_disconnect handler should be called");
+
+    // The connection already left the namespace when the handler runs
+    assert_eq!(namespace.connection_count(), 0);
+    assert!(!disconnected.lock()[0].1);
+
+    cleanup_server_task(server_task).await;
+}
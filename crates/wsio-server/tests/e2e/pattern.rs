@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[tokio::test]
+async fn test_e2e_pattern_and_any_handlers() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let server_events = Arc::new(Mutex::new(Vec::new()));
+    let server_events_clone = server_events.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            let server_events = server_events_clone.clone();
+            async move {
+                ctx.on_pattern("chat:*", move |ctx, raw_event| {
+                    let server_events = server_events.clone();
+                    async move {
+                        let message = raw_event.decode::<String>()?.unwrap_or_default();
+                        server_events.lock().push(format!("{} {message}", raw_event.name()));
+
+                        ctx.emit("echo", Some(&message)).await
+                    }
+                });
+
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let client_events = Arc::new(Mutex::new(Vec::new()));
+    let client_events_clone = client_events.clone();
+    client.on_any(move |_session, raw_event| {
+        let client_events = client_events_clone.clone();
+        async move {
+            client_events.lock().push(raw_event.name().to_owned());
+            Ok(())
+        }
+    });
+
+    client.emit("chat:message", Some(&"hello")).await.unwrap();
+    client.emit("status", Some(&"ignored")).await.unwrap();
+
+    wait_for_condition(|| client_events.lock().len() == 1)
+        .await
+        .expect("client on_any handler should receive the echoed event");
+
+    assert_eq!(*server_events.lock(), ["chat:message hello"]);
+    assert_eq!(*client_events.lock(), ["echo"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
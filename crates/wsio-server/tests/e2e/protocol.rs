@@ -0,0 +1,75 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::{
+    sync::{
+        Mutex,
+        oneshot::channel,
+    },
+    time::timeout,
+};
+use wsio_client::WsIoClient;
+use wsio_server::core::protocol::{
+    WSIO_PROTOCOL_VERSION,
+    WsIoProtocolCapabilities,
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    setup_server,
+};
+
+#[tokio::test]
+async fn test_e2e_protocol_negotiation() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let (server_tx, server_rx) = channel();
+    let server_tx = Arc::new(Mutex::new(Some(server_tx)));
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_ready(move |ctx| {
+            let server_tx = server_tx.clone();
+            async move {
+                if let Some(sender) = server_tx.lock().await.take() {
+                    let _ = sender.send((ctx.protocol_version(), ctx.protocol_capabilities()));
+                }
+
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let (client_tx, client_rx) = channel();
+    let client_tx = Arc::new(Mutex::new(Some(client_tx)));
+    let client = WsIoClient::builder(ws_url.as_str())
+        .unwrap()
+        .on_session_ready(move |session| {
+            let client_tx = client_tx.clone();
+            async move {
+                if let Some(sender) = client_tx.lock().await.take() {
+                    let _ = sender.send((session.protocol_version(), session.protocol_capabilities()));
+                }
+
+                Ok(())
+            }
+        })
+        .build();
+
+    client.connect().await;
+
+    for rx in [server_rx, client_rx] {
+        let (version, capabilities) = timeout(Duration::from_secs(2), rx)
+            .await
+            .expect("Test timed out waiting for negotiated protocol")
+            .expect("Channel closed");
+
+        assert_eq!(version, Some(WSIO_PROTOCOL_VERSION));
+        assert!(capabilities.contains(WsIoProtocolCapabilities::ACKS));
+    }
+
+    cleanup_e2e(vec![client], server_task).await;
+}
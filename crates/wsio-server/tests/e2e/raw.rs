@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use wsio_server::core::value::WsIoValue;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+#[derive(serde::Serialize)]
+struct RouteRequest {
+    target: &'static str,
+    hops: u32,
+}
+
+#[tokio::test]
+async fn test_e2e_raw_handler_forwards_payload_untouched() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let routed_values = Arc::new(Mutex::new(Vec::new()));
+    let routed_values_clone = routed_values.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            let routed_values = routed_values_clone.clone();
+            async move {
+                ctx.on_raw("route", move |ctx, raw_event| {
+                    let routed_values = routed_values.clone();
+                    async move {
+                        let value = raw_event.decode::<WsIoValue>()?.unwrap_or_default();
+                        routed_values.lock().push(value.clone());
+                        ctx.emit("routed", Some(&value)).await
+                    }
+                });
+
+                Ok(())
+            }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let received_values = Arc::new(Mutex::new(Vec::new()));
+    let received_values_clone = received_values.clone();
+    client.on_raw("routed", move |_session, raw_event| {
+        let received_values = received_values_clone.clone();
+        async move {
+            received_values
+                .lock()
+                .push(raw_event.decode::<WsIoValue>()?.unwrap_or_default());
+            Ok(())
+        }
+    });
+
+    client
+        .emit(
+            "route",
+            Some(&RouteRequest {
+                target: "billing",
+                hops: 2,
+            }),
+        )
+        .await
+        .unwrap();
+
+    wait_for_condition(|| !received_values.lock().is_empty())
+        .await
+        .expect("client raw handler should receive the routed event");
+
+    let received_value = received_values.lock()[0].clone();
+    assert_eq!(received_value, routed_values.lock()[0]);
+    assert_eq!(
+        received_value.get("target").and_then(WsIoValue::as_str),
+        Some("billing")
+    );
+    assert_eq!(received_value.get("hops").and_then(WsIoValue::as_u64), Some(2));
+
+    cleanup_e2e(vec![client], server_task).await;
+}
@@ -0,0 +1,133 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use wsio_client::WsIoClient;
+use wsio_server::{
+    WsIoServer,
+    namespace::WsIoServerNamespace,
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    setup_server,
+    wait_for_condition,
+};
+
+type ConnectionIds = Arc<Mutex<Vec<u64>>>;
+
+/// Registers a namespace that drops silent connections quickly, joining new
+/// connections to `room` and recording every connection id.
+fn register_recovery_namespace(
+    server: &WsIoServer,
+    recovery_window: Duration,
+) -> (Arc<WsIoServerNamespace>, ConnectionIds) {
+    let connection_ids = ConnectionIds::default();
+    let connection_ids_clone = connection_ids.clone();
+    let namespace = server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .heartbeat_interval(Duration::from_millis(100))
+        .recovery_window(recovery_window)
+        .on_connect(move |ctx| {
+            connection_ids_clone.lock().push(ctx.id());
+
+            // Recovered connections get their rooms back without rejoining
+            if !ctx.is_recovered() {
+                ctx.join(["room"]);
+            }
+
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    (namespace, connection_ids)
+}
+
+/// Connects a client that never sends heartbeats in time, recording the
+/// `news` events it receives and whether each of its sessions was recovered.
+async fn connect_silent_client(ws_url: &str) -> (WsIoClient, Arc<Mutex<Vec<String>>>, Arc<Mutex<Vec<bool>>>) {
+    let received_news = Arc::new(Mutex::new(Vec::new()));
+    let received_news_clone = received_news.clone();
+    let recovered_sessions = Arc::new(Mutex::new(Vec::new()));
+    let recovered_sessions_clone = recovered_sessions.clone();
+    let client = WsIoClient::builder(ws_url)
+        .unwrap()
+        .ping_interval(Duration::from_secs(10))
+        .reconnect_delay(Duration::from_millis(300))
+        .on_session_ready(move |session| {
+            recovered_sessions_clone.lock().push(session.is_recovered());
+            async { Ok(()) }
+        })
+        .build();
+
+    client.on("news", move |_session, data: Arc<String>| {
+        received_news_clone.lock().push((*data).clone());
+        async { Ok(()) }
+    });
+
+    client.connect().await;
+    (client, received_news, recovered_sessions)
+}
+
+#[tokio::test]
+async fn test_e2e_dropped_connection_recovers_rooms_and_missed_broadcasts() {
+    let (server_task, server, ws_url) = setup_server().await;
+    let (namespace, connection_ids) = register_recovery_namespace(&server, Duration::from_secs(5));
+    let (client, received_news, recovered_sessions) = connect_silent_client(&ws_url).await;
+
+    wait_for_condition(|| namespace.connection_count() == 1)
+        .await
+        .expect("client should connect");
+
+    // The server drops the silent connection, then broadcasts before the client is back
+    wait_for_condition(|| namespace.connection_count() == 0)
+        .await
+        .expect("server should drop the silent connection");
+
+    namespace.to(["room"]).emit("news", Some(&"missed")).await.unwrap();
+    wait_for_condition(|| recovered_sessions.lock().len() >= 2)
+        .await
+        .expect("client should reconnect");
+
+    wait_for_condition(|| received_news.lock().contains(&"missed".to_owned()))
+        .await
+        .expect("missed broadcast should be replayed");
+
+    assert_eq!(recovered_sessions.lock()[..2], [false, true]);
+    let connection_ids = connection_ids.lock().clone();
+    assert_eq!(connection_ids[0], connection_ids[1]);
+
+    // The recovered connection is back in its room
+    wait_for_condition(|| namespace.connection_count() == 1)
+        .await
+        .expect("recovered connection should be ready");
+
+    namespace.to(["room"]).emit("news", Some(&"live")).await.unwrap();
+    wait_for_condition(|| received_news.lock().contains(&"live".to_owned()))
+        .await
+        .expect("recovered connection should receive room broadcasts");
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_expired_session_reconnects_as_new_connection() {
+    let (server_task, server, ws_url) = setup_server().await;
+    let (_, connection_ids) = register_recovery_namespace(&server, Duration::from_millis(50));
+    let (client, _, recovered_sessions) = connect_silent_client(&ws_url).await;
+
+    wait_for_condition(|| recovered_sessions.lock().len() >= 2)
+        .await
+        .expect("client should reconnect");
+
+    // The session expired before the client came back, so nothing was kept
+    assert_eq!(recovered_sessions.lock()[..2], [false, false]);
+    let connection_ids = connection_ids.lock().clone();
+    assert_ne!(connection_ids[0], connection_ids[1]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
@@ -0,0 +1,55 @@
+use std::sync::{
+    Arc,
+    atomic::{
+        AtomicU64,
+        Ordering,
+    },
+};
+
+use wsio_server::{
+    connection::WsIoServerConnection,
+    core::event::extract::WsIoAck,
+    extract::WsIoState,
+};
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+};
+
+#[derive(Default)]
+struct Counter(AtomicU64);
+
+#[tokio::test]
+async fn test_e2e_handlers_share_namespace_state() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .with_state(Counter::default())
+        .on_extract(
+            "count",
+            |ack: WsIoAck<WsIoServerConnection>, WsIoState(counter): WsIoState<Counter>| async move {
+                ack.reply(&(counter.0.fetch_add(1, Ordering::SeqCst) + 1)).await
+            },
+        )
+        .on_with_ack("peek", |ctx, _data: Arc<()>| async move {
+            Ok(ctx.state::<Counter>().unwrap().0.load(Ordering::SeqCst))
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let other_client = create_connected_client(&ws_url).await;
+
+    let count: u64 = client.emit_with_ack::<(), _>("count", None).await.unwrap();
+    assert_eq!(count, 1);
+    let count: u64 = other_client.emit_with_ack::<(), _>("count", None).await.unwrap();
+    assert_eq!(count, 2);
+    let count: u64 = client.emit_with_ack::<(), _>("peek", None).await.unwrap();
+    assert_eq!(count, 2);
+
+    cleanup_e2e(vec![client, other_client], server_task).await;
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use wsio_server::core::traits::event::definition::WsIoEvent;
+
+use super::{
+    TEST_NAMESPACE,
+    cleanup_e2e,
+    create_connected_client,
+    setup_server,
+    wait_for_condition,
+};
+
+// Shared by client and server, as a common contract crate would be
+#[derive(Debug, Deserialize, PartialEq, Serialize, WsIoEvent)]
+#[wsio(crate = "wsio_server::core", name = "chat:message")]
+struct ChatMessage {
+    room: String,
+    text: String,
+}
+
+#[derive(WsIoEvent)]
+#[wsio(crate = "wsio_server::core", data = u32)]
+struct UnreadCount;
+
+#[tokio::test]
+async fn test_e2e_typed_events_roundtrip() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let received_messages = Arc::new(Mutex::new(Vec::new()));
+    let received_messages_clone = received_messages.clone();
+    server
+        .new_namespace_builder(TEST_NAMESPACE)
+        .on_connect(move |ctx| {
+            let received_messages = received_messages_clone.clone();
+            ctx.on_typed::<ChatMessage, _, _>(move |ctx, message| {
+                let received_messages = received_messages.clone();
+                async move {
+                    let unread_count = {
+                        let mut received_messages = received_messages.lock();
+                        received_messages.push(message.text.clone());
+                        received_messages.len() as u32
+                    };
+
+                    ctx.emit_typed::<UnreadCount>(&unread_count).await
+                }
+            });
+
+            async { Ok(()) }
+        })
+        .register()
+        .unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let unread_counts = Arc::new(Mutex::new(Vec::new()));
+    let unread_counts_clone = unread_counts.clone();
+    client.on_typed::<UnreadCount, _, _>(move |_session, count| {
+        unread_counts_clone.lock().push(*count);
+        async { Ok(()) }
+    });
+
+    for text in ["hello", "world"] {
+        client
+            .emit_typed::<ChatMessage>(&ChatMessage {
+                room: "lobby".into(),
+                text: text.into(),
+            })
+            .await
+            .unwrap();
+    }
+
+    wait_for_condition(|| unread_counts.lock().len() == 2)
+        .await
+        .expect("client should receive both typed replies");
+
+    assert_eq!(*received_messages.lock(), ["hello", "world"]);
+    assert_eq!(*unread_counts.lock(), [1, 2]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}
+
+#[tokio::test]
+async fn test_e2e_typed_broadcast_reaches_untyped_handlers() {
+    let (server_task, server, ws_url) = setup_server().await;
+
+    let namespace = server.new_namespace_builder(TEST_NAMESPACE).register().unwrap();
+
+    let client = create_connected_client(&ws_url).await;
+    let received_messages = Arc::new(Mutex::new(Vec::new()));
+    let received_messages_clone = received_messages.clone();
+    client.on(ChatMessage::NAME, move |_session, message: Arc<ChatMessage>| {
+        received_messages_clone.lock().push(message.text.clone());
+        async { Ok(()) }
+    });
+
+    wait_for_condition(|| namespace.connection_count() == 1)
+        .await
+        .expect("connection should be registered");
+
+    namespace
+        .emit_typed::<ChatMessage>(&ChatMessage {
+            room: "lobby".into(),
+            text: "announcement".into(),
+        })
+        .await
+        .unwrap();
+
+    wait_for_condition(|| !received_messages.lock().is_empty())
+        .await
+        .expect("client should receive the typed broadcast");
+
+    assert_eq!(*received_messages.lock(), ["announcement"]);
+
+    cleanup_e2e(vec![client], server_task).await;
+}